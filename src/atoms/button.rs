@@ -49,7 +49,7 @@ impl Button {
             ButtonVariant::Outline | ButtonVariant::Ghost => None,
         };
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             id: None,
             style: BoxStyle {
                 padding: Some(1),
@@ -60,9 +60,10 @@ impl Button {
             children: vec![VNode::Text(TextNode {
                 content: self.label,
                 style: Default::default(),
+                ..Default::default()
             })],
             handlers: Default::default(),
-        })
+        }))
     }
 }
 
@@ -0,0 +1,54 @@
+//! Link Component
+//!
+//! Clickable text that opens a URL in terminals with OSC 8 hyperlinks.
+
+use crate::core::component::{VNode, Color, NamedColor};
+use crate::primitives::Text;
+
+/// Link component.
+#[derive(Debug, Clone)]
+pub struct Link {
+    label: String,
+    url: String,
+    color: Color,
+    underline: bool,
+}
+
+impl Link {
+    /// Create a link showing `label` and pointing at `url`.
+    pub fn new(label: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            url: url.into(),
+            color: Color::Named(NamedColor::Blue),
+            underline: true,
+        }
+    }
+
+    /// Create a link that shows its own URL.
+    pub fn url(url: impl Into<String>) -> Self {
+        let url = url.into();
+        Self::new(url.clone(), url)
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    pub fn build(self) -> VNode {
+        let text = Text::new(self.label).color(self.color).link(self.url);
+        if self.underline { text.underline() } else { text }.build()
+    }
+}
+
+impl From<Link> for VNode {
+    fn from(link: Link) -> VNode {
+        link.build()
+    }
+}
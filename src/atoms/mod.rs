@@ -9,11 +9,13 @@ mod button;
 mod text_input;
 mod spinner;
 mod progress;
+mod link;
 
 pub use button::Button;
 pub use text_input::TextInput;
 pub use spinner::Spinner;
 pub use progress::ProgressBar;
+pub use link::Link;
 
 // Stubs for other atoms
 pub struct Switch;
@@ -21,6 +23,5 @@ pub struct Slider;
 pub struct Timer;
 pub struct Checkbox;
 pub struct Badge;
-pub struct Link;
 pub struct Tooltip;
 pub struct Icon;
@@ -1,6 +1,7 @@
 //! Progress Bar Component
 
-use crate::core::component::{VNode, TextNode, TextStyle, Color, NamedColor};
+use crate::core::component::{VNode, BoxNode, BoxStyle, TextNode, TextStyle, Color, Gradient, NamedColor};
+use crate::utils::glyphs::glyph;
 
 /// Progress bar component.
 #[derive(Debug, Clone)]
@@ -11,6 +12,7 @@ pub struct ProgressBar {
     show_percentage: bool,
     filled_char: char,
     empty_char: char,
+    gradient: Option<Gradient>,
 }
 
 impl Default for ProgressBar {
@@ -22,6 +24,7 @@ impl Default for ProgressBar {
             show_percentage: true,
             filled_char: '█',
             empty_char: '░',
+            gradient: None,
         }
     }
 }
@@ -49,14 +52,22 @@ impl ProgressBar {
         self
     }
 
+    /// Color the filled part along a gradient spanning the whole bar, so it
+    /// shifts toward the end color as progress grows.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
     pub fn build(self) -> VNode {
         let percent = (self.value / self.max).clamp(0.0, 1.0);
         let filled = (percent * self.width as f32) as usize;
         let empty = self.width as usize - filled;
 
-        let bar: String = std::iter::repeat(self.filled_char)
+        let (filled_char, empty_char) = glyph((self.filled_char, self.empty_char), ('#', '-'));
+        let bar: String = std::iter::repeat(filled_char)
             .take(filled)
-            .chain(std::iter::repeat(self.empty_char).take(empty))
+            .chain(std::iter::repeat(empty_char).take(empty))
             .collect();
 
         let content = if self.show_percentage {
@@ -65,13 +76,41 @@ impl ProgressBar {
             bar
         };
 
-        VNode::Text(TextNode {
-            content,
-            style: TextStyle {
-                color: Some(Color::Named(NamedColor::Cyan)),
+        let style = TextStyle {
+            color: Some(Color::Named(NamedColor::Cyan)),
+            ..Default::default()
+        };
+        let Some(gradient) = self.gradient else {
+            return VNode::Text(TextNode {
+                content,
+                style,
                 ..Default::default()
-            },
-        })
+            });
+        };
+
+        // One stop per filled cell, sampled from the full-width gradient
+        let stops = (0..filled).map(|i| gradient.step(i as u16, self.width));
+        let (filled_part, rest) = content.split_at(content.char_indices().nth(filled).map_or(content.len(), |(i, _)| i));
+        VNode::Box(Box::new(BoxNode {
+            style: BoxStyle::row(),
+            children: vec![
+                VNode::Text(TextNode {
+                    content: filled_part.to_string(),
+                    style: TextStyle {
+                        gradient: Some(Gradient::new(stops)),
+                        ..style.clone()
+                    },
+                    ..Default::default()
+                }),
+                // The empty track and label keep the bar's usual color
+                VNode::Text(TextNode {
+                    content: rest.to_string(),
+                    style,
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        }))
     }
 }
 
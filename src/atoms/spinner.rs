@@ -2,13 +2,17 @@
 
 use crate::core::component::{VNode, TextNode};
 use crate::core::tick::get_frame;
+use crate::utils::glyphs::glyph;
 
 const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const ASCII_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
 
 /// Spinner component.
 #[derive(Debug, Clone)]
 pub struct Spinner {
     frames: Vec<char>,
+    /// Used instead of non-ASCII `frames` in ASCII mode
+    ascii_frames: Vec<char>,
     label: Option<String>,
 }
 
@@ -16,6 +20,7 @@ impl Default for Spinner {
     fn default() -> Self {
         Self {
             frames: SPINNER_FRAMES.to_vec(),
+            ascii_frames: ASCII_FRAMES.to_vec(),
             label: None,
         }
     }
@@ -29,6 +34,11 @@ impl Spinner {
         self
     }
 
+    pub fn ascii_frames(mut self, frames: Vec<char>) -> Self {
+        self.ascii_frames = frames;
+        self
+    }
+
     pub fn label(mut self, label: impl Into<String>) -> Self {
         self.label = Some(label.into());
         self
@@ -39,8 +49,13 @@ impl Spinner {
     pub fn circle(self) -> Self { self.frames(vec!['◐', '◓', '◑', '◒']) }
 
     pub fn build(self) -> VNode {
-        let frame_idx = get_frame(self.frames.len());
-        let spinner_char = self.frames[frame_idx];
+        let frames = if self.frames.iter().all(char::is_ascii) {
+            &self.frames
+        } else {
+            glyph(&self.frames, &self.ascii_frames)
+        };
+        let frame_idx = get_frame(frames.len());
+        let spinner_char = frames[frame_idx];
         
         let content = if let Some(label) = self.label {
             format!("{} {}", spinner_char, label)
@@ -51,6 +66,7 @@ impl Spinner {
         VNode::Text(TextNode {
             content,
             style: Default::default(),
+            ..Default::default()
         })
     }
 }
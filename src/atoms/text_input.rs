@@ -1,6 +1,6 @@
 //! Text Input Component
 
-use crate::core::component::{VNode, BoxNode, BoxStyle, TextNode, BorderStyle};
+use crate::core::component::{VNode, BoxNode, BoxStyle, TextNode, TextStyle, BorderStyle, CursorShape, TextCursor};
 
 /// Text input component.
 #[derive(Debug, Clone, Default)]
@@ -8,6 +8,10 @@ pub struct TextInput {
     value: String,
     placeholder: String,
     disabled: bool,
+    focused: bool,
+    cursor: Option<usize>,
+    cursor_shape: Option<CursorShape>,
+    cursor_blink: Option<bool>,
 }
 
 impl TextInput {
@@ -28,6 +32,30 @@ impl TextInput {
         self
     }
 
+    /// Show the terminal cursor at the caret.
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Caret position in characters (defaults to the end of the value).
+    pub fn cursor(mut self, position: usize) -> Self {
+        self.cursor = Some(position);
+        self
+    }
+
+    /// Cursor shape while focused (defaults to a bar).
+    pub fn cursor_shape(mut self, shape: CursorShape) -> Self {
+        self.cursor_shape = Some(shape);
+        self
+    }
+
+    /// Whether the cursor blinks while focused (defaults to true).
+    pub fn cursor_blink(mut self, blink: bool) -> Self {
+        self.cursor_blink = Some(blink);
+        self
+    }
+
     pub fn build(self) -> VNode {
         let display = if self.value.is_empty() {
             self.placeholder.clone()
@@ -35,7 +63,17 @@ impl TextInput {
             self.value.clone()
         };
 
-        VNode::Box(BoxNode {
+        // The caret sits before the placeholder, since it isn't real input
+        let cursor = (self.focused && !self.disabled).then(|| {
+            let len = self.value.chars().count();
+            TextCursor {
+                offset: if self.value.is_empty() { 0 } else { self.cursor.unwrap_or(len).min(len) },
+                shape: self.cursor_shape.unwrap_or(CursorShape::Bar),
+                blinking: self.cursor_blink.unwrap_or(true),
+            }
+        });
+
+        VNode::Box(Box::new(BoxNode {
             style: BoxStyle {
                 padding: Some(0),
                 border_style: Some(BorderStyle::Single),
@@ -43,10 +81,11 @@ impl TextInput {
             },
             children: vec![VNode::Text(TextNode {
                 content: display,
-                style: Default::default(),
+                style: TextStyle { cursor, ..Default::default() },
+                ..Default::default()
             })],
             ..Default::default()
-        })
+        }))
     }
 }
 
@@ -1,6 +1,10 @@
 //! Tuiuiu CLI
 
 use std::env;
+use std::fs;
+use std::io::{self, Read};
+
+use tuiuiu::core::export::{buffer_from_ansi, to_html, to_svg, ExportOptions};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -13,6 +17,12 @@ fn main() {
     match args[1].as_str() {
         "storybook" => run_storybook(),
         "mcp" => run_mcp(),
+        "export" => {
+            if let Err(err) = run_export(&args[2..]) {
+                eprintln!("export: {}", err);
+                std::process::exit(1);
+            }
+        }
         "version" | "-v" | "--version" => print_version(),
         "help" | "-h" | "--help" => print_help(),
         _ => {
@@ -35,6 +45,8 @@ fn print_help() {
     println!("COMMANDS:");
     println!("    storybook    Run the component storybook");
     println!("    mcp          Start the MCP server");
+    println!("    export       Turn ANSI output into HTML or SVG");
+    println!("                 export <html|svg> [FILE] [--title T] [--width N] [--output FILE]");
     println!("    version      Print version info");
     println!("    help         Print this help");
 }
@@ -48,3 +60,51 @@ fn run_mcp() {
     println!("🤖 Starting Tuiuiu MCP Server...");
     println!("(Not yet implemented)");
 }
+
+/// Read ANSI text from a file or stdin and write it out as HTML or SVG.
+///
+/// Pipe a frame in, e.g. `my-app --once | tuiuiu export svg -o shot.svg`.
+fn run_export(args: &[String]) -> io::Result<()> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+    let mut format = None;
+    let mut input = None;
+    let mut output = None;
+    let mut width = None;
+    let mut options = ExportOptions::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| invalid(format!("{} needs a value", arg)));
+        match arg.as_str() {
+            "--title" | "-t" => options = options.title(value()?),
+            "--output" | "-o" => output = Some(value()?),
+            "--width" | "-w" => {
+                let value = value()?;
+                width = Some(value.parse::<u16>().map_err(|_| invalid(format!("invalid width: {}", value)))?);
+            }
+            "html" | "svg" if format.is_none() => format = Some(arg.clone()),
+            _ if input.is_none() && (arg == "-" || !arg.starts_with('-')) => input = Some(arg.clone()),
+            _ => return Err(invalid(format!("unexpected argument: {}", arg))),
+        }
+    }
+    let format = format.ok_or_else(|| invalid("expected a format: html or svg".to_string()))?;
+
+    let mut text = String::new();
+    match input.as_deref() {
+        None | Some("-") => {
+            io::stdin().read_to_string(&mut text)?;
+        }
+        Some(path) => text = fs::read_to_string(path)?,
+    }
+
+    let buffer = buffer_from_ansi(&text, width);
+    let document = if format == "html" { to_html(&buffer, &options) } else { to_svg(&buffer, &options) };
+    match output {
+        Some(path) => fs::write(path, document),
+        None => {
+            print!("{}", document);
+            Ok(())
+        }
+    }
+}
@@ -43,6 +43,63 @@ pub fn lerp_color(a: (u8, u8, u8), b: (u8, u8, u8), t: f64) -> (u8, u8, u8) {
     )
 }
 
+/// Interpolate between two colors through HSL, taking the shorter way
+/// around the hue wheel.
+pub fn lerp_color_hsl(a: (u8, u8, u8), b: (u8, u8, u8), t: f64) -> (u8, u8, u8) {
+    let (h1, s1, l1) = rgb_to_hsl(a);
+    let (mut h2, s2, l2) = rgb_to_hsl(b);
+    // Greys have no hue; borrow the other end's so only lightness moves
+    let h1 = if s1 == 0.0 { h2 } else { h1 };
+    if s2 == 0.0 {
+        h2 = h1;
+    }
+    if h2 - h1 > 180.0 {
+        h2 -= 360.0;
+    } else if h1 - h2 > 180.0 {
+        h2 += 360.0;
+    }
+    hsl_to_rgb(lerp(h1, h2, t).rem_euclid(360.0), lerp(s1, s2, t), lerp(l1, l2, t))
+}
+
+/// Convert RGB to hue (degrees), saturation and lightness (0.0 to 1.0).
+pub fn rgb_to_hsl((r, g, b): (u8, u8, u8)) -> (f64, f64, f64) {
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return (0.0, 0.0, l);
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    (h, s, l)
+}
+
+/// Convert hue (degrees), saturation and lightness (0.0 to 1.0) to RGB.
+pub fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u8 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    let channel = |v: f64| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (channel(r), channel(g), channel(b))
+}
+
 /// Animation options.
 #[derive(Debug, Clone)]
 pub struct AnimationOptions {
@@ -6,9 +6,12 @@ use std::io;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
-use crate::core::terminal::{Terminal, TerminalEvent, Key};
-use crate::core::renderer::OutputBuffer;
+use crate::core::terminal::{Terminal, TerminalEvent, Key, KeyEventKind, KeyboardEnhancements};
+use crate::core::queries::{Query, QueryReply};
+use crate::core::renderer::{CursorPlacement, OutputBuffer, TextRenderOptions};
 use crate::core::component::VNode;
+use crate::core::capabilities::{color_support, set_render_mode, use_unicode, RenderMode};
+use crate::core::graphics::KittyImages;
 
 // =============================================================================
 // App State
@@ -49,6 +52,13 @@ pub struct RenderOptions {
     pub exit_on_escape: bool,
     /// Exit on Ctrl+C
     pub exit_on_ctrl_c: bool,
+    /// Ask the terminal about its features at start-up
+    pub query_terminal: bool,
+    /// Unicode or ASCII glyphs (Auto follows the locale)
+    pub render_mode: RenderMode,
+    /// Kitty keyboard protocol enhancements, turned on once the terminal
+    /// acknowledges the protocol; legacy key parsing stays otherwise
+    pub keyboard_enhancements: Option<KeyboardEnhancements>,
 }
 
 impl Default for RenderOptions {
@@ -59,6 +69,9 @@ impl Default for RenderOptions {
             mouse: true,
             exit_on_escape: true,
             exit_on_ctrl_c: true,
+            query_terminal: true,
+            render_mode: RenderMode::Auto,
+            keyboard_enhancements: None,
         }
     }
 }
@@ -68,17 +81,26 @@ impl Default for RenderOptions {
 // =============================================================================
 
 /// The main application instance.
-pub struct App {
+///
+/// It holds on to its root component for redraws, so the component may
+/// borrow anything that outlives the app.
+pub struct App<'a> {
     terminal: Terminal,
     options: RenderOptions,
+    /// Root component, re-rendered when the UI needs a redraw
+    root: Option<Box<dyn Fn() -> VNode + 'a>>,
     #[allow(dead_code)]
     buffer: OutputBuffer,
     #[allow(dead_code)]
     prev_buffer: OutputBuffer,
     exit_code: i32,
+    /// Whether the hardware cursor is currently shown at a caret
+    cursor_shown: bool,
+    /// Kitty images the terminal holds from earlier frames
+    kitty_images: KittyImages,
 }
 
-impl App {
+impl App<'_> {
     /// Create a new app with options.
     pub fn new(options: RenderOptions) -> io::Result<Self> {
         let terminal = Terminal::new()?;
@@ -87,9 +109,12 @@ impl App {
         Ok(Self {
             terminal,
             options,
+            root: None,
             buffer: OutputBuffer::new(width, height),
             prev_buffer: OutputBuffer::new(width, height),
             exit_code: 0,
+            cursor_shown: false,
+            kitty_images: KittyImages::new(),
         })
     }
 
@@ -105,6 +130,9 @@ impl App {
 
     /// Initialize the terminal for the app.
     pub fn init(&mut self) -> io::Result<()> {
+        set_render_mode(self.options.render_mode);
+        self.buffer.set_unicode(use_unicode());
+        self.prev_buffer.set_unicode(use_unicode());
         self.terminal.enable_raw_mode()?;
 
         if self.options.alternate_screen {
@@ -118,6 +146,14 @@ impl App {
         self.terminal.hide_cursor()?;
         self.terminal.clear()?;
 
+        // Replies arrive as events while the app runs
+        if self.options.query_terminal {
+            crate::core::queries::send_queries(&mut self.terminal, crate::core::queries::QUERY_TIMEOUT)?;
+        } else if self.options.keyboard_enhancements.is_some() {
+            let queries = [Query::KeyboardFlags, Query::DeviceAttributes];
+            crate::core::queries::ask(&mut self.terminal, &queries, crate::core::queries::QUERY_TIMEOUT)?;
+        }
+
         APP_RUNNING.store(true, Ordering::SeqCst);
         APP_EXIT_REQUESTED.store(false, Ordering::SeqCst);
 
@@ -128,7 +164,16 @@ impl App {
     pub fn cleanup(&mut self) -> io::Result<()> {
         APP_RUNNING.store(false, Ordering::SeqCst);
 
+        if self.cursor_shown {
+            self.terminal.reset_cursor_shape()?;
+            self.cursor_shown = false;
+        }
         self.terminal.show_cursor()?;
+        self.terminal.pop_keyboard_enhancements()?;
+        let images = self.kitty_images.clear();
+        if !images.is_empty() {
+            self.terminal.write(&images)?;
+        }
 
         if self.options.mouse {
             self.terminal.disable_mouse()?;
@@ -151,7 +196,8 @@ impl App {
     /// Check if we should exit based on an event.
     pub fn should_exit(&self, event: &TerminalEvent) -> bool {
         match event {
-            TerminalEvent::Key(key_event) => {
+            // Releases follow the press that already counted
+            TerminalEvent::Key(key_event) if key_event.kind != KeyEventKind::Release => {
                 // Escape
                 if self.options.exit_on_escape && key_event.key == Key::Escape {
                     return true;
@@ -185,6 +231,59 @@ impl App {
         self.render(content)
     }
 
+    /// Re-render the root component, if one is mounted.
+    pub fn redraw(&mut self) -> io::Result<()> {
+        if let Some(root) = &self.root {
+            let (width, height) = self.size();
+            crate::core::responsive::set_viewport(width, height);
+            let vnode = root();
+            let context = crate::core::renderer::RenderContext::new(width, height);
+            let buffer = crate::core::renderer::render_to_buffer(&vnode, &context);
+
+            // Hide the cursor while drawing so it doesn't flicker across the frame
+            if self.cursor_shown {
+                self.terminal.hide_cursor()?;
+            }
+            let frame = buffer.to_frame_string(color_support(), &mut self.kitty_images);
+            self.render(&frame)?;
+            self.place_cursor(buffer.cursor())?;
+        }
+        Ok(())
+    }
+
+    /// Show the hardware cursor at a focused input's caret, or keep it hidden.
+    fn place_cursor(&mut self, cursor: Option<CursorPlacement>) -> io::Result<()> {
+        match cursor {
+            Some(cursor) => {
+                self.terminal.set_cursor_shape(cursor.shape, cursor.blinking)?;
+                self.terminal.move_cursor(cursor.x + 1, cursor.y + 1)?;
+                self.terminal.show_cursor()?;
+                self.cursor_shown = true;
+            }
+            None if self.cursor_shown => {
+                self.terminal.reset_cursor_shape()?;
+                self.cursor_shown = false;
+            }
+            None => {}
+        }
+        self.terminal.flush()
+    }
+
+    /// Pick up a new terminal size and redraw for it.
+    ///
+    /// Returns whether the size changed.
+    pub fn handle_resize(&mut self) -> io::Result<bool> {
+        let previous = self.size();
+        self.terminal.refresh_size()?;
+        if self.size() == previous {
+            return Ok(false);
+        }
+
+        self.terminal.clear()?;
+        self.redraw()?;
+        Ok(true)
+    }
+
     /// Wait until exit is requested.
     pub fn wait_until_exit(&mut self) -> io::Result<i32> {
         loop {
@@ -196,6 +295,28 @@ impl App {
                 if self.should_exit(&event) {
                     break;
                 }
+
+                match &event {
+                    // Mouse wheel scrolls the box under the pointer
+                    TerminalEvent::Mouse(mouse) if crate::core::renderer::handle_scroll_event(mouse) => {
+                        self.redraw()?;
+                    }
+                    // Breakpoints follow the terminal as it is resized
+                    TerminalEvent::Resize(..) => {
+                        self.handle_resize()?;
+                    }
+                    // Colors and links may improve once the terminal has answered
+                    TerminalEvent::Reply(reply) => {
+                        if let (QueryReply::KeyboardFlags(_), Some(enhancements)) = (reply, self.options.keyboard_enhancements) {
+                            self.terminal.push_keyboard_enhancements(enhancements)?;
+                        }
+                        if crate::core::queries::handle_reply(reply.clone()) {
+                            crate::core::capabilities::refresh_render_capabilities();
+                            self.redraw()?;
+                        }
+                    }
+                    _ => {}
+                }
             }
         }
 
@@ -203,7 +324,7 @@ impl App {
     }
 }
 
-impl Drop for App {
+impl Drop for App<'_> {
     fn drop(&mut self) {
         let _ = self.cleanup();
     }
@@ -214,40 +335,52 @@ impl Drop for App {
 // =============================================================================
 
 /// Render a component function.
-pub fn render<F, C>(component: F) -> io::Result<App>
+pub fn render<'a, F, C>(component: F) -> io::Result<App<'a>>
 where
-    F: Fn() -> C,
+    F: Fn() -> C + 'a,
     C: Into<VNode>,
 {
     render_with_options(component, RenderOptions::default())
 }
 
 /// Render with custom options.
-pub fn render_with_options<F, C>(component: F, options: RenderOptions) -> io::Result<App>
+pub fn render_with_options<'a, F, C>(component: F, options: RenderOptions) -> io::Result<App<'a>>
 where
-    F: Fn() -> C,
+    F: Fn() -> C + 'a,
     C: Into<VNode>,
 {
     let mut app = App::new(options)?;
     app.init()?;
 
     // Initial render
-    let vnode = component().into();
-    let (width, height) = app.size();
-    let content = crate::core::renderer::render_to_string(&vnode, width, height);
-    app.render(&content)?;
+    app.root = Some(Box::new(move || component().into()));
+    app.redraw()?;
 
     Ok(app)
 }
 
 /// Render once and return immediately.
+///
+/// The output fits the content's height at the width from `COLUMNS` or
+/// the terminal, and keeps ANSI styling only when stdout is a terminal.
 pub fn render_once<F, C>(component: F) -> io::Result<String>
 where
     F: Fn() -> C,
     C: Into<VNode>,
 {
+    render_once_with_options(component, &TextRenderOptions::default())
+}
+
+/// Render once with an explicit width and ANSI setting.
+pub fn render_once_with_options<F, C>(component: F, options: &TextRenderOptions) -> io::Result<String>
+where
+    F: Fn() -> C,
+    C: Into<VNode>,
+{
+    let width = options.resolved_width();
+    crate::core::responsive::set_viewport(width, crate::core::responsive::DEFAULT_VIEWPORT.1);
     let vnode = component().into();
-    Ok(crate::core::renderer::render_to_string(&vnode, 80, 24))
+    Ok(crate::core::renderer::render_to_text(&vnode, &TextRenderOptions { width: Some(width), ..options.clone() }))
 }
 
 // =============================================================================
@@ -298,4 +431,12 @@ mod tests {
 
         APP_EXIT_REQUESTED.store(false, Ordering::SeqCst);
     }
+
+    #[test]
+    fn test_components_may_borrow_locals() {
+        let title = String::from("borrowed");
+        let component = || VNode::text(title.as_str());
+        // Only has to compile: there's no terminal to render to
+        let _ = |options: RenderOptions| render_with_options(component, options).map(drop);
+    }
 }
@@ -2,8 +2,13 @@
 //!
 //! Detect terminal features like color support, Unicode, etc.
 
+use std::cell::{Cell, OnceCell};
 use std::env;
 
+use crate::core::graphics::GraphicsProtocol;
+use crate::core::queries::{self, QueryResults};
+use crate::core::terminfo::{self, Terminfo};
+
 /// Terminal capabilities.
 #[derive(Debug, Clone)]
 pub struct TerminalCapabilities {
@@ -15,6 +20,26 @@ pub struct TerminalCapabilities {
     pub bracketed_paste: bool,
     pub alternate_screen: bool,
     pub title: bool,
+    /// Sixel graphics (DA1 attribute 4)
+    pub sixel: bool,
+    /// Kitty graphics protocol
+    pub kitty_graphics: bool,
+    /// iTerm2 inline images
+    pub iterm2_images: bool,
+    /// Synchronized output (mode 2026)
+    pub synchronized_output: bool,
+    /// OSC 8 hyperlinks
+    pub hyperlinks: bool,
+    /// Curly, dotted and other underline styles plus underline colors
+    pub styled_underlines: bool,
+    /// Name and version reported by XTVERSION
+    pub terminal_version: Option<String>,
+    /// Default foreground color (OSC 10)
+    pub foreground: Option<(u8, u8, u8)>,
+    /// Default background color (OSC 11)
+    pub background: Option<(u8, u8, u8)>,
+    /// Whether the background is dark, when the terminal reported it
+    pub dark_background: Option<bool>,
 }
 
 impl Default for TerminalCapabilities {
@@ -28,35 +53,210 @@ impl Default for TerminalCapabilities {
             bracketed_paste: true,
             alternate_screen: true,
             title: true,
+            sixel: false,
+            kitty_graphics: false,
+            iterm2_images: false,
+            synchronized_output: false,
+            hyperlinks: false,
+            styled_underlines: false,
+            terminal_version: None,
+            foreground: None,
+            background: None,
+            dark_background: None,
         }
     }
 }
 
-/// Detect terminal capabilities.
+impl TerminalCapabilities {
+    /// Richest color palette these capabilities allow.
+    pub fn color_support(&self) -> ColorSupport {
+        if self.true_color {
+            ColorSupport::TrueColor
+        } else if self.colors_256 {
+            ColorSupport::Colors256
+        } else if self.colors_16 {
+            ColorSupport::Colors16
+        } else {
+            ColorSupport::None
+        }
+    }
+
+    /// Best way to draw images, falling back to block characters.
+    pub fn graphics_protocol(&self) -> GraphicsProtocol {
+        if self.kitty_graphics {
+            GraphicsProtocol::Kitty
+        } else if self.iterm2_images {
+            GraphicsProtocol::Iterm2
+        } else if self.sixel {
+            GraphicsProtocol::Sixel
+        } else {
+            GraphicsProtocol::HalfBlocks
+        }
+    }
+
+    /// Fold in what the terminal answered to queries.
+    ///
+    /// Answers override guesses from the environment and terminfo. A
+    /// terminal known for true color upgrades the palette unless colors
+    /// are disabled or pinned with `FORCE_COLOR`.
+    pub fn apply_query_results(&mut self, results: &QueryResults) {
+        /// XTVERSION names of terminals with 24-bit color.
+        const TRUE_COLOR_TERMINALS: [&str; 7] = ["kitty", "wezterm", "iterm2", "foot", "ghostty", "contour", "konsole"];
+        /// XTVERSION names of terminals with OSC 8 hyperlinks.
+        const HYPERLINK_TERMINALS: [&str; 6] = ["kitty", "wezterm", "iterm2", "foot", "ghostty", "contour"];
+        /// XTVERSION names of terminals with styled underlines.
+        const UNDERLINE_TERMINALS: [&str; 5] = ["kitty", "wezterm", "foot", "ghostty", "contour"];
+        /// XTVERSION names of terminals with the Kitty graphics protocol.
+        const KITTY_GRAPHICS_TERMINALS: [&str; 2] = ["kitty", "ghostty"];
+        /// XTVERSION names of terminals with iTerm2 inline images.
+        const ITERM2_IMAGE_TERMINALS: [&str; 2] = ["iterm2", "wezterm"];
+
+        if let Some(version) = &results.version {
+            let name = version.to_lowercase();
+            let forced = env::var_os("FORCE_COLOR").is_some();
+            if self.colors_16 && !forced && TRUE_COLOR_TERMINALS.iter().any(|t| name.starts_with(t)) {
+                self.true_color = true;
+                self.colors_256 = true;
+            }
+            if HYPERLINK_TERMINALS.iter().any(|t| name.starts_with(t)) {
+                self.hyperlinks = true;
+            }
+            if UNDERLINE_TERMINALS.iter().any(|t| name.starts_with(t)) {
+                self.styled_underlines = true;
+            }
+            if KITTY_GRAPHICS_TERMINALS.iter().any(|t| name.starts_with(t)) {
+                self.kitty_graphics = true;
+            }
+            if ITERM2_IMAGE_TERMINALS.iter().any(|t| name.starts_with(t)) {
+                self.iterm2_images = true;
+            }
+            self.terminal_version = Some(version.clone());
+        }
+
+        if results.device_attributes.is_some() {
+            self.sixel = results.has_attribute(4);
+        }
+        if let Some(state) = results.mode(2026) {
+            self.synchronized_output = state.is_supported();
+        }
+        if let Some(state) = results.mode(2004) {
+            self.bracketed_paste = state.is_supported();
+        }
+        if let Some(state) = results.mode(1006) {
+            self.mouse = state.is_supported();
+        }
+
+        self.foreground = results.foreground.or(self.foreground);
+        self.background = results.background.or(self.background);
+        self.dark_background = results.dark_background().or(self.dark_background);
+    }
+}
+
+/// Detect terminal capabilities from the environment, terminfo and any
+/// replies to terminal queries received so far.
 pub fn detect_terminal_capabilities() -> TerminalCapabilities {
-    let colorterm = env::var("COLORTERM").unwrap_or_default();
-    let term = env::var("TERM").unwrap_or_default();
+    let mut capabilities = capabilities_for(ColorSupport::detect(), terminfo::current().as_deref());
+    capabilities.apply_query_results(&queries::query_results());
+    capabilities
+}
 
-    let true_color = colorterm.contains("truecolor") || colorterm.contains("24bit");
-    let colors_256 = term.contains("256color") || true_color;
+/// Without a terminfo entry, assume an xterm-compatible terminal.
+fn capabilities_for(colors: ColorSupport, info: Option<&Terminfo>) -> TerminalCapabilities {
+    let has = |check: fn(&Terminfo) -> bool| info.map_or(true, check);
+    let image_protocols = image_protocols_from_env(|name| env::var(name).ok(), stdout_is_tty());
 
     TerminalCapabilities {
-        true_color,
-        colors_256,
-        colors_16: true,
+        true_color: colors == ColorSupport::TrueColor,
+        colors_256: matches!(colors, ColorSupport::TrueColor | ColorSupport::Colors256),
+        colors_16: colors != ColorSupport::None,
         unicode: supports_unicode(),
-        mouse: true,
-        bracketed_paste: true,
-        alternate_screen: true,
-        title: true,
+        mouse: has(|info| info.has("kmous") || info.has("XM")),
+        // `XT` marks xterm-like entries, which tmux takes to imply bracketed paste
+        bracketed_paste: has(|info| info.has("BE") || info.flag("XT")),
+        alternate_screen: has(|info| info.has("smcup")),
+        title: has(|info| info.has("tsl") || info.has("TS") || info.flag("XT")),
+        hyperlinks: supports_hyperlinks(),
+        kitty_graphics: image_protocols.0,
+        iterm2_images: image_protocols.1,
+        // `Smulx` sets the underline style, `Su` marks undercurl support
+        styled_underlines: info.is_some_and(|info| info.has("Smulx") || info.flag("Su")),
+        ..TerminalCapabilities::default()
     }
 }
 
+fn supports_hyperlinks() -> bool {
+    hyperlinks_from_env(|name| env::var(name).ok(), stdout_is_tty())
+}
+
+/// Decide OSC 8 hyperlink support from environment variables.
+///
+/// `FORCE_HYPERLINK` overrides detection (`0`/`false` disables). Otherwise
+/// only terminals known to support hyperlinks get them, since others may
+/// print the escape sequence or drop the link text.
+pub fn hyperlinks_from_env(var: impl Fn(&str) -> Option<String>, is_tty: bool) -> bool {
+    /// `TERM_PROGRAM` values of terminals with hyperlinks.
+    const PROGRAMS: [&str; 5] = ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"];
+    /// `TERM` prefixes of terminals with hyperlinks.
+    const TERMS: [&str; 6] = ["xterm-kitty", "xterm-ghostty", "wezterm", "foot", "alacritty", "contour"];
+
+    match var("FORCE_HYPERLINK").as_deref().map(str::trim) {
+        Some("0" | "false") => return false,
+        Some(_) => return true,
+        None => {}
+    }
+    if !is_tty {
+        return false;
+    }
+
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    let term = var("TERM").unwrap_or_default();
+    // VTE (GNOME Terminal, Tilix, ...) has hyperlinks since 0.50
+    let vte = var("VTE_VERSION").and_then(|v| v.parse::<u32>().ok()).unwrap_or(0);
+
+    PROGRAMS.contains(&program.as_str())
+        || TERMS.iter().any(|t| term.starts_with(t))
+        || vte >= 5000
+        || var("KITTY_WINDOW_ID").is_some()
+        || var("WT_SESSION").is_some()
+}
+
+/// Detect Kitty graphics and iTerm2 inline image support from environment
+/// variables, as `(kitty, iterm2)`.
+///
+/// Both are off inside tmux and screen, which need passthrough for them,
+/// and when stdout isn't a terminal.
+pub fn image_protocols_from_env(var: impl Fn(&str) -> Option<String>, is_tty: bool) -> (bool, bool) {
+    if !is_tty || var("TMUX").is_some() || var("TERM").is_some_and(|t| t.starts_with("screen")) {
+        return (false, false);
+    }
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    let term = var("TERM").unwrap_or_default();
+
+    let kitty = term.starts_with("xterm-kitty")
+        || term.starts_with("xterm-ghostty")
+        || program == "ghostty"
+        || var("KITTY_WINDOW_ID").is_some();
+    // `LC_TERMINAL` survives ssh, unlike `TERM_PROGRAM`
+    let iterm2 = matches!(program.as_str(), "iTerm.app" | "WezTerm")
+        || var("LC_TERMINAL").as_deref() == Some("iTerm2");
+    (kitty, iterm2)
+}
+
 fn supports_unicode() -> bool {
-    env::var("LANG")
-        .or_else(|_| env::var("LC_ALL"))
-        .map(|v| v.to_lowercase().contains("utf"))
-        .unwrap_or(true)
+    locale_is_utf8(|name| env::var(name).ok())
+}
+
+/// Whether the locale uses UTF-8, going by the first of `LC_ALL`,
+/// `LC_CTYPE` and `LANG` that is set. No locale at all counts as UTF-8,
+/// since that is the common case outside minimal containers.
+fn locale_is_utf8(var: impl Fn(&str) -> Option<String>) -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| var(name).filter(|v| !v.is_empty()))
+        .map_or(true, |locale| {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
 }
 
 /// Character set for rendering.
@@ -75,11 +275,374 @@ pub enum ColorSupport {
     None,
 }
 
+impl ColorSupport {
+    /// Detect color support from the environment, stdout and terminfo.
+    pub fn detect() -> Self {
+        Self::from_env(|name| env::var(name).ok(), stdout_is_tty(), terminfo::current().as_deref())
+    }
+
+    /// Decide color support from environment variables.
+    ///
+    /// `NO_COLOR` (non-empty) disables colors. `FORCE_COLOR` enables them
+    /// even when output isn't a terminal: `0`/`false` disables, `1`/`true`
+    /// or empty means 16 colors, `2` 256 colors and `3` true color.
+    /// Otherwise non-terminal output and `TERM=dumb` get no colors, and
+    /// the palette comes from `COLORTERM`, then the terminfo entry, then
+    /// the name in `TERM`.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>, is_tty: bool, terminfo: Option<&Terminfo>) -> Self {
+        if var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return ColorSupport::None;
+        }
+
+        let detected = || {
+            let colorterm = var("COLORTERM").unwrap_or_default().to_lowercase();
+            let term = var("TERM").unwrap_or_default();
+            if colorterm.contains("truecolor") || colorterm.contains("24bit") {
+                ColorSupport::TrueColor
+            } else if let Some(info) = terminfo {
+                Self::from_terminfo(info)
+            } else if term.contains("256color") {
+                ColorSupport::Colors256
+            } else {
+                ColorSupport::Colors16
+            }
+        };
+
+        match var("FORCE_COLOR").as_deref().map(str::trim) {
+            Some("0" | "false") => ColorSupport::None,
+            Some("2") => ColorSupport::Colors256,
+            Some("3") => ColorSupport::TrueColor,
+            // `1`, `true` or empty: forced on at the basic palette
+            Some(_) => ColorSupport::Colors16,
+            None if !is_tty || var("TERM").as_deref() == Some("dumb") => ColorSupport::None,
+            None => detected(),
+        }
+    }
+
+    /// Palette advertised by a terminfo entry.
+    pub fn from_terminfo(info: &Terminfo) -> Self {
+        match info.colors() {
+            _ if info.true_color() => ColorSupport::TrueColor,
+            256.. => ColorSupport::Colors256,
+            8.. => ColorSupport::Colors16,
+            _ => ColorSupport::None,
+        }
+    }
+}
+
+thread_local! {
+    static COLOR_SUPPORT: Cell<Option<ColorSupport>> = const { Cell::new(None) };
+}
+
+/// Color support used for rendering, detected on first use.
+pub fn color_support() -> ColorSupport {
+    COLOR_SUPPORT.with(|support| {
+        support.get().unwrap_or_else(|| {
+            let detected = ColorSupport::detect();
+            support.set(Some(detected));
+            detected
+        })
+    })
+}
+
+/// Override the color support used for rendering.
+pub fn set_color_support(value: ColorSupport) {
+    COLOR_SUPPORT.with(|support| support.set(Some(value)));
+}
+
+/// Re-detect the color support used for rendering, e.g. once query
+/// replies have arrived.
+pub fn refresh_color_support() {
+    set_color_support(detect_terminal_capabilities().color_support());
+}
+
+thread_local! {
+    static HYPERLINKS: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Whether text links are emitted as OSC 8 hyperlinks, detected on first use.
+///
+/// Without them, links show their URL next to the text.
+pub fn hyperlinks_supported() -> bool {
+    HYPERLINKS.with(|supported| {
+        supported.get().unwrap_or_else(|| {
+            let detected = supports_hyperlinks();
+            supported.set(Some(detected));
+            detected
+        })
+    })
+}
+
+/// Override hyperlink support, e.g. for tests or a user setting.
+pub fn set_hyperlinks_supported(value: bool) {
+    HYPERLINKS.with(|supported| supported.set(Some(value)));
+}
+
+thread_local! {
+    static STYLED_UNDERLINES: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Whether underline styles and colors are emitted, detected on first use.
+///
+/// Without them every underline style renders as a plain underline.
+pub fn styled_underlines_supported() -> bool {
+    STYLED_UNDERLINES.with(|supported| {
+        supported.get().unwrap_or_else(|| {
+            let detected = detect_terminal_capabilities().styled_underlines;
+            supported.set(Some(detected));
+            detected
+        })
+    })
+}
+
+/// Override styled underline support.
+pub fn set_styled_underlines_supported(value: bool) {
+    STYLED_UNDERLINES.with(|supported| supported.set(Some(value)));
+}
+
+thread_local! {
+    static GRAPHICS: Cell<Option<GraphicsProtocol>> = const { Cell::new(None) };
+}
+
+/// How images are drawn, detected on first use.
+pub fn graphics_protocol() -> GraphicsProtocol {
+    GRAPHICS.with(|protocol| {
+        protocol.get().unwrap_or_else(|| {
+            let detected = detect_terminal_capabilities().graphics_protocol();
+            protocol.set(Some(detected));
+            detected
+        })
+    })
+}
+
+/// Override how images are drawn, e.g. for tests or a user setting.
+pub fn set_graphics_protocol(value: GraphicsProtocol) {
+    GRAPHICS.with(|protocol| protocol.set(Some(value)));
+}
+
+/// Re-detect color, hyperlink, underline and image support together.
+pub fn refresh_render_capabilities() {
+    let capabilities = detect_terminal_capabilities();
+    set_color_support(capabilities.color_support());
+    set_hyperlinks_supported(capabilities.hyperlinks);
+    set_styled_underlines_supported(capabilities.styled_underlines);
+    set_graphics_protocol(capabilities.graphics_protocol());
+}
+
+/// Whether stdout is a terminal rather than a pipe or file.
+#[cfg(unix)]
+pub fn stdout_is_tty() -> bool {
+    unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+}
+
+#[cfg(not(unix))]
+pub fn stdout_is_tty() -> bool {
+    true
+}
+
 /// Render mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum RenderMode {
+    /// Unicode unless the locale isn't UTF-8
     #[default]
     Auto,
     Unicode,
     Ascii,
 }
+
+impl RenderMode {
+    /// Character set this mode draws with.
+    pub fn character_set(self) -> CharacterSet {
+        let unicode = match self {
+            RenderMode::Auto => UNICODE_LOCALE.with(|cached| *cached.get_or_init(supports_unicode)),
+            RenderMode::Unicode => true,
+            RenderMode::Ascii => false,
+        };
+        if unicode {
+            CharacterSet::Unicode
+        } else {
+            CharacterSet::Ascii
+        }
+    }
+}
+
+thread_local! {
+    static RENDER_MODE: Cell<RenderMode> = const { Cell::new(RenderMode::Auto) };
+    static UNICODE_LOCALE: OnceCell<bool> = const { OnceCell::new() };
+}
+
+/// Render mode used for borders and glyphs.
+pub fn render_mode() -> RenderMode {
+    RENDER_MODE.with(Cell::get)
+}
+
+/// Choose Unicode or ASCII drawing, or `Auto` to follow the locale.
+pub fn set_render_mode(mode: RenderMode) {
+    RENDER_MODE.with(|current| current.set(mode));
+}
+
+/// Whether borders and glyphs are drawn with Unicode characters.
+pub fn use_unicode() -> bool {
+    render_mode().character_set() == CharacterSet::Unicode
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn support(vars: &[(&str, &str)], is_tty: bool) -> ColorSupport {
+        ColorSupport::from_env(
+            |name| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| (*v).to_string()),
+            is_tty,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_color_support_from_env() {
+        assert_eq!(support(&[("COLORTERM", "truecolor")], true), ColorSupport::TrueColor);
+        assert_eq!(support(&[("TERM", "xterm-256color")], true), ColorSupport::Colors256);
+        assert_eq!(support(&[("TERM", "linux")], true), ColorSupport::Colors16);
+        assert_eq!(support(&[("TERM", "dumb")], true), ColorSupport::None);
+
+        // NO_COLOR and pipes strip colors
+        assert_eq!(support(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")], true), ColorSupport::None);
+        assert_eq!(support(&[("NO_COLOR", "")], true), ColorSupport::Colors16);
+        assert_eq!(support(&[("COLORTERM", "truecolor")], false), ColorSupport::None);
+
+        // FORCE_COLOR overrides the TTY check
+        assert_eq!(support(&[("FORCE_COLOR", "1"), ("TERM", "xterm-256color")], false), ColorSupport::Colors16);
+        assert_eq!(support(&[("FORCE_COLOR", ""), ("COLORTERM", "truecolor")], true), ColorSupport::Colors16);
+        assert_eq!(support(&[("FORCE_COLOR", "2")], false), ColorSupport::Colors256);
+        assert_eq!(support(&[("FORCE_COLOR", "3")], false), ColorSupport::TrueColor);
+        assert_eq!(support(&[("FORCE_COLOR", "0"), ("COLORTERM", "truecolor")], true), ColorSupport::None);
+        assert_eq!(support(&[("FORCE_COLOR", "1"), ("NO_COLOR", "1")], false), ColorSupport::None);
+    }
+
+    #[test]
+    fn test_terminfo_capabilities() {
+        use crate::core::terminfo::tests::compile;
+
+        let direct = Terminfo::parse(&compile(
+            "xterm-direct",
+            &["XT", "RGB"],
+            &[("colors", 1 << 24)],
+            &[("smcup", b"\x1B[?1049h"), ("kmous", b"\x1B[<"), ("BE", b"\x1B[?2004h"), ("Smulx", b"\x1B[4:%p1%dm")],
+        ))
+        .unwrap();
+        let vt100 = Terminfo::parse(&compile("vt100", &["am"], &[("cols", 80)], &[("cup", b"\x1B[%i%p1%d;%p2%dH")])).unwrap();
+
+        assert_eq!(ColorSupport::from_terminfo(&direct), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_terminfo(&vt100), ColorSupport::None);
+
+        // Terminfo beats the TERM name, COLORTERM beats terminfo
+        let vars = [("TERM", "xterm-256color")];
+        let from = |vars: &[(&str, &str)], info| {
+            ColorSupport::from_env(|name| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| (*v).to_string()), true, info)
+        };
+        assert_eq!(from(&vars, Some(&vt100)), ColorSupport::None);
+        assert_eq!(from(&[("COLORTERM", "truecolor")], Some(&vt100)), ColorSupport::TrueColor);
+        assert_eq!(from(&[("FORCE_COLOR", "1")], Some(&vt100)), ColorSupport::Colors16);
+
+        let caps = capabilities_for(ColorSupport::from_terminfo(&direct), Some(&direct));
+        assert!(caps.true_color && caps.mouse && caps.bracketed_paste && caps.alternate_screen && caps.title);
+        assert!(caps.styled_underlines);
+        let caps = capabilities_for(ColorSupport::None, Some(&vt100));
+        assert!(!caps.colors_16 && !caps.mouse && !caps.bracketed_paste && !caps.alternate_screen && !caps.title);
+        assert!(!caps.styled_underlines);
+        assert!(capabilities_for(ColorSupport::Colors16, None).mouse);
+    }
+
+    #[test]
+    fn test_query_results_override_guesses() {
+        use crate::core::queries::{ModeState, QueryReply};
+
+        let mut results = QueryResults::default();
+        results.apply(QueryReply::Version("WezTerm 20240203".to_string()));
+        results.apply(QueryReply::DeviceAttributes(vec![65, 4, 22]));
+        results.apply(QueryReply::Mode { mode: 2026, state: ModeState::Reset });
+        results.apply(QueryReply::Mode { mode: 2004, state: ModeState::NotRecognized });
+        results.apply(QueryReply::Background(0x28, 0x2c, 0x34));
+
+        let mut caps = capabilities_for(ColorSupport::Colors256, None);
+        caps.apply_query_results(&results);
+        assert!(caps.true_color && caps.sixel && caps.synchronized_output && caps.hyperlinks);
+        assert!(caps.styled_underlines);
+        assert_eq!(caps.graphics_protocol(), GraphicsProtocol::Iterm2);
+        assert!(!caps.bracketed_paste);
+        assert!(caps.mouse, "unanswered modes keep the guess");
+        assert_eq!(caps.terminal_version.as_deref(), Some("WezTerm 20240203"));
+        assert_eq!(caps.dark_background, Some(true));
+
+        // Disabled colors stay disabled
+        let mut caps = capabilities_for(ColorSupport::None, None);
+        caps.apply_query_results(&results);
+        assert_eq!(caps.color_support(), ColorSupport::None);
+    }
+
+    #[test]
+    fn test_hyperlinks_from_env() {
+        let hyperlinks = |vars: &[(&str, &str)], is_tty: bool| {
+            hyperlinks_from_env(|name| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| (*v).to_string()), is_tty)
+        };
+        assert!(!hyperlinks(&[("TERM", "xterm-256color")], true));
+        assert!(hyperlinks(&[("TERM", "xterm-kitty")], true));
+        assert!(hyperlinks(&[("TERM_PROGRAM", "WezTerm")], true));
+        assert!(hyperlinks(&[("VTE_VERSION", "7600")], true));
+        assert!(!hyperlinks(&[("VTE_VERSION", "4800")], true));
+        assert!(!hyperlinks(&[("TERM", "xterm-kitty")], false));
+        assert!(hyperlinks(&[("FORCE_HYPERLINK", "1")], false));
+        assert!(!hyperlinks(&[("FORCE_HYPERLINK", "0"), ("TERM", "foot")], true));
+    }
+
+    #[test]
+    fn test_image_protocols_from_env() {
+        let protocols = |vars: &[(&str, &str)], is_tty: bool| {
+            image_protocols_from_env(|name| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| (*v).to_string()), is_tty)
+        };
+        assert_eq!(protocols(&[("TERM", "xterm-kitty")], true), (true, false));
+        assert_eq!(protocols(&[("TERM_PROGRAM", "iTerm.app")], true), (false, true));
+        assert_eq!(protocols(&[("LC_TERMINAL", "iTerm2"), ("TERM", "xterm-256color")], true), (false, true));
+        assert_eq!(protocols(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux-0/default,1,0")], true), (false, false));
+        assert_eq!(protocols(&[("KITTY_WINDOW_ID", "1")], false), (false, false));
+
+        let caps = TerminalCapabilities { sixel: true, ..TerminalCapabilities::default() };
+        assert_eq!(caps.graphics_protocol(), GraphicsProtocol::Sixel);
+        assert_eq!(TerminalCapabilities::default().graphics_protocol(), GraphicsProtocol::HalfBlocks);
+    }
+
+    #[test]
+    fn test_render_mode_follows_locale() {
+        let utf8 = |vars: &[(&str, &str)]| {
+            locale_is_utf8(|name| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| (*v).to_string()))
+        };
+        assert!(utf8(&[]));
+        assert!(utf8(&[("LANG", "en_US.UTF-8")]));
+        assert!(!utf8(&[("LANG", "C")]));
+        assert!(!utf8(&[("LC_ALL", "POSIX"), ("LANG", "en_US.UTF-8")]));
+        assert!(utf8(&[("LC_ALL", ""), ("LC_CTYPE", "de_DE.utf8"), ("LANG", "C")]));
+
+        set_render_mode(RenderMode::Ascii);
+        assert!(!use_unicode());
+        set_render_mode(RenderMode::Unicode);
+        assert!(use_unicode());
+        set_render_mode(RenderMode::Auto);
+    }
+
+    #[test]
+    fn test_color_downsample() {
+        use crate::core::component::{Color, NamedColor};
+
+        let orange = Color::Rgb(255, 135, 0);
+        assert_eq!(orange.downsample(ColorSupport::TrueColor), orange);
+        assert_eq!(orange.downsample(ColorSupport::Colors256), Color::Ansi256(208));
+        assert_eq!(Color::Rgb(255, 0, 0).downsample(ColorSupport::Colors256), Color::Ansi256(196));
+        // Greys prefer the grey ramp over the cube
+        assert_eq!(Color::Rgb(128, 128, 128).downsample(ColorSupport::Colors256), Color::Ansi256(244));
+
+        assert_eq!(Color::Rgb(250, 10, 10).downsample(ColorSupport::Colors16), Color::Named(NamedColor::BrightRed));
+        assert_eq!(Color::Ansi256(1).downsample(ColorSupport::Colors16), Color::Named(NamedColor::Red));
+        assert_eq!(Color::Ansi256(21).downsample(ColorSupport::Colors16), Color::Named(NamedColor::Blue));
+        assert_eq!(orange.downsample(ColorSupport::None), Color::Default);
+    }
+}
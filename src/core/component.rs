@@ -3,7 +3,10 @@
 //! Components are the building blocks of Tuiuiu applications.
 //! They define what to render and how to respond to events.
 
+use std::rc::Rc;
+
 use crate::core::layout::LayoutNode;
+use crate::core::graphics::{GraphicsProtocol, RgbaImage};
 
 // =============================================================================
 // Component Trait
@@ -47,17 +50,50 @@ where
 #[derive(Debug, Clone)]
 pub enum VNode {
     /// A box container
-    Box(BoxNode),
+    Box(Box<BoxNode>),
     /// Text content
     Text(TextNode),
     /// Spacer element
     Spacer(SpacerNode),
     /// Fragment (multiple children, no wrapper)
     Fragment(Vec<VNode>),
+    /// Inline image
+    Image(ImageNode),
     /// Empty/null node
     Empty,
 }
 
+/// Image node properties.
+#[derive(Debug, Clone)]
+pub struct ImageNode {
+    /// Decoded pixels, shared so rebuilding the tree doesn't copy them
+    pub image: Rc<RgbaImage>,
+    /// Width in cells
+    pub width: Option<u16>,
+    /// Height in cells
+    pub height: Option<u16>,
+    /// Protocol override; detected from the terminal by default
+    pub protocol: Option<GraphicsProtocol>,
+}
+
+impl ImageNode {
+    /// Size in cells. A missing dimension follows the image's aspect ratio;
+    /// with neither, the image is shown at its pixel size.
+    pub fn cell_size(&self) -> (u16, u16) {
+        let (cell_width, cell_height) = crate::core::graphics::cell_pixel_size();
+        let (cell_width, cell_height) = (u64::from(cell_width), u64::from(cell_height));
+        let (width, height) = (u64::from(self.image.width()).max(1), u64::from(self.image.height()).max(1));
+        let clamp = |cells: u64| cells.clamp(1, u64::from(u16::MAX)) as u16;
+
+        match (self.width, self.height) {
+            (Some(w), Some(h)) => (w, h),
+            (Some(w), None) => (w, clamp((u64::from(w) * cell_width * height + width * cell_height / 2) / (width * cell_height))),
+            (None, Some(h)) => (clamp((u64::from(h) * cell_height * width + height * cell_width / 2) / (height * cell_width)), h),
+            (None, None) => (clamp((width + cell_width - 1) / cell_width), clamp((height + cell_height - 1) / cell_height)),
+        }
+    }
+}
+
 /// Box node properties.
 #[derive(Debug, Clone, Default)]
 pub struct BoxNode {
@@ -75,6 +111,10 @@ pub struct BoxNode {
 #[derive(Debug, Clone, Default)]
 pub struct BoxStyle {
     // Layout
+    /// Hide the box and its children (takes no space)
+    pub hidden: Option<bool>,
+    /// Layout mode (flexbox or grid)
+    pub display: Option<crate::core::layout::Display>,
     /// Flex direction
     pub flex_direction: Option<crate::core::layout::FlexDirection>,
     /// Justify content
@@ -89,6 +129,32 @@ pub struct BoxStyle {
     pub flex_grow: Option<f32>,
     /// Flex shrink
     pub flex_shrink: Option<f32>,
+    /// Flex basis
+    pub flex_basis: Option<crate::core::layout::Size>,
+    /// Align self (overrides the parent's align items)
+    pub align_self: Option<crate::core::layout::AlignSelf>,
+    /// Align content (distribution of wrapped lines)
+    pub align_content: Option<crate::core::layout::AlignContent>,
+    /// Gap between rows (overrides `gap`)
+    pub row_gap: Option<u16>,
+    /// Gap between columns (overrides `gap`)
+    pub column_gap: Option<u16>,
+
+    // Grid
+    /// Grid column track sizes
+    pub grid_template_columns: Option<Vec<crate::core::layout::GridTrack>>,
+    /// Grid row track sizes
+    pub grid_template_rows: Option<Vec<crate::core::layout::GridTrack>>,
+    /// Grid named areas, one string per row
+    pub grid_template_areas: Option<Vec<String>>,
+    /// Size of implicit grid rows
+    pub grid_auto_rows: Option<crate::core::layout::GridTrack>,
+    /// Grid column placement (as an item)
+    pub grid_column: Option<crate::core::layout::GridPlacement>,
+    /// Grid row placement (as an item)
+    pub grid_row: Option<crate::core::layout::GridPlacement>,
+    /// Named grid area (as an item)
+    pub grid_area: Option<String>,
 
     // Sizing
     /// Width
@@ -117,16 +183,74 @@ pub struct BoxStyle {
     pub padding_left: Option<u16>,
     /// Margin (all sides)
     pub margin: Option<u16>,
+    /// Margin top
+    pub margin_top: Option<u16>,
+    /// Margin right
+    pub margin_right: Option<u16>,
+    /// Margin bottom
+    pub margin_bottom: Option<u16>,
+    /// Margin left
+    pub margin_left: Option<u16>,
 
     // Border
     /// Border style
     pub border_style: Option<BorderStyle>,
     /// Border color
     pub border_color: Option<Color>,
+    /// Draw the top side of the border (default true)
+    pub border_top: Option<bool>,
+    /// Draw the right side of the border (default true)
+    pub border_right: Option<bool>,
+    /// Draw the bottom side of the border (default true)
+    pub border_bottom: Option<bool>,
+    /// Draw the left side of the border (default true)
+    pub border_left: Option<bool>,
+    /// Top border color, overriding `border_color`
+    pub border_top_color: Option<Color>,
+    /// Right border color, overriding `border_color`
+    pub border_right_color: Option<Color>,
+    /// Bottom border color, overriding `border_color`
+    pub border_bottom_color: Option<Color>,
+    /// Left border color, overriding `border_color`
+    pub border_left_color: Option<Color>,
+    /// Titles drawn into the top or bottom border
+    pub border_titles: Option<Vec<BorderTitle>>,
 
     // Colors
     /// Background color
     pub background: Option<Color>,
+    /// Background gradient, drawn instead of `background`
+    pub background_gradient: Option<Gradient>,
+    /// Background opacity from 0.0 to 1.0; below 1 the background tints
+    /// what's beneath instead of hiding it
+    pub opacity: Option<f32>,
+    /// Tint over everything drawn before this box, e.g. to dim the view
+    /// behind a modal
+    pub backdrop: Option<Backdrop>,
+
+    // Overflow
+    /// Overflow behavior
+    pub overflow: Option<crate::core::layout::Overflow>,
+    /// Horizontal scroll offset (overrides the tracked offset when set)
+    pub scroll_x: Option<u16>,
+    /// Vertical scroll offset (overrides the tracked offset when set)
+    pub scroll_y: Option<u16>,
+    /// Show a scrollbar when scrollable content overflows
+    pub scrollbar: Option<bool>,
+
+    // Position
+    /// Positioning scheme
+    pub position: Option<crate::core::layout::Position>,
+    /// Top offset
+    pub top: Option<u16>,
+    /// Right offset
+    pub right: Option<u16>,
+    /// Bottom offset
+    pub bottom: Option<u16>,
+    /// Left offset
+    pub left: Option<u16>,
+    /// Stacking order; boxes with a z-index are drawn as layers above content
+    pub z_index: Option<i32>,
 }
 
 /// Text node properties.
@@ -136,6 +260,36 @@ pub struct TextNode {
     pub content: String,
     /// Style properties
     pub style: TextStyle,
+    /// Styled runs making up `content`, layered over `style`; empty for
+    /// plain text
+    pub spans: Vec<TextSpan>,
+}
+
+/// Run of text with its own style inside a rich text node.
+#[derive(Debug, Clone, Default)]
+pub struct TextSpan {
+    /// Span text
+    pub text: String,
+    /// Style layered over the node's style
+    pub style: TextStyle,
+}
+
+impl TextSpan {
+    /// Create an unstyled span.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            style: TextStyle::default(),
+        }
+    }
+
+    /// Create a span with a style.
+    pub fn styled(text: impl Into<String>, style: TextStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
 }
 
 /// Text styling properties.
@@ -151,6 +305,10 @@ pub struct TextStyle {
     pub italic: bool,
     /// Underline
     pub underline: bool,
+    /// Underline style, single when unset
+    pub underline_style: Option<UnderlineStyle>,
+    /// Underline color, the text color when unset
+    pub underline_color: Option<Color>,
     /// Strikethrough
     pub strikethrough: bool,
     /// Dim
@@ -159,6 +317,12 @@ pub struct TextStyle {
     pub inverse: bool,
     /// Wrap mode
     pub wrap: Option<WrapMode>,
+    /// URL the text links to, emitted as an OSC 8 hyperlink
+    pub hyperlink: Option<String>,
+    /// Terminal cursor shown at a caret in this text
+    pub cursor: Option<TextCursor>,
+    /// Foreground gradient across the text, overriding `color`
+    pub gradient: Option<Gradient>,
 }
 
 /// Spacer node properties.
@@ -206,6 +370,130 @@ impl Default for Color {
     }
 }
 
+impl Color {
+    /// Parse a color: a name like `red` or `bright_blue`, `default`,
+    /// `#rrggbb`, `rgb(r,g,b)` or a 256-palette index like `color(208)`.
+    pub fn parse(spec: &str) -> Option<Color> {
+        let spec = spec.trim();
+        if spec.eq_ignore_ascii_case("default") {
+            return Some(Color::Default);
+        }
+        if let Some(named) = NamedColor::from_name(spec) {
+            return Some(Color::Named(named));
+        }
+        if spec.starts_with('#') {
+            let (r, g, b) = crate::core::queries::parse_color_spec(spec)?;
+            return Some(Color::Rgb(r, g, b));
+        }
+        if let Some(index) = spec.strip_prefix("color(").and_then(|rest| rest.strip_suffix(')')) {
+            return index.trim().parse().ok().map(Color::Ansi256);
+        }
+        let channels = spec.strip_prefix("rgb(")?.strip_suffix(')')?;
+        let channels: Vec<u8> = channels.split(',').map(|c| c.trim().parse().ok()).collect::<Option<_>>()?;
+        match channels[..] {
+            [r, g, b] => Some(Color::Rgb(r, g, b)),
+            _ => None,
+        }
+    }
+
+    /// RGB value of this color (`None` for the terminal default).
+    ///
+    /// Named and 256-palette colors use the standard xterm values.
+    pub fn to_rgb(self) -> Option<(u8, u8, u8)> {
+        match self {
+            Color::Default => None,
+            Color::Named(named) => Some(named.to_rgb()),
+            Color::Ansi256(index) => Some(ansi256_to_rgb(index)),
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+        }
+    }
+
+    /// Blend `over` on top of this color with an alpha from 0.0 to 1.0.
+    ///
+    /// `default` stands in for the terminal default color.
+    pub fn blend(self, over: (u8, u8, u8), alpha: f32, default: (u8, u8, u8)) -> Color {
+        if alpha <= 0.0 {
+            return self;
+        }
+        let under = self.to_rgb().unwrap_or(default);
+        let (r, g, b) = crate::core::animation::lerp_color(under, over, f64::from(alpha));
+        Color::Rgb(r, g, b)
+    }
+
+    /// Convert to the closest color the terminal can show.
+    pub fn downsample(self, support: crate::core::capabilities::ColorSupport) -> Color {
+        use crate::core::capabilities::ColorSupport;
+
+        match (support, self) {
+            (ColorSupport::None, _) => Color::Default,
+            (ColorSupport::TrueColor, color) => color,
+            (ColorSupport::Colors256, Color::Rgb(r, g, b)) => Color::Ansi256(rgb_to_ansi256(r, g, b)),
+            (ColorSupport::Colors16, Color::Ansi256(index)) if index < 16 => Color::Named(NamedColor::ALL[usize::from(index)]),
+            (ColorSupport::Colors16, Color::Ansi256(_) | Color::Rgb(..)) => {
+                let (r, g, b) = self.to_rgb().unwrap_or_default();
+                Color::Named(nearest_named(r, g, b))
+            }
+            (_, color) => color,
+        }
+    }
+}
+
+/// Levels of the 6x6x6 color cube in the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn ansi256_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => NamedColor::ALL[usize::from(index)].to_rgb(),
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[usize::from(i / 36)],
+                CUBE_LEVELS[usize::from(i / 6 % 6)],
+                CUBE_LEVELS[usize::from(i % 6)],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).unsigned_abs().pow(2);
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Nearest entry in the 256-color palette's cube or grey ramp.
+fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |v: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (i32::from(**level) - i32::from(v)).abs())
+            .map_or(0, |(i, _)| i as u8)
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    let target = (r, g, b);
+    if distance(ansi256_to_rgb(grey), target) < distance(ansi256_to_rgb(cube), target) {
+        grey
+    } else {
+        cube
+    }
+}
+
+fn nearest_named(r: u8, g: u8, b: u8) -> NamedColor {
+    NamedColor::ALL
+        .into_iter()
+        .min_by_key(|named| distance(named.to_rgb(), (r, g, b)))
+        .unwrap_or(NamedColor::White)
+}
+
 /// Named terminal colors.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NamedColor {
@@ -228,6 +516,77 @@ pub enum NamedColor {
     Gray,
 }
 
+impl NamedColor {
+    /// The 16 palette colors, in ANSI index order.
+    pub const ALL: [NamedColor; 16] = [
+        NamedColor::Black,
+        NamedColor::Red,
+        NamedColor::Green,
+        NamedColor::Yellow,
+        NamedColor::Blue,
+        NamedColor::Magenta,
+        NamedColor::Cyan,
+        NamedColor::White,
+        NamedColor::BrightBlack,
+        NamedColor::BrightRed,
+        NamedColor::BrightGreen,
+        NamedColor::BrightYellow,
+        NamedColor::BrightBlue,
+        NamedColor::BrightMagenta,
+        NamedColor::BrightCyan,
+        NamedColor::BrightWhite,
+    ];
+
+    /// Look up a color by name, ignoring case and `_`/`-` separators:
+    /// `red`, `bright_red`, `BrightRed`, `gray` or `grey`.
+    pub fn from_name(name: &str) -> Option<NamedColor> {
+        let name: String = name.chars().filter(|c| !matches!(c, '_' | '-' | ' ')).collect::<String>().to_ascii_lowercase();
+        let color = match name.as_str() {
+            "black" => NamedColor::Black,
+            "red" => NamedColor::Red,
+            "green" => NamedColor::Green,
+            "yellow" => NamedColor::Yellow,
+            "blue" => NamedColor::Blue,
+            "magenta" => NamedColor::Magenta,
+            "cyan" => NamedColor::Cyan,
+            "white" => NamedColor::White,
+            "brightblack" => NamedColor::BrightBlack,
+            "brightred" => NamedColor::BrightRed,
+            "brightgreen" => NamedColor::BrightGreen,
+            "brightyellow" => NamedColor::BrightYellow,
+            "brightblue" => NamedColor::BrightBlue,
+            "brightmagenta" => NamedColor::BrightMagenta,
+            "brightcyan" => NamedColor::BrightCyan,
+            "brightwhite" => NamedColor::BrightWhite,
+            "gray" | "grey" => NamedColor::Gray,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    /// Default xterm RGB value of this color.
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        match self {
+            NamedColor::Black => (0, 0, 0),
+            NamedColor::Red => (205, 0, 0),
+            NamedColor::Green => (0, 205, 0),
+            NamedColor::Yellow => (205, 205, 0),
+            NamedColor::Blue => (0, 0, 238),
+            NamedColor::Magenta => (205, 0, 205),
+            NamedColor::Cyan => (0, 205, 205),
+            NamedColor::White => (229, 229, 229),
+            NamedColor::BrightBlack | NamedColor::Gray => (127, 127, 127),
+            NamedColor::BrightRed => (255, 0, 0),
+            NamedColor::BrightGreen => (0, 255, 0),
+            NamedColor::BrightYellow => (255, 255, 0),
+            NamedColor::BrightBlue => (92, 92, 255),
+            NamedColor::BrightMagenta => (255, 0, 255),
+            NamedColor::BrightCyan => (0, 255, 255),
+            NamedColor::BrightWhite => (255, 255, 255),
+        }
+    }
+}
+
 /// Border style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum BorderStyle {
@@ -243,8 +602,249 @@ pub enum BorderStyle {
     Classic,
 }
 
-/// Text wrap mode.
+/// Shape of the terminal cursor (DECSCUSR).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorShape {
+    #[default]
+    Block,
+    Underline,
+    Bar,
+}
+
+impl CursorShape {
+    /// DECSCUSR parameter for this shape.
+    pub fn decscusr(self, blinking: bool) -> u8 {
+        let steady = match self {
+            CursorShape::Block => 2,
+            CursorShape::Underline => 4,
+            CursorShape::Bar => 6,
+        };
+        if blinking { steady - 1 } else { steady }
+    }
+}
+
+/// Caret in a text node where the terminal cursor is placed, so IMEs and
+/// screen magnifiers can follow it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextCursor {
+    /// Caret position, in characters from the start of the text
+    pub offset: usize,
+    /// Cursor shape
+    pub shape: CursorShape,
+    /// Whether the cursor blinks
+    pub blinking: bool,
+}
+
+impl TextCursor {
+    /// A blinking bar cursor at a caret offset.
+    pub fn at(offset: usize) -> Self {
+        Self { offset, shape: CursorShape::Bar, blinking: true }
+    }
+}
+
+/// Horizontal alignment of a border title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TitleAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Border side a title sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TitlePosition {
+    #[default]
+    Top,
+    Bottom,
+}
+
+/// Tint laid over content behind a box, such as a modal's dimmed backdrop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backdrop {
+    /// Color blended over the content
+    pub color: Color,
+    /// How strongly it is blended, from 0.0 to 1.0
+    pub opacity: f32,
+}
+
+impl Backdrop {
+    /// A tint of `color` at `opacity`.
+    pub fn new(color: Color, opacity: f32) -> Self {
+        Self { color, opacity: opacity.clamp(0.0, 1.0) }
+    }
+
+    /// Darken content to half brightness.
+    pub fn dim() -> Self {
+        Self::new(Color::Rgb(0, 0, 0), 0.5)
+    }
+}
+
+impl Default for Backdrop {
+    fn default() -> Self {
+        Self::dim()
+    }
+}
+
+/// Direction a gradient runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientAxis {
+    /// Left to right
+    #[default]
+    Horizontal,
+    /// Top to bottom
+    Vertical,
+}
+
+/// Color space a gradient is interpolated in.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientSpace {
+    /// Straight RGB blending
+    #[default]
+    Rgb,
+    /// Through hue, which keeps colors saturated between the stops
+    Hsl,
+}
+
+/// Linear gradient through evenly spaced color stops.
+///
+/// Cells get true colors, which are downsampled for 256- and 16-color
+/// terminals when the frame is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gradient {
+    /// Color stops; the terminal default counts as black
+    pub stops: Vec<Color>,
+    pub axis: GradientAxis,
+    pub space: GradientSpace,
+}
+
+impl Gradient {
+    /// A horizontal RGB gradient through `stops`.
+    pub fn new(stops: impl IntoIterator<Item = Color>) -> Self {
+        Self {
+            stops: stops.into_iter().collect(),
+            axis: GradientAxis::default(),
+            space: GradientSpace::default(),
+        }
+    }
+
+    /// A left-to-right gradient between two colors.
+    pub fn horizontal(from: Color, to: Color) -> Self {
+        Self::new([from, to])
+    }
+
+    /// A top-to-bottom gradient between two colors.
+    pub fn vertical(from: Color, to: Color) -> Self {
+        Self::new([from, to]).axis(GradientAxis::Vertical)
+    }
+
+    /// Set the direction.
+    pub fn axis(mut self, axis: GradientAxis) -> Self {
+        self.axis = axis;
+        self
+    }
+
+    /// Interpolate in HSL instead of RGB.
+    pub fn hsl(mut self) -> Self {
+        self.space = GradientSpace::Hsl;
+        self
+    }
+
+    /// Color at `t`, from 0.0 at the first stop to 1.0 at the last.
+    pub fn at(&self, t: f32) -> Color {
+        use crate::core::animation::{lerp_color, lerp_color_hsl};
+
+        let rgb = |i: usize| self.stops[i].to_rgb().unwrap_or_default();
+        match self.stops.len() {
+            0 => Color::Default,
+            1 => self.stops[0],
+            n => {
+                let position = f64::from(t.clamp(0.0, 1.0)) * (n - 1) as f64;
+                let i = (position as usize).min(n - 2);
+                let local = position - i as f64;
+                let (r, g, b) = match self.space {
+                    GradientSpace::Rgb => lerp_color(rgb(i), rgb(i + 1), local),
+                    GradientSpace::Hsl => lerp_color_hsl(rgb(i), rgb(i + 1), local),
+                };
+                Color::Rgb(r, g, b)
+            }
+        }
+    }
+
+    /// Color of cell `index` out of `count` along the axis.
+    pub fn step(&self, index: u16, count: u16) -> Color {
+        if count <= 1 {
+            return self.at(0.0);
+        }
+        self.at(f32::from(index) / f32::from(count - 1))
+    }
+}
+
+/// Text drawn into a box border, like `┌─ Files ───┐`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BorderTitle {
+    /// Title text
+    pub text: String,
+    /// Alignment along the border
+    pub align: TitleAlign,
+    /// Top or bottom border
+    pub position: TitlePosition,
+    /// Text color (defaults to the border color)
+    pub color: Option<Color>,
+}
+
+impl BorderTitle {
+    /// Create a left-aligned title on the top border.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the alignment.
+    pub fn align(mut self, align: TitleAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Center the title.
+    pub fn center(self) -> Self {
+        self.align(TitleAlign::Center)
+    }
+
+    /// Align the title to the right.
+    pub fn right(self) -> Self {
+        self.align(TitleAlign::Right)
+    }
+
+    /// Put the title on the bottom border.
+    pub fn bottom(mut self) -> Self {
+        self.position = TitlePosition::Bottom;
+        self
+    }
+
+    /// Set the text color.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+impl From<&str> for BorderTitle {
+    fn from(text: &str) -> Self {
+        BorderTitle::new(text)
+    }
+}
+
+impl From<String> for BorderTitle {
+    fn from(text: String) -> Self {
+        BorderTitle::new(text)
+    }
+}
+
+/// Text wrap mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum WrapMode {
     #[default]
     None,
@@ -253,6 +853,17 @@ pub enum WrapMode {
     Truncate,
 }
 
+/// Underline style; anything but `Single` needs terminal support (SGR 4:x).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UnderlineStyle {
+    #[default]
+    Single,
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
 // =============================================================================
 // Child Types
 // =============================================================================
@@ -313,6 +924,7 @@ fn child_to_vnodes(child: Child) -> Vec<VNode> {
         Child::Text(s) => vec![VNode::Text(TextNode {
             content: s,
             style: TextStyle::default(),
+            ..Default::default()
         })],
         Child::Many(children) => children.into_iter().flat_map(child_to_vnodes).collect(),
         Child::Empty => vec![],
@@ -329,6 +941,7 @@ impl VNode {
         VNode::Text(TextNode {
             content: content.into(),
             style: TextStyle::default(),
+            ..Default::default()
         })
     }
 
@@ -337,39 +950,40 @@ impl VNode {
         VNode::Text(TextNode {
             content: content.into(),
             style,
+            ..Default::default()
         })
     }
 
     /// Create a box with children.
     pub fn container(children: Vec<VNode>) -> Self {
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             ..Default::default()
-        })
+        }))
     }
 
     /// Create a column box.
     pub fn column(children: Vec<VNode>) -> Self {
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle {
                 flex_direction: Some(crate::core::layout::FlexDirection::Column),
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }))
     }
 
     /// Create a row box.
     pub fn row(children: Vec<VNode>) -> Self {
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle {
                 flex_direction: Some(crate::core::layout::FlexDirection::Row),
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }))
     }
 }
 
@@ -379,6 +993,7 @@ impl TextNode {
         Self {
             content: content.into(),
             style: TextStyle::default(),
+            spans: Vec::new(),
         }
     }
 
@@ -390,6 +1005,7 @@ impl TextNode {
                 color: Some(color),
                 ..Default::default()
             },
+            spans: Vec::new(),
         }
     }
 
@@ -401,6 +1017,7 @@ impl TextNode {
                 bold: true,
                 ..Default::default()
             },
+            spans: Vec::new(),
         }
     }
 
@@ -413,6 +1030,7 @@ impl TextNode {
                 bold: true,
                 ..Default::default()
             },
+            spans: Vec::new(),
         }
     }
 }
@@ -450,6 +1068,26 @@ impl TextStyle {
             ..Default::default()
         }
     }
+    /// Layer `over` on top of this style: its colors and options win where
+    /// set, and attribute flags add up.
+    pub fn merge(&self, over: &TextStyle) -> TextStyle {
+        TextStyle {
+            color: over.color.or(self.color),
+            background: over.background.or(self.background),
+            bold: self.bold || over.bold,
+            italic: self.italic || over.italic,
+            underline: self.underline || over.underline,
+            underline_style: over.underline_style.or(self.underline_style),
+            underline_color: over.underline_color.or(self.underline_color),
+            strikethrough: self.strikethrough || over.strikethrough,
+            dim: self.dim || over.dim,
+            inverse: self.inverse || over.inverse,
+            wrap: over.wrap.or(self.wrap),
+            hyperlink: over.hyperlink.clone().or_else(|| self.hyperlink.clone()),
+            cursor: over.cursor.or(self.cursor),
+            gradient: over.gradient.clone().or_else(|| self.gradient.clone()),
+        }
+    }
 }
 
 impl BoxStyle {
@@ -475,6 +1113,21 @@ impl BoxStyle {
         self
     }
 
+    /// Add a border title.
+    pub fn with_title(mut self, title: impl Into<BorderTitle>) -> Self {
+        self.border_titles.get_or_insert_with(Vec::new).push(title.into());
+        self
+    }
+
+    /// Whether each border side is drawn, as `[top, right, bottom, left]`;
+    /// all false without a border.
+    pub fn border_sides(&self) -> [bool; 4] {
+        if self.border_style.is_none() {
+            return [false; 4];
+        }
+        [self.border_top, self.border_right, self.border_bottom, self.border_left].map(|side| side.unwrap_or(true))
+    }
+
     /// Set padding all sides.
     pub fn with_padding(mut self, padding: u16) -> Self {
         self.padding = Some(padding);
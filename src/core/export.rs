@@ -0,0 +1,531 @@
+//! Screenshot Export
+//!
+//! Turns an `OutputBuffer` into a standalone HTML `<pre>` document or an
+//! SVG with a terminal window frame, for docs and bug reports.
+
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::core::component::{Color, TextStyle, UnderlineStyle};
+use crate::core::renderer::{Cell, OutputBuffer};
+use crate::utils::ansi::parse_ansi;
+use crate::utils::text::char_width;
+
+// =============================================================================
+// Options
+// =============================================================================
+
+/// Options for exporting a buffer.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Document title; the SVG shows it in the window frame
+    pub title: Option<String>,
+    /// Color of text without a foreground color
+    pub foreground: (u8, u8, u8),
+    /// Color behind cells without a background color
+    pub background: (u8, u8, u8),
+    /// CSS font family
+    pub font_family: String,
+    /// Font size in pixels
+    pub font_size: f32,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            title: None,
+            foreground: (229, 229, 229),
+            background: (30, 30, 30),
+            font_family: "ui-monospace, 'SF Mono', Menlo, Consolas, 'DejaVu Sans Mono', monospace".to_string(),
+            font_size: 14.0,
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn colors(mut self, foreground: (u8, u8, u8), background: (u8, u8, u8)) -> Self {
+        self.foreground = foreground;
+        self.background = background;
+        self
+    }
+
+    pub fn font_family(mut self, family: impl Into<String>) -> Self {
+        self.font_family = family.into();
+        self
+    }
+
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+}
+
+impl OutputBuffer {
+    /// Export as a standalone HTML document with default options.
+    pub fn to_html(&self) -> String {
+        to_html(self, &ExportOptions::default())
+    }
+
+    /// Export as an SVG in a terminal window frame with default options.
+    pub fn to_svg(&self) -> String {
+        to_svg(self, &ExportOptions::default())
+    }
+}
+
+// =============================================================================
+// Runs
+// =============================================================================
+
+/// Cells of a row sharing a style and hyperlink.
+struct Run<'a> {
+    text: String,
+    /// First display column
+    column: usize,
+    /// Width in display columns; wide characters count twice
+    width: usize,
+    cell: &'a Cell,
+}
+
+impl Run<'_> {
+    /// Whether the run draws nothing beyond the default background.
+    fn is_blank(&self) -> bool {
+        let cell = self.cell;
+        self.text.chars().all(|c| c == ' ')
+            && cell.bg == Color::Default
+            && !cell.inverse
+            && !cell.underline
+            && !cell.strikethrough
+            && cell.hyperlink.is_none()
+    }
+}
+
+/// Split a row into runs, dropping blank runs at the end.
+fn row_runs(buffer: &OutputBuffer, y: u16) -> Vec<Run<'_>> {
+    let mut runs: Vec<Run> = Vec::new();
+    let mut column = 0;
+
+    for x in 0..buffer.size().0 {
+        let (Some(cell), Some(c)) = (buffer.get(x, y), buffer.output_char(x, y)) else {
+            continue;
+        };
+        let width = char_width(c);
+        match runs.last_mut() {
+            Some(run) if run.cell.same_style(cell) && run.cell.hyperlink == cell.hyperlink => {
+                run.text.push(c);
+                run.width += width;
+            }
+            _ => runs.push(Run { text: c.to_string(), column, width, cell }),
+        }
+        column += width;
+    }
+
+    while runs.last().is_some_and(Run::is_blank) {
+        runs.pop();
+    }
+    runs
+}
+
+/// Red, green and blue channels.
+type Rgb = (u8, u8, u8);
+
+/// Foreground and (non-default) background of a cell, after inverse and dim.
+fn cell_colors(cell: &Cell, options: &ExportOptions) -> (Rgb, Option<Rgb>) {
+    let fg = cell.fg.to_rgb().unwrap_or(options.foreground);
+    let bg = cell.bg.to_rgb();
+    let (fg, bg) = if cell.inverse { (bg.unwrap_or(options.background), Some(fg)) } else { (fg, bg) };
+    let fg = if cell.dim { mix(fg, bg.unwrap_or(options.background)) } else { fg };
+    (fg, bg)
+}
+
+/// Halfway between two colors.
+fn mix(a: Rgb, b: Rgb) -> Rgb {
+    let half = |a: u8, b: u8| ((u16::from(a) + u16::from(b)) / 2) as u8;
+    (half(a.0, b.0), half(a.1, b.1), half(a.2, b.2))
+}
+
+fn hex((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// CSS `text-decoration` for a cell, if it has one.
+fn text_decoration(cell: &Cell) -> Option<String> {
+    let mut lines = Vec::new();
+    if cell.underline {
+        lines.push("underline");
+    }
+    if cell.strikethrough {
+        lines.push("line-through");
+    }
+    if lines.is_empty() {
+        return None;
+    }
+
+    let mut decoration = lines.join(" ");
+    if cell.underline {
+        let style = match cell.underline_style {
+            UnderlineStyle::Single => "",
+            UnderlineStyle::Double => " double",
+            UnderlineStyle::Curly => " wavy",
+            UnderlineStyle::Dotted => " dotted",
+            UnderlineStyle::Dashed => " dashed",
+        };
+        decoration.push_str(style);
+        if let Some(color) = cell.underline_color.to_rgb() {
+            decoration.push(' ');
+            decoration.push_str(&hex(color));
+        }
+    }
+    Some(decoration)
+}
+
+/// Escape text for HTML and XML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c if c.is_control() => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Only web links are exported as anchors.
+fn safe_url(url: &Rc<str>) -> Option<&str> {
+    let lower = url.to_ascii_lowercase();
+    ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+        .then_some(&**url)
+}
+
+// =============================================================================
+// HTML
+// =============================================================================
+
+/// Export a buffer as a standalone HTML document.
+///
+/// Each row becomes a line of a `<pre>` block, with runs of styled cells
+/// in `<span>`s and hyperlinks as `<a>` elements.
+pub fn to_html(buffer: &OutputBuffer, options: &ExportOptions) -> String {
+    let title = escape(options.title.as_deref().unwrap_or("tuiuiu"));
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         pre.tuiuiu {{ margin: 0; padding: 1em; color: {}; background: {}; font-family: {}; font-size: {}px; line-height: 1.2; }}\n\
+         pre.tuiuiu a {{ color: inherit; }}\n\
+         </style>\n</head>\n<body>\n<pre class=\"tuiuiu\">",
+        title,
+        hex(options.foreground),
+        hex(options.background),
+        // Style content isn't entity-decoded, so only keep it from closing the tag
+        options.font_family.replace(['<', '>'], ""),
+        number(options.font_size),
+    );
+
+    for y in 0..buffer.size().1 {
+        if y > 0 {
+            html.push('\n');
+        }
+        for run in row_runs(buffer, y) {
+            let text = escape(&run.text);
+            let text = match run.cell.hyperlink.as_ref().and_then(safe_url) {
+                Some(url) => format!("<a href=\"{}\">{}</a>", escape(url), text),
+                None => text,
+            };
+            let css = run_css(run.cell, options);
+            if css.is_empty() {
+                html.push_str(&text);
+            } else {
+                let _ = write!(html, "<span style=\"{}\">{}</span>", css, text);
+            }
+        }
+    }
+
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+/// Inline CSS for a run; empty for default-styled text.
+fn run_css(cell: &Cell, options: &ExportOptions) -> String {
+    let (fg, bg) = cell_colors(cell, options);
+    let mut css = Vec::new();
+    if fg != options.foreground {
+        css.push(format!("color: {}", hex(fg)));
+    }
+    if let Some(bg) = bg {
+        css.push(format!("background: {}", hex(bg)));
+    }
+    if cell.bold {
+        css.push("font-weight: bold".to_string());
+    }
+    if cell.italic {
+        css.push("font-style: italic".to_string());
+    }
+    if let Some(decoration) = text_decoration(cell) {
+        css.push(format!("text-decoration: {}", decoration));
+    }
+    css.join("; ")
+}
+
+// =============================================================================
+// SVG
+// =============================================================================
+
+/// Height of the window title bar.
+const TITLE_BAR: f32 = 32.0;
+/// Space between the frame and the cells.
+const PADDING: f32 = 12.0;
+
+/// Export a buffer as an SVG image framed like a terminal window.
+///
+/// Cells are laid out on a fixed grid, so the image looks the same
+/// whatever monospace font the viewer falls back to.
+pub fn to_svg(buffer: &OutputBuffer, options: &ExportOptions) -> String {
+    let cell_width = options.font_size * 0.6;
+    let line_height = options.font_size * 1.25;
+    let rows: Vec<Vec<Run>> = (0..buffer.size().1).map(|y| row_runs(buffer, y)).collect();
+    let columns = rows
+        .iter()
+        .filter_map(|runs| runs.last().map(|run| run.column + run.width))
+        .max()
+        .unwrap_or(0)
+        .max(usize::from(buffer.size().0));
+
+    // Wide enough for the window buttons
+    let width = (columns as f32 * cell_width + PADDING * 2.0).max(80.0);
+    let height = rows.len() as f32 * line_height + TITLE_BAR + PADDING;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" \
+         font-family=\"{}\" font-size=\"{}\">",
+        escape(&options.font_family),
+        number(options.font_size),
+        w = number(width),
+        h = number(height),
+    );
+    svg.push_str("<style>text { white-space: pre; }</style>\n");
+
+    // Window frame
+    let _ = writeln!(
+        svg,
+        "<rect width=\"{}\" height=\"{}\" rx=\"8\" fill=\"{}\"/>",
+        number(width),
+        number(height),
+        hex(options.background)
+    );
+    for (i, color) in ["#ff5f57", "#febc2e", "#28c840"].iter().enumerate() {
+        let _ = writeln!(svg, "<circle cx=\"{}\" cy=\"16\" r=\"6\" fill=\"{}\"/>", 20 + i * 20, color);
+    }
+    if let Some(title) = &options.title {
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"21\" text-anchor=\"middle\" fill=\"{}\" opacity=\"0.6\">{}</text>",
+            number(width / 2.0),
+            hex(options.foreground),
+            escape(title)
+        );
+    }
+
+    let _ = writeln!(svg, "<g transform=\"translate({} {})\">", number(PADDING), number(TITLE_BAR));
+    for (row, runs) in rows.iter().enumerate() {
+        let top = row as f32 * line_height;
+        for run in runs {
+            let (fg, bg) = cell_colors(run.cell, options);
+            let x = run.column as f32 * cell_width;
+            let run_width = run.width as f32 * cell_width;
+            if let Some(bg) = bg {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    number(x),
+                    number(top),
+                    number(run_width),
+                    number(line_height),
+                    hex(bg)
+                );
+            }
+            if run.text.trim().is_empty() && !run.cell.underline && !run.cell.strikethrough {
+                continue;
+            }
+
+            let mut text = format!(
+                "<text x=\"{}\" y=\"{}\" fill=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\"",
+                number(x),
+                number(top + options.font_size),
+                hex(fg),
+                number(run_width)
+            );
+            if run.cell.bold {
+                text.push_str(" font-weight=\"bold\"");
+            }
+            if run.cell.italic {
+                text.push_str(" font-style=\"italic\"");
+            }
+            if let Some(decoration) = text_decoration(run.cell) {
+                let _ = write!(text, " style=\"text-decoration: {}\"", decoration);
+            }
+            let _ = write!(text, ">{}</text>", escape(&run.text));
+
+            match run.cell.hyperlink.as_ref().and_then(safe_url) {
+                Some(url) => {
+                    let _ = writeln!(svg, "<a href=\"{}\">{}</a>", escape(url), text);
+                }
+                None => {
+                    svg.push_str(&text);
+                    svg.push('\n');
+                }
+            }
+        }
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// Format a length without trailing zeros.
+fn number(value: f32) -> String {
+    let formatted = format!("{:.2}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// =============================================================================
+// ANSI Input
+// =============================================================================
+
+/// Build a buffer from text with ANSI escape codes, such as captured
+/// program output.
+///
+/// SGR colors and attributes and OSC 8 hyperlinks are kept; other escape
+/// sequences are dropped. The buffer is as wide as the longest line
+/// unless `width` is given, and as tall as the number of lines.
+pub fn buffer_from_ansi(input: &str, width: Option<u16>) -> OutputBuffer {
+    let mut rows: Vec<Vec<Cell>> = vec![Vec::new()];
+    for span in parse_ansi(input) {
+        let pen = span_cell(&span.style);
+        for c in span.text.chars() {
+            if c == '\n' {
+                rows.push(Vec::new());
+            } else if let Some(row) = rows.last_mut() {
+                // Wide characters cover two cells; zero-width ones none
+                let cell = Cell { char: c, ..pen.clone() };
+                match char_width(c) {
+                    0 => {}
+                    1 => row.push(cell),
+                    _ => {
+                        let rest = cell.continuation();
+                        row.extend([cell, rest]);
+                    }
+                }
+            }
+        }
+    }
+    // As with `str::lines`, a final newline doesn't start another line
+    if rows.last().is_some_and(Vec::is_empty) {
+        rows.pop();
+    }
+
+    let width = width.unwrap_or_else(|| {
+        let longest = rows.iter().map(Vec::len).max().unwrap_or(0);
+        u16::try_from(longest).unwrap_or(u16::MAX)
+    });
+    let height = u16::try_from(rows.len()).unwrap_or(u16::MAX);
+
+    let mut buffer = OutputBuffer::new(width, height);
+    buffer.set_unicode(true);
+    for (y, row) in rows.into_iter().enumerate().take(usize::from(height)) {
+        for (x, cell) in row.into_iter().enumerate().take(usize::from(width)) {
+            buffer.set(x as u16, y as u16, cell);
+        }
+    }
+    buffer
+}
+
+/// Blank cell with the attributes of a parsed span.
+fn span_cell(style: &TextStyle) -> Cell {
+    Cell {
+        fg: style.color.unwrap_or_default(),
+        bg: style.background.unwrap_or_default(),
+        bold: style.bold,
+        dim: style.dim,
+        italic: style.italic,
+        underline: style.underline,
+        underline_style: style.underline_style.unwrap_or_default(),
+        underline_color: style.underline_color.unwrap_or_default(),
+        inverse: style.inverse,
+        strikethrough: style.strikethrough,
+        hyperlink: style.hyperlink.as_deref().map(Rc::from),
+        ..Cell::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::component::NamedColor;
+
+    fn sample() -> OutputBuffer {
+        buffer_from_ansi(
+            "\x1B[1;31mError\x1B[0m: <tag> & \x1B[4:3;58;5;226mtypo\x1B[0m\n\
+             \x1B]8;;https://example.com\x1B\\link\x1B]8;;\x1B\\ 漢字\x1B[7m!\x1B[0m",
+            None,
+        )
+    }
+
+    #[test]
+    fn test_buffer_from_ansi() {
+        let buffer = sample();
+        assert_eq!(buffer.size(), (19, 2));
+
+        let e = buffer.get(0, 0).unwrap();
+        assert_eq!((e.char, e.bold, e.fg), ('E', true, Color::Named(NamedColor::Red)));
+        let t = buffer.get(15, 0).unwrap();
+        assert_eq!(t.char, 't');
+        assert!(t.underline);
+        assert_eq!(t.underline_style, UnderlineStyle::Curly);
+        assert_eq!(t.underline_color, Color::Ansi256(226));
+        assert_eq!(buffer.get(0, 1).unwrap().hyperlink.as_deref(), Some("https://example.com"));
+        assert_eq!(buffer.get(4, 1).unwrap().hyperlink, None);
+    }
+
+    #[test]
+    fn test_to_html() {
+        let html = to_html(&sample(), &ExportOptions::new().title("Demo <1>"));
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Demo &lt;1&gt;</title>"));
+        assert!(html.contains("<span style=\"color: #cd0000; font-weight: bold\">Error</span>: &lt;tag&gt; &amp; "));
+        assert!(html.contains("text-decoration: underline wavy #ffff00\">typo</span>"));
+        assert!(html.contains("<a href=\"https://example.com\">link</a> 漢字"));
+        // Inverse swaps in the default colors
+        assert!(html.contains("<span style=\"color: #1e1e1e; background: #e5e5e5\">!</span></pre>"));
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let svg = to_svg(&sample(), &ExportOptions::new().title("Demo"));
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(">Demo</text>"));
+        assert!(svg.contains("font-weight=\"bold\">Error</text>"));
+        assert!(svg.contains("<a href=\"https://example.com\"><text x=\"0\""));
+        // Wide characters take two columns, so the inverse cell after
+        // " 漢字" starts at column 9
+        assert!(svg.contains(&format!("<rect x=\"{}\" y=\"17.5\"", number(9.0 * 14.0 * 0.6))));
+    }
+}
@@ -1 +1,907 @@
-//! Stub module - to be implemented
+//! Terminal Graphics
+//!
+//! Inline images for terminals that can show them:
+//! - **Decoding**: PPM/PGM, BMP and QOI files without external crates
+//! - **Protocols**: Kitty graphics, iTerm2 inline images and Sixel
+//! - **Fallback**: Half-block and quadrant characters everywhere else
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::core::component::Color;
+use crate::core::renderer::Cell;
+
+/// Largest image the decoders accept, in pixels.
+const MAX_PIXELS: u64 = 1 << 26;
+
+/// Cell size assumed when the terminal doesn't report one.
+const DEFAULT_CELL_SIZE: (u16, u16) = (10, 20);
+
+// =============================================================================
+// Images
+// =============================================================================
+
+/// Decoded image with 8-bit RGBA pixels, row by row from the top left.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    /// Content hash, used as the Kitty image ID and to cache encodings
+    id: u32,
+}
+
+impl fmt::Debug for RgbaImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RgbaImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RgbaImage {
+    /// Wrap RGBA pixel data, which must hold exactly `width * height` pixels.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> io::Result<Self> {
+        let expected = u64::from(width) * u64::from(height) * 4;
+        if pixels.len() as u64 != expected {
+            return Err(invalid("pixel data does not match the image size"));
+        }
+        let id = content_id(width, height, &pixels);
+        Ok(Self { width, height, pixels, id })
+    }
+
+    /// Build an image from a function of each pixel's position.
+    pub fn from_fn(width: u32, height: u32, mut pixel: impl FnMut(u32, u32) -> [u8; 4]) -> Self {
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend_from_slice(&pixel(x, y));
+            }
+        }
+        let id = content_id(width, height, &pixels);
+        Self { width, height, pixels, id }
+    }
+
+    /// Decode a PPM/PGM, BMP or QOI file.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        decode(&fs::read(path)?)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Raw RGBA bytes.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// RGBA value of a pixel; transparent outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        if x >= self.width || y >= self.height {
+            return [0; 4];
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    /// Scale to a new size, averaging the source pixels under each target.
+    ///
+    /// Colors are weighted by alpha so transparent pixels don't bleed.
+    pub fn resize(&self, width: u32, height: u32) -> RgbaImage {
+        if (width, height) == (self.width, self.height) || self.width == 0 || self.height == 0 {
+            return RgbaImage::from_fn(width, height, |x, y| self.pixel(x, y));
+        }
+        let span = |t: u32, target: u32, source: u32| {
+            let start = u64::from(t) * u64::from(source) / u64::from(target);
+            let end = (u64::from(t + 1) * u64::from(source) / u64::from(target)).max(start + 1);
+            start as u32..end as u32
+        };
+        RgbaImage::from_fn(width, height, |tx, ty| {
+            let mut sum = [0u64; 4];
+            let mut count = 0u64;
+            for y in span(ty, height, self.height) {
+                for x in span(tx, width, self.width) {
+                    let [r, g, b, a] = self.pixel(x, y).map(u64::from);
+                    sum[0] += r * a;
+                    sum[1] += g * a;
+                    sum[2] += b * a;
+                    sum[3] += a;
+                    count += 1;
+                }
+            }
+            if sum[3] == 0 {
+                return [0; 4];
+            }
+            let channel = |value: u64| (value / sum[3]) as u8;
+            [channel(sum[0]), channel(sum[1]), channel(sum[2]), (sum[3] / count) as u8]
+        })
+    }
+}
+
+/// FNV-1a over the size and pixels; never zero, since Kitty reserves ID 0.
+fn content_id(width: u32, height: u32, pixels: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in width.to_le_bytes().iter().chain(&height.to_le_bytes()).chain(pixels) {
+        hash = (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193);
+    }
+    hash.max(1)
+}
+
+// =============================================================================
+// Decoding
+// =============================================================================
+
+/// Decode an image, picking the format from its magic bytes.
+pub fn decode(data: &[u8]) -> io::Result<RgbaImage> {
+    match data {
+        [b'P', b'2' | b'3' | b'5' | b'6', ..] => decode_ppm(data),
+        [b'B', b'M', ..] => decode_bmp(data),
+        [b'q', b'o', b'i', b'f', ..] => decode_qoi(data),
+        _ => Err(invalid("unrecognized image format")),
+    }
+}
+
+/// Decode a Netpbm image: PPM (`P3`, `P6`) or PGM (`P2`, `P5`).
+pub fn decode_ppm(data: &[u8]) -> io::Result<RgbaImage> {
+    let mut reader = Reader { data, pos: 2 };
+    let (gray, binary) = match data.get(..2) {
+        Some(b"P2") => (true, false),
+        Some(b"P3") => (false, false),
+        Some(b"P5") => (true, true),
+        Some(b"P6") => (false, true),
+        _ => return Err(invalid("not a PPM or PGM image")),
+    };
+    let width = reader.ppm_number()?;
+    let height = reader.ppm_number()?;
+    let max = reader.ppm_number()?;
+    if max == 0 || max > 65535 {
+        return Err(invalid("PPM maximum value out of range"));
+    }
+    check_size(width, height)?;
+
+    // A single whitespace byte separates the header from binary samples
+    if binary {
+        reader.bytes(1)?;
+    }
+    // Every sample takes at least a byte (two above 255 in binary files)
+    let channels = if gray { 1 } else { 3 };
+    let sample_size = if binary && max > 255 { 2 } else { 1 };
+    reader.ensure(u64::from(width) * u64::from(height) * channels * sample_size)?;
+
+    let mut sample = || -> io::Result<u8> {
+        let value = match (binary, max > 255) {
+            (false, _) => reader.ppm_number()?,
+            (true, false) => u32::from(reader.bytes(1)?[0]),
+            (true, true) => {
+                let bytes = reader.bytes(2)?;
+                u32::from(u16::from_be_bytes([bytes[0], bytes[1]]))
+            }
+        };
+        Ok((value.min(max) * 255 / max) as u8)
+    };
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for _ in 0..u64::from(width) * u64::from(height) {
+        if gray {
+            let v = sample()?;
+            pixels.extend_from_slice(&[v, v, v, 255]);
+        } else {
+            pixels.extend_from_slice(&[sample()?, sample()?, sample()?, 255]);
+        }
+    }
+    RgbaImage::new(width, height, pixels)
+}
+
+/// Decode an uncompressed BMP with 8-bit palette, 24-bit or 32-bit pixels.
+pub fn decode_bmp(data: &[u8]) -> io::Result<RgbaImage> {
+    let mut reader = Reader { data, pos: 10 };
+    let offset = reader.u32_le()? as usize;
+    let header_size = reader.u32_le()?;
+    if header_size < 40 {
+        return Err(invalid("unsupported BMP header"));
+    }
+    let width = reader.u32_le()? as i32;
+    let height = reader.u32_le()? as i32;
+    reader.bytes(2)?; // planes
+    let bits = reader.u16_le()?;
+    let compression = reader.u32_le()?;
+    reader.bytes(12)?; // image size and resolution
+    let palette_size = reader.u32_le()?;
+
+    // Negative heights store rows top-down
+    let top_down = height < 0;
+    let (width, height) = (width.unsigned_abs(), height.unsigned_abs());
+    if width == 0 || width > i32::MAX as u32 {
+        return Err(invalid("BMP width out of range"));
+    }
+    check_size(width, height)?;
+
+    // Channel masks live in the header from V3 on, or right after it
+    let masks = match (compression, bits) {
+        (0, 32) => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+        (3, 32) => {
+            let at = if header_size >= 52 { 54 } else { 14 + header_size as usize };
+            let mut masks = Reader { data, pos: at };
+            let (r, g, b) = (masks.u32_le()?, masks.u32_le()?, masks.u32_le()?);
+            let a = if header_size >= 56 { masks.u32_le()? } else { 0 };
+            [r, g, b, a]
+        }
+        (0, 8 | 24) => [0; 4],
+        _ => return Err(invalid("unsupported BMP compression or bit depth")),
+    };
+
+    let palette = if bits == 8 {
+        let count = if palette_size == 0 { 256 } else { palette_size.min(256) as usize };
+        let mut table = Reader { data, pos: 14 + header_size as usize };
+        table.bytes(count * 4)?.chunks(4).map(|c| [c[2], c[1], c[0], 255]).collect()
+    } else {
+        Vec::new()
+    };
+
+    let stride = (width as usize * usize::from(bits) + 31) / 32 * 4;
+    if (offset as u64).saturating_add(stride as u64 * u64::from(height)) > data.len() as u64 {
+        return Err(invalid("truncated image"));
+    }
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    for row in 0..height as usize {
+        let mut line = Reader { data, pos: offset + row * stride };
+        let line = line.bytes(stride)?;
+        let y = if top_down { row } else { height as usize - 1 - row };
+        for x in 0..width as usize {
+            let rgba = match bits {
+                8 => palette.get(usize::from(line[x])).copied().unwrap_or([0, 0, 0, 255]),
+                24 => [line[x * 3 + 2], line[x * 3 + 1], line[x * 3], 255],
+                _ => {
+                    let value = u32::from_le_bytes([line[x * 4], line[x * 4 + 1], line[x * 4 + 2], line[x * 4 + 3]]);
+                    masks.map(|mask| extract_channel(value, mask))
+                }
+            };
+            let i = (y * width as usize + x) * 4;
+            pixels[i..i + 4].copy_from_slice(&rgba);
+        }
+    }
+
+    // Many writers leave the alpha byte zero; treat that as opaque
+    if bits == 32 && (masks[3] == 0 || pixels.chunks(4).all(|p| p[3] == 0)) {
+        pixels.chunks_mut(4).for_each(|p| p[3] = 255);
+    }
+    RgbaImage::new(width, height, pixels)
+}
+
+/// Scale the bits under a channel mask to 0..=255.
+fn extract_channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let raw = (value & mask) >> mask.trailing_zeros();
+    let max = mask >> mask.trailing_zeros();
+    (u64::from(raw) * 255 / u64::from(max)) as u8
+}
+
+/// Decode a QOI ("Quite OK Image") file.
+pub fn decode_qoi(data: &[u8]) -> io::Result<RgbaImage> {
+    let mut reader = Reader { data, pos: 4 };
+    if data.get(..4) != Some(b"qoif") {
+        return Err(invalid("not a QOI image"));
+    }
+    let width = reader.u32_be()?;
+    let height = reader.u32_be()?;
+    reader.bytes(2)?; // channels and colorspace
+    check_size(width, height)?;
+
+    // A run op covers at most 62 pixels
+    reader.ensure(u64::from(width) * u64::from(height) / 62)?;
+    let total = width as usize * height as usize;
+    let mut pixels = Vec::with_capacity(total * 4);
+    let mut index = [[0u8; 4]; 64];
+    let mut px = [0, 0, 0, 255u8];
+    let mut run = 0;
+
+    while pixels.len() < total * 4 {
+        if run > 0 {
+            run -= 1;
+        } else {
+            let op = reader.bytes(1)?[0];
+            match op {
+                0xfe => {
+                    let rgb = reader.bytes(3)?;
+                    px = [rgb[0], rgb[1], rgb[2], px[3]];
+                }
+                0xff => {
+                    let rgba = reader.bytes(4)?;
+                    px = [rgba[0], rgba[1], rgba[2], rgba[3]];
+                }
+                _ => match op >> 6 {
+                    0 => px = index[usize::from(op & 0x3f)],
+                    1 => {
+                        px[0] = px[0].wrapping_add((op >> 4) & 3).wrapping_sub(2);
+                        px[1] = px[1].wrapping_add((op >> 2) & 3).wrapping_sub(2);
+                        px[2] = px[2].wrapping_add(op & 3).wrapping_sub(2);
+                    }
+                    2 => {
+                        let next = reader.bytes(1)?[0];
+                        let dg = (op & 0x3f).wrapping_sub(32);
+                        px[0] = px[0].wrapping_add(dg).wrapping_add(next >> 4).wrapping_sub(8);
+                        px[1] = px[1].wrapping_add(dg);
+                        px[2] = px[2].wrapping_add(dg).wrapping_add(next & 0x0f).wrapping_sub(8);
+                    }
+                    _ => run = op & 0x3f,
+                },
+            }
+            let [r, g, b, a] = px.map(usize::from);
+            index[(r * 3 + g * 5 + b * 7 + a * 11) % 64] = px;
+        }
+        pixels.extend_from_slice(&px);
+    }
+    RgbaImage::new(width, height, pixels)
+}
+
+fn check_size(width: u32, height: u32) -> io::Result<()> {
+    if u64::from(width) * u64::from(height) > MAX_PIXELS {
+        return Err(invalid("image too large"));
+    }
+    Ok(())
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Fail early when fewer than `len` bytes are left, before a decoder
+    /// allocates for what the header claims.
+    fn ensure(&self, len: u64) -> io::Result<()> {
+        if (self.data.len().saturating_sub(self.pos) as u64) < len {
+            return Err(invalid("truncated image"));
+        }
+        Ok(())
+    }
+
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| invalid("truncated image"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u16_le(&mut self) -> io::Result<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32_le(&mut self) -> io::Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u32_be(&mut self) -> io::Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// An ASCII number in a Netpbm file, skipping whitespace and comments.
+    fn ppm_number(&mut self) -> io::Result<u32> {
+        loop {
+            match self.data.get(self.pos) {
+                Some(b'#') => {
+                    while self.data.get(self.pos).is_some_and(|&b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                Some(_) => break,
+                None => return Err(invalid("truncated image")),
+            }
+        }
+        let start = self.pos;
+        while self.data.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| invalid("bad number in PPM header"))
+    }
+}
+
+// =============================================================================
+// Protocols
+// =============================================================================
+
+/// How an image is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GraphicsProtocol {
+    /// Kitty graphics protocol (also Ghostty)
+    Kitty,
+    /// iTerm2 inline images (also WezTerm)
+    Iterm2,
+    /// DEC Sixel
+    Sixel,
+    /// Two pixels per cell with `▀` and `▄`
+    #[default]
+    HalfBlocks,
+    /// Four pixels per cell with quadrant characters
+    Quadrants,
+}
+
+impl GraphicsProtocol {
+    /// Whether this draws real pixels rather than characters.
+    pub fn is_pixel(self) -> bool {
+        matches!(self, GraphicsProtocol::Kitty | GraphicsProtocol::Iterm2 | GraphicsProtocol::Sixel)
+    }
+}
+
+/// Size of a terminal cell in pixels, as reported by the terminal.
+#[cfg(unix)]
+pub fn cell_pixel_size() -> (u16, u16) {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+    if ok && size.ws_col > 0 && size.ws_row > 0 && size.ws_xpixel > 0 && size.ws_ypixel > 0 {
+        ((size.ws_xpixel / size.ws_col).max(1), (size.ws_ypixel / size.ws_row).max(1))
+    } else {
+        DEFAULT_CELL_SIZE
+    }
+}
+
+#[cfg(not(unix))]
+pub fn cell_pixel_size() -> (u16, u16) {
+    DEFAULT_CELL_SIZE
+}
+
+/// Image ID, protocol and cell size of a cached encoding.
+type EncodingKey = (u32, GraphicsProtocol, u16, u16);
+
+thread_local! {
+    static ENCODED: RefCell<HashMap<EncodingKey, Rc<str>>> = RefCell::new(HashMap::new());
+}
+
+/// Escape sequence drawing an image over `cols` x `rows` cells, cached so
+/// redraws don't re-encode. `None` for the character protocols.
+///
+/// For Kitty this only transmits the image; it is shown with [`kitty_place`].
+pub fn encode(image: &RgbaImage, protocol: GraphicsProtocol, cols: u16, rows: u16) -> Option<Rc<str>> {
+    /// Encodings kept before the cache starts over.
+    const CACHE_LIMIT: usize = 32;
+
+    if !protocol.is_pixel() || cols == 0 || rows == 0 {
+        return None;
+    }
+    let key = (image.id, protocol, cols, rows);
+    if let Some(sequence) = ENCODED.with(|cache| cache.borrow().get(&key).cloned()) {
+        return Some(sequence);
+    }
+
+    let (cell_width, cell_height) = cell_pixel_size();
+    let target = (u32::from(cols) * u32::from(cell_width), u32::from(rows) * u32::from(cell_height));
+    let sequence: Rc<str> = match protocol {
+        GraphicsProtocol::Kitty => kitty_transmit(&shrink_to(image, target), kitty_image_id(image, cols, rows)),
+        GraphicsProtocol::Iterm2 => iterm2_sequence(&shrink_to(image, target), cols, rows),
+        _ => sixel_sequence(&image.resize(target.0, target.1)),
+    }
+    .into();
+
+    ENCODED.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert(key, sequence.clone());
+    });
+    Some(sequence)
+}
+
+/// Downscale to fit a pixel area; terminals that scale images themselves
+/// don't need more pixels than they show.
+fn shrink_to(image: &RgbaImage, (width, height): (u32, u32)) -> RgbaImage {
+    if image.width <= width && image.height <= height {
+        image.clone()
+    } else {
+        image.resize(width.min(image.width), height.min(image.height))
+    }
+}
+
+/// ID a Kitty image is transmitted under; each cell size gets its own, as
+/// the pixels sent are scaled for it.
+pub fn kitty_image_id(image: &RgbaImage, cols: u16, rows: u16) -> u32 {
+    (image.id ^ (u32::from(cols) << 16 | u32::from(rows))).max(1)
+}
+
+/// Kitty graphics sequence transmitting raw RGBA data under an image ID,
+/// without showing it; [`kitty_place`] shows it.
+pub fn kitty_transmit(image: &RgbaImage, id: u32) -> String {
+    /// Largest base64 payload Kitty accepts per escape sequence.
+    const CHUNK: usize = 4096;
+
+    let payload = base64(&image.pixels);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(CHUNK).collect();
+    let mut output = String::with_capacity(payload.len() + chunks.len() * 16 + 96);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            output.push_str(&format!(
+                "\x1B_Ga=t,f=32,s={},v={},i={},q=2,m={};{}\x1B\\",
+                image.width, image.height, id, more, chunk
+            ));
+        } else {
+            output.push_str(&format!("\x1B_Gm={};{}\x1B\\", more, chunk));
+        }
+    }
+    output
+}
+
+/// Kitty sequence showing a transmitted image scaled to a cell area at the
+/// cursor, which stays put (`C=1`). Placing it again replaces the previous
+/// placement instead of stacking them.
+pub fn kitty_place(id: u32, cols: u16, rows: u16) -> String {
+    format!("\x1B_Ga=p,i={id},p=1,c={cols},r={rows},C=1,q=2\x1B\\")
+}
+
+/// Kitty sequence deleting an image's placements and its data.
+pub fn kitty_delete(id: u32) -> String {
+    format!("\x1B_Ga=d,d=I,i={id},q=2\x1B\\")
+}
+
+/// Kitty images a terminal holds, so frames place them by ID instead of
+/// sending their pixels again, and delete them once no longer shown.
+#[derive(Debug, Default)]
+pub struct KittyImages {
+    transmitted: HashSet<u32>,
+    shown: HashSet<u32>,
+}
+
+impl KittyImages {
+    /// No images transmitted yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an image shown in the current frame. Returns whether it
+    /// still has to be transmitted.
+    pub fn show(&mut self, id: u32) -> bool {
+        self.shown.insert(id);
+        self.transmitted.insert(id)
+    }
+
+    /// End a frame, returning sequences deleting the images it no longer
+    /// shows.
+    pub fn end_frame(&mut self) -> String {
+        let shown = std::mem::take(&mut self.shown);
+        let gone: Vec<u32> = self.transmitted.difference(&shown).copied().collect();
+        self.transmitted = shown;
+        gone.into_iter().map(kitty_delete).collect()
+    }
+
+    /// Sequences deleting every transmitted image, e.g. on exit.
+    pub fn clear(&mut self) -> String {
+        self.shown.clear();
+        self.transmitted.drain().map(kitty_delete).collect()
+    }
+}
+
+/// iTerm2 inline image sequence, sending the image as a BMP file.
+pub fn iterm2_sequence(image: &RgbaImage, cols: u16, rows: u16) -> String {
+    let file = encode_bmp(image);
+    format!(
+        "\x1B]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0:{}\x07",
+        file.len(),
+        cols,
+        rows,
+        base64(&file)
+    )
+}
+
+/// Sixel sequence for an image at its pixel size.
+///
+/// Colors are quantized to a 6x6x6 cube; pixels under half alpha are left
+/// transparent.
+pub fn sixel_sequence(image: &RgbaImage) -> String {
+    let level = |v: u8| (u16::from(v) * 5 + 127) / 255;
+    let color = |x: u32, y: u32| {
+        let [r, g, b, a] = image.pixel(x, y);
+        (a >= 128).then(|| (level(r) * 36 + level(g) * 6 + level(b)) as u8)
+    };
+
+    // Transparent background (P2=1) and square pixels
+    let mut output = format!("\x1BP0;1;0q\"1;1;{};{}", image.width, image.height);
+    let mut defined = [false; 216];
+    for band in (0..image.height).step_by(6) {
+        let mut used: Vec<u8> = (band..(band + 6).min(image.height))
+            .flat_map(|y| (0..image.width).filter_map(move |x| color(x, y)))
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+
+        for (i, &index) in used.iter().enumerate() {
+            if !std::mem::replace(&mut defined[usize::from(index)], true) {
+                let percent = |level: u8| u16::from(level) * 20;
+                output.push_str(&format!(
+                    "#{};2;{};{};{}",
+                    index,
+                    percent(index / 36),
+                    percent(index / 6 % 6),
+                    percent(index % 6)
+                ));
+            }
+            output.push_str(&format!("#{}", index));
+
+            let column = |x: u32| {
+                let bits = (0..6).filter(|&dy| color(x, band + dy) == Some(index)).fold(0, |bits, dy| bits | 1 << dy);
+                char::from(63 + bits as u8)
+            };
+            let mut x = 0;
+            while x < image.width {
+                let c = column(x);
+                let mut run = 1;
+                while x + run < image.width && column(x + run) == c {
+                    run += 1;
+                }
+                if run > 3 {
+                    output.push_str(&format!("!{}{}", run, c));
+                } else {
+                    (0..run).for_each(|_| output.push(c));
+                }
+                x += run;
+            }
+            // Back to the start of the band for the next color
+            if i + 1 < used.len() {
+                output.push('$');
+            }
+        }
+        output.push('-');
+    }
+    output.push_str("\x1B\\");
+    output
+}
+
+/// Encode a 32-bit top-down BMP with an alpha channel.
+pub fn encode_bmp(image: &RgbaImage) -> Vec<u8> {
+    /// File header plus a BITMAPV4HEADER.
+    const HEADER: u32 = 14 + 108;
+
+    let size = HEADER + image.width * image.height * 4;
+    let mut file = Vec::with_capacity(size as usize);
+    file.extend_from_slice(b"BM");
+    file.extend_from_slice(&size.to_le_bytes());
+    file.extend_from_slice(&[0; 4]);
+    file.extend_from_slice(&HEADER.to_le_bytes());
+    file.extend_from_slice(&108u32.to_le_bytes());
+    file.extend_from_slice(&(image.width as i32).to_le_bytes());
+    file.extend_from_slice(&(-(image.height as i32)).to_le_bytes());
+    file.extend_from_slice(&1u16.to_le_bytes());
+    file.extend_from_slice(&32u16.to_le_bytes());
+    file.extend_from_slice(&3u32.to_le_bytes()); // BI_BITFIELDS
+    file.extend_from_slice(&(image.width * image.height * 4).to_le_bytes());
+    file.extend_from_slice(&[0; 16]); // resolution and palette
+    for mask in [0x00ff_0000u32, 0x0000_ff00, 0x0000_00ff, 0xff00_0000] {
+        file.extend_from_slice(&mask.to_le_bytes());
+    }
+    file.extend_from_slice(b"BGRs"); // sRGB, little-endian
+    file.extend_from_slice(&[0; 48]); // endpoints and gamma
+    for pixel in image.pixels.chunks(4) {
+        file.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+    }
+    file
+}
+
+/// Standard base64 with padding.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut output = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let n = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(char::from(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize]));
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+// =============================================================================
+// Character Fallback
+// =============================================================================
+
+/// Quadrant characters indexed by filled quarters: top-left 1, top-right 2,
+/// bottom-left 4, bottom-right 8.
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// Cells drawing an image with characters, row by row.
+///
+/// Without Unicode each cell is a space colored with the average of its
+/// pixels.
+pub fn block_cells(image: &RgbaImage, protocol: GraphicsProtocol, cols: u16, rows: u16, unicode: bool) -> Vec<Cell> {
+    let quadrants = protocol == GraphicsProtocol::Quadrants;
+    let (per_x, per_y) = if quadrants { (2, 2) } else { (1, 2) };
+    let scaled = image.resize(u32::from(cols) * per_x, u32::from(rows) * per_y);
+
+    let mut cells = Vec::with_capacity(usize::from(cols) * usize::from(rows));
+    for row in 0..u32::from(rows) {
+        for col in 0..u32::from(cols) {
+            let pixels: Vec<[u8; 4]> = (0..per_y)
+                .flat_map(|dy| (0..per_x).map(move |dx| (dx, dy)))
+                .map(|(dx, dy)| scaled.pixel(col * per_x + dx, row * per_y + dy))
+                .collect();
+            cells.push(if !unicode {
+                shaded_cell(&pixels)
+            } else if quadrants {
+                quadrant_cell(&pixels)
+            } else {
+                half_block_cell(pixels[0], pixels[1])
+            });
+        }
+    }
+    cells
+}
+
+fn opaque(pixel: [u8; 4]) -> bool {
+    pixel[3] >= 128
+}
+
+fn rgb(pixel: [u8; 4]) -> Color {
+    Color::Rgb(pixel[0], pixel[1], pixel[2])
+}
+
+fn cell(char: char, fg: Color, bg: Color) -> Cell {
+    Cell { char, fg, bg, ..Cell::default() }
+}
+
+fn average(pixels: &[[u8; 4]]) -> [u8; 4] {
+    let n = pixels.len().max(1) as u32;
+    let sum = |i: usize| (pixels.iter().map(|p| u32::from(p[i])).sum::<u32>() / n) as u8;
+    [sum(0), sum(1), sum(2), 255]
+}
+
+fn half_block_cell(top: [u8; 4], bottom: [u8; 4]) -> Cell {
+    match (opaque(top), opaque(bottom)) {
+        (true, true) => cell('▀', rgb(top), rgb(bottom)),
+        (true, false) => cell('▀', rgb(top), Color::Default),
+        (false, true) => cell('▄', rgb(bottom), Color::Default),
+        (false, false) => Cell::default(),
+    }
+}
+
+/// Split four pixels into the two most different colors.
+fn quadrant_cell(pixels: &[[u8; 4]]) -> Cell {
+    let distance = |a: [u8; 4], b: [u8; 4]| {
+        (0..3).map(|i| (i32::from(a[i]) - i32::from(b[i])).pow(2)).sum::<i32>()
+    };
+    let solid: Vec<[u8; 4]> = pixels.iter().copied().filter(|&p| opaque(p)).collect();
+    if solid.is_empty() {
+        return Cell::default();
+    }
+
+    // Transparent quarters show the terminal background
+    let (fg, bg) = if solid.len() < pixels.len() {
+        (average(&solid), None)
+    } else {
+        let pairs = (0..4).flat_map(|a| (a + 1..4).map(move |b| (a, b)));
+        let (a, b) = pairs.max_by_key(|&(a, b)| distance(pixels[a], pixels[b])).unwrap_or((0, 1));
+        (pixels[a], Some(pixels[b]))
+    };
+    let in_fg = |p: [u8; 4]| opaque(p) && bg.map_or(true, |bg| distance(p, fg) <= distance(p, bg));
+    let mask = pixels.iter().enumerate().filter(|&(_, &p)| in_fg(p)).fold(0, |mask, (i, _)| mask | 1 << i);
+
+    let group = |fg_side: bool| -> Vec<[u8; 4]> {
+        pixels.iter().copied().filter(|&p| opaque(p) && in_fg(p) == fg_side).collect()
+    };
+    let fg = average(&group(true));
+    let bg = match bg {
+        Some(_) if mask != 15 => rgb(average(&group(false))),
+        _ => Color::Default,
+    };
+    cell(QUADRANTS[mask], rgb(fg), bg)
+}
+
+fn shaded_cell(pixels: &[[u8; 4]]) -> Cell {
+    let solid: Vec<[u8; 4]> = pixels.iter().copied().filter(|&p| opaque(p)).collect();
+    if solid.is_empty() {
+        Cell::default()
+    } else {
+        cell(' ', Color::Default, rgb(average(&solid)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+    #[test]
+    fn test_decode_formats() {
+        let ppm = decode(b"P3\n# two pixels\n2 1\n255\n255 0 0  0 0 0\n").unwrap();
+        assert_eq!((ppm.width(), ppm.height()), (2, 1));
+        assert_eq!((ppm.pixel(0, 0), ppm.pixel(1, 0)), (RED, BLACK));
+        let pgm = decode(b"P5 1 1 15\n\x0f").unwrap();
+        assert_eq!(pgm.pixel(0, 0), [255, 255, 255, 255]);
+
+        // QOI: RGB, run of one, diff wrapping red to black, index back to red
+        let mut qoi = b"qoif\0\0\0\x02\0\0\0\x02\x04\x00".to_vec();
+        qoi.extend_from_slice(&[0xfe, 255, 0, 0, 0xc0, 0x7a, 0x32, 0, 0, 0, 0, 0, 0, 0, 1]);
+        let qoi = decode(&qoi).unwrap();
+        assert_eq!(qoi.pixels(), [RED, RED, BLACK, RED].concat());
+
+        // BMP round trip keeps alpha
+        let image = RgbaImage::from_fn(3, 2, |x, y| [x as u8 * 100, y as u8 * 200, 7, 128 + x as u8]);
+        assert_eq!(decode(&encode_bmp(&image)).unwrap(), image);
+
+        assert!(decode(b"GIF89a").is_err());
+        assert!(decode(b"P6 4 4 255\n\x01\x02").is_err());
+        assert!(decode(b"qoif\xff\xff\xff\xff\xff\xff\xff\xff\x04\x00").is_err());
+
+        // Headers claiming huge images fail before allocating for them
+        for header in [&b"P6 8000 8000 255\n"[..], b"P3 8000 8000 255\n1", b"qoif\0\0\x1f\x40\0\0\x1f\x40\x04\x00\xfd"] {
+            assert_eq!(decode(header).unwrap_err().to_string(), "truncated image");
+        }
+        let mut bmp = encode_bmp(&RgbaImage::from_fn(1, 1, |_, _| RED));
+        bmp[18..26].copy_from_slice(&[0x40, 0x1f, 0, 0, 0x40, 0x1f, 0, 0]);
+        assert_eq!(decode(&bmp).unwrap_err().to_string(), "truncated image");
+    }
+
+    #[test]
+    fn test_protocol_sequences() {
+        assert_eq!(base64(b"hello"), "aGVsbG8=");
+        let image = RgbaImage::from_fn(2, 6, |x, _| if x == 0 { RED } else { [0; 4] });
+
+        let kitty = kitty_transmit(&image, 7);
+        assert!(kitty.starts_with("\x1B_Ga=t,f=32,s=2,v=6,i=7,") && kitty.ends_with("\x1B\\"));
+        assert_eq!(kitty_place(7, 1, 1), "\x1B_Ga=p,i=7,p=1,c=1,r=1,C=1,q=2\x1B\\");
+
+        // Images are sent once, then deleted once a frame no longer shows them
+        let mut images = KittyImages::new();
+        assert!(images.show(7) && images.end_frame().is_empty());
+        assert!(!images.show(7) && images.show(8) && images.end_frame().is_empty());
+        assert!(!images.show(8));
+        assert_eq!(images.end_frame(), kitty_delete(7));
+        assert_eq!(images.clear(), "\x1B_Ga=d,d=I,i=8,q=2\x1B\\");
+        assert!(iterm2_sequence(&image, 1, 1).starts_with("\x1B]1337;File=inline=1;size="));
+
+        // One band, red in the first column only, the rest transparent
+        let sixel = sixel_sequence(&image);
+        assert_eq!(sixel, "\x1BP0;1;0q\"1;1;2;6#180;2;100;0;0#180~?-\x1B\\");
+    }
+
+    #[test]
+    fn test_block_fallback() {
+        let image = RgbaImage::from_fn(2, 2, |x, y| match (x, y) {
+            (0, 0) => RED,
+            (1, 1) => [0; 4],
+            _ => BLACK,
+        });
+        let cells = block_cells(&image, GraphicsProtocol::HalfBlocks, 2, 1, true);
+        assert_eq!((cells[0].char, cells[0].fg, cells[0].bg), ('▀', Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 0)));
+        assert_eq!((cells[1].char, cells[1].bg), ('▀', Color::Default));
+
+        let cells = block_cells(&image, GraphicsProtocol::Quadrants, 1, 1, true);
+        assert_eq!((cells[0].char, cells[0].bg), ('▛', Color::Default));
+        let ascii = block_cells(&image, GraphicsProtocol::HalfBlocks, 1, 1, false);
+        assert_eq!((ascii[0].char, ascii[0].bg), (' ', Color::Rgb(127, 0, 0)));
+    }
+}
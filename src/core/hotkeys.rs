@@ -29,7 +29,9 @@ pub fn parse_hotkey(s: &str) -> ParsedHotkey {
             "ctrl" | "control" => modifiers.ctrl = true,
             "alt" | "option" => modifiers.alt = true,
             "shift" => modifiers.shift = true,
-            "cmd" | "meta" | "super" | "win" => modifiers.meta = true,
+            "meta" => modifiers.meta = true,
+            "cmd" | "super" | "win" => modifiers.super_key = true,
+            "hyper" => modifiers.hyper = true,
             "enter" | "return" => key = Key::Enter,
             "esc" | "escape" => key = Key::Escape,
             "tab" => key = Key::Tab,
@@ -64,6 +66,8 @@ pub fn matches_hotkey(key: &Key, mods: &KeyModifiers, hotkey: &ParsedHotkey) ->
         && mods.alt == hotkey.modifiers.alt
         && mods.shift == hotkey.modifiers.shift
         && mods.meta == hotkey.modifiers.meta
+        && mods.super_key == hotkey.modifiers.super_key
+        && mods.hyper == hotkey.modifiers.hyper
 }
 
 #[cfg(test)]
@@ -75,5 +79,8 @@ mod tests {
         let h = parse_hotkey("ctrl+s");
         assert!(h.modifiers.ctrl);
         assert_eq!(h.key, Key::Char('s'));
+
+        let h = parse_hotkey("cmd+p");
+        assert!(h.modifiers.super_key && !h.modifiers.meta);
     }
 }
@@ -12,19 +12,35 @@
 //! - `align_content`: Multi-line alignment
 //! - `flex_wrap`: Wrap, NoWrap, WrapReverse
 //! - `flex_grow`, `flex_shrink`: Size distribution
-//! - `gap`: Spacing between children
-//! - `padding`, `margin`: Box model
+//! - `gap`, `row_gap`, `column_gap`: Spacing between children and lines
+//! - `padding`, `margin`: Box model, per side
 //! - `width`, `height`: Fixed or percentage sizes
 //! - `min_width`, `min_height`, `max_width`, `max_height`: Constraints
+//! - `position`: Static, Relative, Absolute (with top/right/bottom/left offsets)
+//! - `display_mode: Grid`: template rows/columns (fixed, percent, `fr`, auto),
+//!   spans and named areas
+//!
+//! Text leaves carry a [`TextMeasure`] so they are measured against the
+//! width they are actually given: wrapped paragraphs grow in height and
+//! truncated text is cut at its computed width.
+//!
+//! [`calculate_layout_cached`] keeps a [`LayoutCache`] between frames and
+//! skips subtrees whose styles, content and size constraints are unchanged.
 
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::core::component::WrapMode;
+use crate::utils::text::{char_width, truncate_text, visible_width, wrap_chars, wrap_text};
 
 // =============================================================================
 // Types
 // =============================================================================
 
 /// Flex direction (main axis).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum FlexDirection {
     /// Left to right (default)
     #[default]
@@ -50,7 +66,7 @@ impl FlexDirection {
 }
 
 /// Justify content (main axis alignment).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum JustifyContent {
     /// Pack items at the start
     #[default]
@@ -68,7 +84,7 @@ pub enum JustifyContent {
 }
 
 /// Align items (cross axis alignment).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum AlignItems {
     /// Align to start of cross axis
     FlexStart,
@@ -84,7 +100,7 @@ pub enum AlignItems {
 }
 
 /// Align self (override align_items for a single child).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum AlignSelf {
     /// Use parent's align_items
     #[default]
@@ -102,7 +118,7 @@ pub enum AlignSelf {
 }
 
 /// Align content (multi-line cross axis alignment).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum AlignContent {
     /// Pack lines at start
     #[default]
@@ -120,7 +136,7 @@ pub enum AlignContent {
 }
 
 /// Flex wrap behavior.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum FlexWrap {
     /// No wrapping (default)
     #[default]
@@ -131,6 +147,120 @@ pub enum FlexWrap {
     WrapReverse,
 }
 
+/// Overflow behavior for content that exceeds a box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Overflow {
+    /// Content draws outside the box (default)
+    #[default]
+    Visible,
+    /// Content is clipped to the box
+    Hidden,
+    /// Content is clipped and laid out at its natural size so it can be scrolled
+    Scroll,
+}
+
+/// Positioning scheme for a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Position {
+    /// In normal flow; a left or top offset pins the node to that screen
+    /// column or row, as fixed positions always have (default)
+    #[default]
+    Static,
+    /// In normal flow, shifted by its offsets; contains absolute descendants
+    Relative,
+    /// Out of flow, placed by its offsets inside the nearest positioned
+    /// ancestor (or the screen when there is none)
+    Absolute,
+}
+
+/// How a container lays out its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Display {
+    /// Flexbox along `flex_direction` (default)
+    #[default]
+    Flex,
+    /// Two-dimensional grid of template rows and columns
+    Grid,
+}
+
+/// Size of a grid row or column track.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GridTrack {
+    /// Fixed size in characters/lines
+    Fixed(u16),
+    /// Percentage of the grid container
+    Percent(f32),
+    /// Share of the space left after the other tracks
+    Fr(f32),
+    /// Sized to the largest item in the track
+    #[default]
+    Auto,
+}
+
+impl Hash for GridTrack {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Fixed(v) => v.hash(state),
+            Self::Percent(f) | Self::Fr(f) => f.to_bits().hash(state),
+            Self::Auto => {}
+        }
+    }
+}
+
+impl GridTrack {
+    /// Parse a track list such as `"20 1fr 2fr auto 25%"`.
+    ///
+    /// Unrecognised entries are treated as `auto`.
+    pub fn parse_list(spec: &str) -> Vec<GridTrack> {
+        spec.split_whitespace()
+            .map(|token| {
+                if let Some(fr) = token.strip_suffix("fr") {
+                    fr.parse().map(GridTrack::Fr).unwrap_or(GridTrack::Auto)
+                } else if let Some(percent) = token.strip_suffix('%') {
+                    percent.parse().map(GridTrack::Percent).unwrap_or(GridTrack::Auto)
+                } else {
+                    token.parse().map(GridTrack::Fixed).unwrap_or(GridTrack::Auto)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Placement of a grid item along one axis.
+///
+/// Lines are numbered from 1 like CSS; without a start line the item is
+/// auto-placed in the next free cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct GridPlacement {
+    /// First grid line (1-based), or auto-placed when `None`
+    pub start: Option<u16>,
+    /// Number of tracks spanned (0 is treated as 1)
+    pub span: u16,
+}
+
+impl GridPlacement {
+    /// Auto-placed, spanning one track.
+    pub fn auto() -> Self {
+        Self { start: None, span: 1 }
+    }
+
+    /// Start at a grid line, spanning one track.
+    pub fn line(start: u16) -> Self {
+        Self { start: Some(start), span: 1 }
+    }
+
+    /// Auto-placed, spanning `span` tracks.
+    pub fn span(span: u16) -> Self {
+        Self { start: None, span }
+    }
+
+    /// Start at a grid line and span `span` tracks.
+    pub fn at(start: u16, span: u16) -> Self {
+        Self { start: Some(start), span }
+    }
+}
+
 /// Size value (fixed, percentage, or auto).
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Size {
@@ -144,6 +274,17 @@ pub enum Size {
     Fill,
 }
 
+impl Hash for Size {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Fixed(v) => v.hash(state),
+            Self::Percent(p) => p.to_bits().hash(state),
+            Self::Auto | Self::Fill => {}
+        }
+    }
+}
+
 impl Default for Size {
     fn default() -> Self {
         Self::Auto
@@ -163,7 +304,7 @@ impl Size {
 }
 
 /// Edge values (for padding, margin).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Edges {
     /// Top edge
     pub top: u16,
@@ -207,6 +348,93 @@ impl Edges {
     }
 }
 
+// =============================================================================
+// Text Measurement
+// =============================================================================
+
+/// Measurable text content for leaf nodes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TextMeasure {
+    /// Text content (may contain newlines)
+    pub content: String,
+    /// How the text is broken into lines
+    pub wrap: WrapMode,
+}
+
+impl TextMeasure {
+    /// Create a new text measure.
+    pub fn new(content: impl Into<String>, wrap: WrapMode) -> Self {
+        Self {
+            content: content.into(),
+            wrap,
+        }
+    }
+
+    /// Size of the text when no width constraint applies.
+    pub fn max_content(&self) -> (u16, u16) {
+        let mut width = 0;
+        let mut height = 0;
+        for line in self.content.split('\n') {
+            width = width.max(visible_width(line));
+            height += 1;
+        }
+        (clamp_u16(width), clamp_u16(height))
+    }
+
+    /// Smallest width the text can be given without overflowing.
+    pub fn min_content_width(&self) -> u16 {
+        match self.wrap {
+            WrapMode::None => self.max_content().0,
+            WrapMode::Word => clamp_u16(
+                self.content
+                    .split_whitespace()
+                    .map(visible_width)
+                    .max()
+                    .unwrap_or(0),
+            ),
+            WrapMode::Char => clamp_u16(self.content.chars().map(char_width).max().unwrap_or(0)),
+            WrapMode::Truncate => 0,
+        }
+    }
+
+    /// Measure the text when laid out within `available_width` columns.
+    pub fn measure(&self, available_width: u16) -> (u16, u16) {
+        let lines = self.lines(available_width);
+        let width = lines.iter().map(|l| visible_width(l)).max().unwrap_or(0);
+        (clamp_u16(width), clamp_u16(lines.len()))
+    }
+
+    /// Break the text into the lines it occupies at `width` columns.
+    pub fn lines(&self, width: u16) -> Vec<String> {
+        let width = usize::from(width);
+        let mut lines = Vec::new();
+
+        for paragraph in self.content.split('\n') {
+            match self.wrap {
+                WrapMode::None => lines.push(paragraph.to_string()),
+                WrapMode::Word => {
+                    for line in wrap_text(paragraph, width) {
+                        // Words longer than the line are broken as a last resort
+                        if width > 0 && visible_width(&line) > width {
+                            lines.extend(wrap_chars(&line, width));
+                        } else {
+                            lines.push(line);
+                        }
+                    }
+                }
+                WrapMode::Char => lines.extend(wrap_chars(paragraph, width)),
+                WrapMode::Truncate => lines.push(truncate_text(paragraph, width, "…")),
+            }
+        }
+
+        lines
+    }
+}
+
+fn clamp_u16(value: usize) -> u16 {
+    u16::try_from(value).unwrap_or(u16::MAX)
+}
+
 // =============================================================================
 // Layout Node
 // =============================================================================
@@ -218,6 +446,9 @@ pub struct LayoutStyle {
     /// Whether this node is visible
     pub display: bool,
 
+    /// Layout mode for children (flexbox or grid)
+    pub display_mode: Display,
+
     // Flex container
     /// Main axis direction
     pub flex_direction: FlexDirection,
@@ -236,6 +467,24 @@ pub struct LayoutStyle {
     /// Column gap (overrides gap for columns)
     pub column_gap: Option<u16>,
 
+    // Grid container
+    /// Column track sizes
+    pub grid_template_columns: Vec<GridTrack>,
+    /// Row track sizes
+    pub grid_template_rows: Vec<GridTrack>,
+    /// Named areas, one string per row (e.g. `"header header"`, `. ` for none)
+    pub grid_template_areas: Vec<String>,
+    /// Size of rows created beyond the template
+    pub grid_auto_rows: GridTrack,
+
+    // Grid item
+    /// Column placement
+    pub grid_column: GridPlacement,
+    /// Row placement
+    pub grid_row: GridPlacement,
+    /// Named template area (overrides row/column placement)
+    pub grid_area: Option<String>,
+
     // Flex item
     /// Override parent's align_items
     pub align_self: AlignSelf,
@@ -270,11 +519,62 @@ pub struct LayoutStyle {
     /// Border width (usually 0 or 1)
     pub border_width: u16,
 
+    // Overflow
+    /// Overflow behavior (scroll containers don't shrink their children)
+    pub overflow: Overflow,
+
     // Position
-    /// Fixed position X
+    /// Positioning scheme
+    pub position: Position,
+    /// Left offset, or the fixed screen column of a static node
     pub position_x: Option<u16>,
-    /// Fixed position Y
+    /// Top offset, or the fixed screen row of a static node
     pub position_y: Option<u16>,
+    /// Right offset (for relative and absolute positioning)
+    pub position_right: Option<u16>,
+    /// Bottom offset (for relative and absolute positioning)
+    pub position_bottom: Option<u16>,
+}
+
+impl Hash for LayoutStyle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.display.hash(state);
+        self.display_mode.hash(state);
+        self.flex_direction.hash(state);
+        self.justify_content.hash(state);
+        self.align_items.hash(state);
+        self.align_content.hash(state);
+        self.flex_wrap.hash(state);
+        self.gap.hash(state);
+        self.row_gap.hash(state);
+        self.column_gap.hash(state);
+        self.grid_template_columns.hash(state);
+        self.grid_template_rows.hash(state);
+        self.grid_template_areas.hash(state);
+        self.grid_auto_rows.hash(state);
+        self.grid_column.hash(state);
+        self.grid_row.hash(state);
+        self.grid_area.hash(state);
+        self.align_self.hash(state);
+        self.flex_grow.to_bits().hash(state);
+        self.flex_shrink.to_bits().hash(state);
+        self.flex_basis.hash(state);
+        self.width.hash(state);
+        self.height.hash(state);
+        self.min_width.hash(state);
+        self.min_height.hash(state);
+        self.max_width.hash(state);
+        self.max_height.hash(state);
+        self.padding.hash(state);
+        self.margin.hash(state);
+        self.border_width.hash(state);
+        self.overflow.hash(state);
+        self.position.hash(state);
+        self.position_x.hash(state);
+        self.position_y.hash(state);
+        self.position_right.hash(state);
+        self.position_bottom.hash(state);
+    }
 }
 
 impl LayoutStyle {
@@ -317,6 +617,17 @@ impl LayoutStyle {
         self
     }
 
+    /// Gaps along the main axis (between items) and the cross axis (between lines).
+    pub fn axis_gaps(&self) -> (u16, u16) {
+        let row_gap = self.row_gap.unwrap_or(self.gap);
+        let column_gap = self.column_gap.unwrap_or(self.gap);
+        if self.flex_direction.is_row() {
+            (column_gap, row_gap)
+        } else {
+            (row_gap, column_gap)
+        }
+    }
+
     /// Builder pattern: set flex grow.
     pub fn flex_grow(mut self, value: f32) -> Self {
         self.flex_grow = value;
@@ -347,6 +658,8 @@ pub struct LayoutNode {
     pub children: Vec<LayoutNode>,
     /// Content size (for leaf nodes)
     pub content_size: (u16, u16),
+    /// Measurable text (for text leaves); takes precedence over `content_size`
+    pub measure: Option<TextMeasure>,
 }
 
 impl LayoutNode {
@@ -357,6 +670,7 @@ impl LayoutNode {
             style: LayoutStyle::new(),
             children: Vec::new(),
             content_size: (0, 0),
+            measure: None,
         }
     }
 
@@ -367,6 +681,26 @@ impl LayoutNode {
             style: LayoutStyle::new(),
             children: Vec::new(),
             content_size: (width, height),
+            measure: None,
+        }
+    }
+
+    /// Create a text node measured from its content.
+    pub fn measured(id: u64, measure: TextMeasure) -> Self {
+        Self {
+            id,
+            style: LayoutStyle::new(),
+            children: Vec::new(),
+            content_size: measure.max_content(),
+            measure: Some(measure),
+        }
+    }
+
+    /// Content size of a leaf given the inner width it is laid out in.
+    pub fn leaf_content_size(&self, inner_width: u16) -> (u16, u16) {
+        match &self.measure {
+            Some(measure) => measure.measure(inner_width),
+            None => self.content_size,
         }
     }
 
@@ -383,7 +717,7 @@ impl LayoutNode {
 }
 
 /// Computed layout result.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct ComputedLayout {
     /// X position
     pub x: u16,
@@ -401,6 +735,20 @@ impl ComputedLayout {
         x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
     }
 
+    /// Intersect with another rectangle (empty if they don't overlap).
+    pub fn intersect(&self, other: &ComputedLayout) -> ComputedLayout {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        ComputedLayout {
+            x,
+            y,
+            width: right.saturating_sub(x),
+            height: bottom.saturating_sub(y),
+        }
+    }
+
     /// Get the inner bounds (accounting for padding would need style).
     pub fn inner(&self, padding: &Edges) -> ComputedLayout {
         ComputedLayout {
@@ -424,89 +772,260 @@ pub fn calculate_layout(
 ) -> HashMap<u64, ComputedLayout> {
     let mut layouts = HashMap::new();
 
-    calculate_node_layout(node, 0, 0, available_width, available_height, &mut layouts);
+    if node.style.display {
+        let margin = node.style.margin;
+        let (width, height) = resolve_root_size(
+            node,
+            available_width.saturating_sub(margin.horizontal()),
+            available_height.saturating_sub(margin.vertical()),
+        );
+        let screen = ComputedLayout {
+            x: 0,
+            y: 0,
+            width: available_width,
+            height: available_height,
+        };
+        place_node(node, margin.left, margin.top, width, height, screen, &mut layouts);
+    }
 
     layouts
 }
 
-fn calculate_node_layout(
-    node: &LayoutNode,
-    x: u16,
-    y: u16,
-    available_width: u16,
-    available_height: u16,
-    layouts: &mut HashMap<u64, ComputedLayout>,
-) {
-    if !node.style.display {
-        return;
-    }
-
+/// Resolve the size of the root node against the available space.
+fn resolve_root_size(node: &LayoutNode, available_width: u16, available_height: u16) -> (u16, u16) {
     let style = &node.style;
-
-    // Calculate this node's size
     let padding_h = style.padding.horizontal() + style.border_width * 2;
     let padding_v = style.padding.vertical() + style.border_width * 2;
 
     // Resolve width/height
-    let mut width = match style.width {
+    let width = match style.width {
         Size::Fixed(w) => w,
         Size::Percent(p) => ((available_width as f32) * p / 100.0).round() as u16,
         Size::Auto | Size::Fill => available_width,
     };
 
-    let mut height = match style.height {
+    // Apply width constraints before measuring content against it
+    let width = apply_constraints(width, style.min_width, style.max_width);
+
+    let height = match style.height {
         Size::Fixed(h) => h,
         Size::Percent(p) => ((available_height as f32) * p / 100.0).round() as u16,
         Size::Auto | Size::Fill => {
             if node.children.is_empty() {
-                node.content_size.1 + padding_v
+                node.leaf_content_size(width.saturating_sub(padding_h)).1 + padding_v
             } else {
                 available_height
             }
         }
     };
 
-    // Apply constraints
-    if let Some(min) = style.min_width {
-        width = width.max(min);
-    }
-    if let Some(max) = style.max_width {
-        width = width.min(max);
-    }
-    if let Some(min) = style.min_height {
-        height = height.max(min);
+    (width, apply_constraints(height, style.min_height, style.max_height))
+}
+
+/// Record a node's layout at its final size and lay out its children.
+///
+/// `containing_block` is the padding box of the nearest positioned
+/// ancestor, which absolute descendants are placed against.
+fn place_node(
+    node: &LayoutNode,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    containing_block: ComputedLayout,
+    layouts: &mut HashMap<u64, ComputedLayout>,
+) {
+    let key = placement_key(node, x, y, width, height, containing_block);
+    if let Some(key) = key {
+        if reuse_placement(node.id, key, x, y, layouts) {
+            return;
+        }
     }
-    if let Some(max) = style.max_height {
-        height = height.min(max);
+
+    place_subtree(node, x, y, width, height, containing_block, layouts);
+
+    if let Some(key) = key {
+        remember_placement(node, key, x, y, layouts);
     }
+}
+
+fn place_subtree(
+    node: &LayoutNode,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    containing_block: ComputedLayout,
+    layouts: &mut HashMap<u64, ComputedLayout>,
+) {
+    let style = &node.style;
 
-    // Apply position if specified
-    let final_x = style.position_x.unwrap_or(x);
-    let final_y = style.position_y.unwrap_or(y);
+    // Relative nodes are shifted from their flow position; static ones may
+    // be pinned to a screen position
+    let (final_x, final_y) = match style.position {
+        Position::Relative => (
+            offset_position(x, style.position_x, style.position_right),
+            offset_position(y, style.position_y, style.position_bottom),
+        ),
+        Position::Static => (style.position_x.unwrap_or(x), style.position_y.unwrap_or(y)),
+        Position::Absolute => (x, y),
+    };
 
     // Store this node's layout
-    layouts.insert(
-        node.id,
-        ComputedLayout {
-            x: final_x,
-            y: final_y,
-            width,
-            height,
-        },
-    );
+    let layout = ComputedLayout {
+        x: final_x,
+        y: final_y,
+        width,
+        height,
+    };
+    layouts.insert(node.id, layout);
 
     // If no children, we're done
     if node.children.is_empty() {
         return;
     }
 
+    let border = style.border_width;
+    let containing_block = if style.position == Position::Static {
+        containing_block
+    } else {
+        layout.inner(&Edges::all(border))
+    };
+
     // Calculate children layout
-    let inner_x = final_x + style.padding.left + style.border_width;
-    let inner_y = final_y + style.padding.top + style.border_width;
-    let inner_width = width.saturating_sub(padding_h);
-    let inner_height = height.saturating_sub(padding_v);
+    let inner_x = final_x + style.padding.left + border;
+    let inner_y = final_y + style.padding.top + border;
+    let inner_width = width.saturating_sub(style.padding.horizontal() + border * 2);
+    let inner_height = height.saturating_sub(style.padding.vertical() + border * 2);
+
+    match style.display_mode {
+        Display::Flex => {
+            layout_children(node, inner_x, inner_y, inner_width, inner_height, containing_block, layouts);
+        }
+        Display::Grid => {
+            layout_grid(node, inner_x, inner_y, inner_width, inner_height, containing_block, layouts);
+        }
+    }
+
+    for child in &node.children {
+        if child.style.display && child.style.position == Position::Absolute {
+            layout_absolute(child, inner_x, inner_y, containing_block, layouts);
+        }
+    }
+}
+
+/// Shift a coordinate by a start offset, or back by an end offset.
+fn offset_position(value: u16, start: Option<u16>, end: Option<u16>) -> u16 {
+    match (start, end) {
+        (Some(start), _) => value.saturating_add(start),
+        (None, Some(end)) => value.saturating_sub(end),
+        (None, None) => value,
+    }
+}
+
+/// Lay out an absolutely positioned node inside its containing block.
+///
+/// Without offsets on an axis the node stays at its static position (the
+/// parent's content origin); with both start and end offsets and an auto
+/// size it stretches between them; otherwise it takes its intrinsic size.
+fn layout_absolute(
+    node: &LayoutNode,
+    static_x: u16,
+    static_y: u16,
+    containing_block: ComputedLayout,
+    layouts: &mut HashMap<u64, ComputedLayout>,
+) {
+    let style = &node.style;
+    let cb = containing_block;
+
+    let width = match (style.width, style.position_x, style.position_right) {
+        (Size::Fixed(w), _, _) => w,
+        (Size::Percent(p), _, _) => ((cb.width as f32) * p / 100.0).round() as u16,
+        (Size::Fill, _, _) => cb.width,
+        (Size::Auto, Some(left), Some(right)) => cb.width.saturating_sub(left.saturating_add(right)),
+        (Size::Auto, _, _) => intrinsic_size(node, cb.width).0.min(cb.width),
+    };
+    let width = apply_constraints(width, style.min_width, style.max_width);
+
+    let height = match (style.height, style.position_y, style.position_bottom) {
+        (Size::Fixed(h), _, _) => h,
+        (Size::Percent(p), _, _) => ((cb.height as f32) * p / 100.0).round() as u16,
+        (Size::Fill, _, _) => cb.height,
+        (Size::Auto, Some(top), Some(bottom)) => cb.height.saturating_sub(top.saturating_add(bottom)),
+        (Size::Auto, _, _) => intrinsic_size(node, width).1,
+    };
+    let height = apply_constraints(height, style.min_height, style.max_height);
+
+    let x = match (style.position_x, style.position_right) {
+        (Some(left), _) => cb.x.saturating_add(left),
+        (None, Some(right)) => cb.x.saturating_add(cb.width).saturating_sub(right.saturating_add(width)),
+        (None, None) => static_x,
+    };
+    let y = match (style.position_y, style.position_bottom) {
+        (Some(top), _) => cb.y.saturating_add(top),
+        (None, Some(bottom)) => cb.y.saturating_add(cb.height).saturating_sub(bottom.saturating_add(height)),
+        (None, None) => static_y,
+    };
+
+    place_node(node, x, y, width, height, containing_block, layouts);
+}
+
+/// Natural size of a node, with text wrapped to fit within `max_width`.
+///
+/// Leaves are measured from their content; containers sum their flow
+/// children along the main axis and take the largest on the cross axis.
+pub fn intrinsic_size(node: &LayoutNode, max_width: u16) -> (u16, u16) {
+    if let Some(size) = cached_intrinsic(node.id, max_width) {
+        return size;
+    }
+    let size = measure_intrinsic(node, max_width);
+    remember_intrinsic(node.id, max_width, size);
+    size
+}
+
+fn measure_intrinsic(node: &LayoutNode, max_width: u16) -> (u16, u16) {
+    let style = &node.style;
+    let padding_h = style.padding.horizontal() + style.border_width * 2;
+    let padding_v = style.padding.vertical() + style.border_width * 2;
+
+    let fixed_width = match style.width {
+        Size::Fixed(w) => Some(w),
+        Size::Percent(p) => Some(((max_width as f32) * p / 100.0).round() as u16),
+        Size::Auto | Size::Fill => None,
+    };
+    let fixed_height = match style.height {
+        Size::Fixed(h) => Some(h),
+        _ => None,
+    };
+    let inner_max = fixed_width.unwrap_or(max_width).saturating_sub(padding_h);
 
-    layout_children(node, inner_x, inner_y, inner_width, inner_height, layouts);
+    let (content_w, content_h) = if node.children.is_empty() {
+        node.leaf_content_size(inner_max)
+    } else if style.display_mode == Display::Grid {
+        grid_content_size(node, inner_max)
+    } else {
+        let is_row = style.flex_direction.is_row();
+        let (main_gap, _) = style.axis_gaps();
+        let mut main: u16 = 0;
+        let mut cross: u16 = 0;
+        let mut count: u16 = 0;
+        for child in node.children.iter().filter(|c| c.style.display && c.style.position != Position::Absolute) {
+            let margin = &child.style.margin;
+            let (w, h) = intrinsic_size(child, inner_max.saturating_sub(margin.horizontal()));
+            let (w, h) = (w + margin.horizontal(), h + margin.vertical());
+            let (child_main, child_cross) = main_cross((w, h), is_row);
+            main = main.saturating_add(child_main);
+            cross = cross.max(child_cross);
+            count += 1;
+        }
+        main = main.saturating_add(main_gap * count.saturating_sub(1));
+        if is_row { (main, cross) } else { (cross, main) }
+    };
+
+    (
+        apply_constraints(fixed_width.unwrap_or(content_w + padding_h), style.min_width, style.max_width),
+        apply_constraints(fixed_height.unwrap_or(content_h + padding_v), style.min_height, style.max_height),
+    )
 }
 
 fn layout_children(
@@ -515,6 +1034,7 @@ fn layout_children(
     inner_y: u16,
     inner_width: u16,
     inner_height: u16,
+    containing_block: ComputedLayout,
     layouts: &mut HashMap<u64, ComputedLayout>,
 ) {
     let style = &node.style;
@@ -523,180 +1043,436 @@ fn layout_children(
 
     let main_size = if is_row { inner_width } else { inner_height };
     let cross_size = if is_row { inner_height } else { inner_width };
+    let (main_gap, cross_gap) = style.axis_gaps();
 
-    let gap = style.gap;
-    let visible_children: Vec<_> = node.children.iter().filter(|c| c.style.display).collect();
-    let child_count = visible_children.len();
+    let visible_children: Vec<_> = node
+        .children
+        .iter()
+        .filter(|c| c.style.display && c.style.position != Position::Absolute)
+        .collect();
 
-    if child_count == 0 {
+    if visible_children.is_empty() {
         return;
     }
 
-    // Calculate total flex grow and base sizes
-    let mut total_flex_grow: f32 = 0.0;
-    let mut total_base_size: u16 = 0;
-    let mut child_sizes: Vec<(u16, u16)> = Vec::with_capacity(child_count);
-
-    for child in &visible_children {
-        let (base_w, base_h) = calculate_child_base_size(child, inner_width, inner_height, is_row);
-        let base_main = if is_row { base_w } else { base_h };
+    // Hypothetical sizes of each item
+    let mut child_sizes: Vec<(u16, u16)> = visible_children
+        .iter()
+        .map(|child| {
+            let stretch = matches!(effective_align(style, child), AlignItems::Stretch);
+            calculate_child_base_size(child, inner_width, inner_height, is_row, stretch)
+        })
+        .collect();
+
+    // Break items into lines
+    let lines = collect_lines(&visible_children, &child_sizes, style.flex_wrap, main_size, main_gap, is_row);
+
+    // Resolve flexible lengths within each line
+    for line in &lines {
+        let children = &visible_children[line.clone()];
+        let sizes = &mut child_sizes[line.clone()];
+        let used = outer_main_total(children, sizes, main_gap, is_row);
+
+        if used > main_size && style.overflow != Overflow::Scroll {
+            shrink_children(children, sizes, used - main_size, is_row);
+        } else if used < main_size {
+            grow_children(children, sizes, main_size - used, is_row);
+        }
 
-        total_base_size += base_main;
-        total_flex_grow += child.style.flex_grow;
-        child_sizes.push((base_w, base_h));
+        if is_row {
+            // Text reflows to the width it ended up with
+            for (child, size) in children.iter().zip(sizes.iter_mut()) {
+                if let Some(height) = remeasure_height(child, size.0) {
+                    size.1 = height;
+                }
+            }
+        }
     }
 
-    // Add gaps to total
-    let total_gap = gap * (child_count.saturating_sub(1)) as u16;
-    let total_used = total_base_size + total_gap;
-
-    // Calculate remaining space
-    let remaining = main_size.saturating_sub(total_used);
-
-    // Distribute remaining space to flex-grow items
-    let flex_unit = if total_flex_grow > 0.0 {
-        (remaining as f32) / total_flex_grow
+    // Cross size of each line: a single-line container uses its full cross size
+    let single_line = style.flex_wrap == FlexWrap::NoWrap;
+    let mut line_cross: Vec<u16> = lines
+        .iter()
+        .map(|line| {
+            if single_line {
+                cross_size
+            } else {
+                visible_children[line.clone()]
+                    .iter()
+                    .zip(&child_sizes[line.clone()])
+                    .map(|(child, size)| {
+                        let (_, cross) = main_cross(*size, is_row);
+                        cross + cross_margins(child, is_row).0 + cross_margins(child, is_row).1
+                    })
+                    .max()
+                    .unwrap_or(0)
+            }
+        })
+        .collect();
+
+    // Distribute extra cross space between lines (align-content)
+    let line_count = lines.len() as u16;
+    let used_cross = line_cross.iter().sum::<u16>() + cross_gap * line_count.saturating_sub(1);
+    let free_cross = cross_size.saturating_sub(used_cross);
+    let (mut cross_pos, line_spacing) = if single_line {
+        (0, 0)
     } else {
-        0.0
+        match style.align_content {
+            AlignContent::FlexStart => (0, 0),
+            AlignContent::FlexEnd => (free_cross, 0),
+            AlignContent::Center => (free_cross / 2, 0),
+            AlignContent::SpaceBetween if line_count > 1 => (0, free_cross / (line_count - 1)),
+            AlignContent::SpaceBetween => (0, 0),
+            AlignContent::SpaceAround => (free_cross / (line_count * 2), free_cross / line_count),
+            AlignContent::Stretch => {
+                let shares = distribute(free_cross, &vec![1.0; lines.len()]);
+                for (cross, share) in line_cross.iter_mut().zip(shares) {
+                    *cross += share;
+                }
+                (0, 0)
+            }
+        }
     };
 
-    // Calculate final sizes
-    let mut final_sizes: Vec<(u16, u16)> = Vec::with_capacity(child_count);
-    for (i, child) in visible_children.iter().enumerate() {
-        let (base_w, base_h) = child_sizes[i];
-        let grow_amount = (child.style.flex_grow * flex_unit).round() as u16;
-
-        let (w, h) = if is_row {
-            (base_w + grow_amount, base_h)
-        } else {
-            (base_w, base_h + grow_amount)
+    for (line, &line_size) in lines.iter().zip(&line_cross) {
+        let children = &visible_children[line.clone()];
+        let sizes = &child_sizes[line.clone()];
+        let count = children.len() as u16;
+
+        // Calculate starting position based on justify-content
+        let used = outer_main_total(children, sizes, main_gap, is_row);
+        let extra_space = main_size.saturating_sub(used);
+        let (mut main_pos, space_between) = match style.justify_content {
+            JustifyContent::FlexStart => (0, 0),
+            JustifyContent::FlexEnd => (extra_space, 0),
+            JustifyContent::Center => (extra_space / 2, 0),
+            JustifyContent::SpaceBetween if count > 1 => (0, extra_space / (count - 1)),
+            JustifyContent::SpaceBetween => (0, 0),
+            JustifyContent::SpaceAround => {
+                let space = extra_space / (count * 2);
+                (space, space * 2)
+            }
+            JustifyContent::SpaceEvenly => {
+                let space = extra_space / (count + 1);
+                (space, space)
+            }
         };
 
-        final_sizes.push((w, h));
-    }
+        for (child, &size) in children.iter().zip(sizes) {
+            let (child_main, child_cross) = main_cross(size, is_row);
+            let (margin_main_start, margin_main_end) = main_margins(child, is_row);
+            let (margin_cross_start, margin_cross_end) = cross_margins(child, is_row);
+            let cross_space = line_size.saturating_sub(margin_cross_start + margin_cross_end);
+
+            // Only auto-sized items stretch across the cross axis
+            let align = effective_align(style, child);
+            let cross_auto = if is_row {
+                matches!(child.style.height, Size::Auto)
+            } else {
+                matches!(child.style.width, Size::Auto)
+            };
+            let final_cross = if matches!(align, AlignItems::Stretch) && cross_auto {
+                if is_row {
+                    apply_constraints(cross_space, child.style.min_height, child.style.max_height)
+                } else {
+                    apply_constraints(cross_space, child.style.min_width, child.style.max_width)
+                }
+            } else {
+                child_cross
+            };
 
-    // Calculate starting position based on justify-content
-    let total_final_main: u16 = final_sizes.iter().map(|(w, h)| if is_row { *w } else { *h }).sum();
-    let total_with_gaps = total_final_main + total_gap;
-    let extra_space = main_size.saturating_sub(total_with_gaps);
+            let cross_offset = margin_cross_start
+                + match align {
+                    AlignItems::FlexEnd => cross_space.saturating_sub(final_cross),
+                    AlignItems::Center => cross_space.saturating_sub(final_cross) / 2,
+                    // TODO: proper baseline calculation
+                    AlignItems::FlexStart | AlignItems::Stretch | AlignItems::Baseline => 0,
+                };
+
+            // Reversed axes mirror positions from the far edge
+            let mut item_main = main_pos + margin_main_start;
+            if is_reversed {
+                item_main = main_size.saturating_sub(item_main + child_main);
+            }
+            let mut item_cross = cross_pos + cross_offset;
+            if style.flex_wrap == FlexWrap::WrapReverse {
+                item_cross = cross_size.saturating_sub(item_cross + final_cross);
+            }
 
-    let (mut main_pos, space_between, _space_around) = match style.justify_content {
-        JustifyContent::FlexStart => (0, 0, 0),
-        JustifyContent::FlexEnd => (extra_space, 0, 0),
-        JustifyContent::Center => (extra_space / 2, 0, 0),
-        JustifyContent::SpaceBetween => {
-            let space = if child_count > 1 {
-                extra_space / (child_count as u16 - 1)
+            let (child_x, child_y, final_w, final_h) = if is_row {
+                (inner_x + item_main, inner_y + item_cross, child_main, final_cross)
             } else {
-                0
+                (inner_x + item_cross, inner_y + item_main, final_cross, child_main)
             };
-            (0, space, 0)
-        }
-        JustifyContent::SpaceAround => {
-            let space = extra_space / (child_count as u16 * 2);
-            (space, space * 2, 0)
-        }
-        JustifyContent::SpaceEvenly => {
-            let space = extra_space / (child_count as u16 + 1);
-            (space, space, 0)
+
+            // Recursively layout this child
+            place_node(child, child_x, child_y, final_w, final_h, containing_block, layouts);
+
+            // Move to next position
+            main_pos += margin_main_start + child_main + margin_main_end + main_gap + space_between;
         }
-    };
 
-    // Reverse order if needed
-    let order: Vec<usize> = if is_reversed {
-        (0..child_count).rev().collect()
+        cross_pos += line_size + cross_gap + line_spacing;
+    }
+}
+
+/// Split main and cross components of a (width, height) pair.
+fn main_cross(size: (u16, u16), is_row: bool) -> (u16, u16) {
+    if is_row {
+        size
     } else {
-        (0..child_count).collect()
-    };
+        (size.1, size.0)
+    }
+}
 
-    // Position children
-    for (iter_idx, &i) in order.iter().enumerate() {
-        let child = visible_children[i];
-        let (child_w, child_h) = final_sizes[i];
-
-        let child_main = if is_row { child_w } else { child_h };
-        let child_cross = if is_row { child_h } else { child_w };
-
-        // Calculate cross-axis position based on align_items
-        let align = match child.style.align_self {
-            AlignSelf::Auto => style.align_items,
-            AlignSelf::FlexStart => AlignItems::FlexStart,
-            AlignSelf::FlexEnd => AlignItems::FlexEnd,
-            AlignSelf::Center => AlignItems::Center,
-            AlignSelf::Stretch => AlignItems::Stretch,
-            AlignSelf::Baseline => AlignItems::Baseline,
-        };
+/// Start and end margins of a node along the main axis.
+fn main_margins(node: &LayoutNode, is_row: bool) -> (u16, u16) {
+    let margin = &node.style.margin;
+    if is_row {
+        (margin.left, margin.right)
+    } else {
+        (margin.top, margin.bottom)
+    }
+}
 
-        let cross_pos = match align {
-            AlignItems::FlexStart => 0,
-            AlignItems::FlexEnd => cross_size.saturating_sub(child_cross),
-            AlignItems::Center => (cross_size.saturating_sub(child_cross)) / 2,
-            AlignItems::Stretch => 0,
-            AlignItems::Baseline => 0, // TODO: proper baseline calculation
-        };
+/// Start and end margins of a node along the cross axis.
+fn cross_margins(node: &LayoutNode, is_row: bool) -> (u16, u16) {
+    main_margins(node, !is_row)
+}
 
-        let stretched_cross = if matches!(align, AlignItems::Stretch) {
-            cross_size
-        } else {
-            child_cross
-        };
+/// Total main-axis space taken by items, including margins and gaps.
+fn outer_main_total(children: &[&LayoutNode], sizes: &[(u16, u16)], gap: u16, is_row: bool) -> u16 {
+    let items: u16 = children
+        .iter()
+        .zip(sizes)
+        .map(|(child, size)| {
+            let (start, end) = main_margins(child, is_row);
+            main_cross(*size, is_row).0 + start + end
+        })
+        .sum();
+    items + gap * (children.len() as u16).saturating_sub(1)
+}
 
-        // Calculate final position
-        let (child_x, child_y, final_w, final_h) = if is_row {
-            (
-                inner_x + main_pos,
-                inner_y + cross_pos,
-                child_w,
-                stretched_cross,
-            )
-        } else {
-            (
-                inner_x + cross_pos,
-                inner_y + main_pos,
-                stretched_cross,
-                child_h,
-            )
-        };
+/// Break items into flex lines, returning the index range of each line.
+fn collect_lines(
+    children: &[&LayoutNode],
+    sizes: &[(u16, u16)],
+    wrap: FlexWrap,
+    main_size: u16,
+    gap: u16,
+    is_row: bool,
+) -> Vec<std::ops::Range<usize>> {
+    if wrap == FlexWrap::NoWrap {
+        return std::iter::once(0..children.len()).collect();
+    }
 
-        // Recursively layout this child
-        calculate_node_layout(child, child_x, child_y, final_w, final_h, layouts);
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut used: u16 = 0;
+    for (i, (child, size)) in children.iter().zip(sizes).enumerate() {
+        let (margin_start, margin_end) = main_margins(child, is_row);
+        let outer = main_cross(*size, is_row).0 + margin_start + margin_end;
+        // Every line holds at least one item
+        if i > start && used + gap + outer > main_size {
+            lines.push(start..i);
+            start = i;
+            used = outer;
+        } else if i > start {
+            used += gap + outer;
+        } else {
+            used = outer;
+        }
+    }
+    lines.push(start..children.len());
+    lines
+}
 
-        // Move to next position
-        main_pos += child_main + gap + space_between;
+/// Split `total` in proportion to `weights`, rounding so the shares add up.
+fn distribute(total: u16, weights: &[f32]) -> Vec<u16> {
+    let sum: f32 = weights.iter().sum();
+    if sum <= 0.0 {
+        return vec![0; weights.len()];
+    }
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut cumulative = 0.0;
+    let mut given: u16 = 0;
+    for weight in weights {
+        cumulative += weight;
+        let upto = (f32::from(total) * cumulative / sum).round() as u16;
+        shares.push(upto - given);
+        given = upto;
+    }
+    shares
+}
 
-        if iter_idx == 0 && !matches!(style.justify_content, JustifyContent::SpaceAround) {
-            // First item doesn't add extra space for SpaceAround
+/// Distribute free main-axis space to children by `flex_grow`.
+fn grow_children(children: &[&LayoutNode], sizes: &mut [(u16, u16)], free: u16, is_row: bool) {
+    let weights: Vec<f32> = children.iter().map(|c| c.style.flex_grow.max(0.0)).collect();
+    for ((child, size), share) in children.iter().zip(sizes.iter_mut()).zip(distribute(free, &weights)) {
+        if share == 0 {
+            continue;
+        }
+        let style = &child.style;
+        if is_row {
+            size.0 = apply_constraints(size.0 + share, style.min_width, style.max_width);
+        } else {
+            size.1 = apply_constraints(size.1 + share, style.min_height, style.max_height);
         }
     }
 }
 
-fn calculate_child_base_size(
-    child: &LayoutNode,
-    parent_width: u16,
-    parent_height: u16,
-    is_row: bool,
-) -> (u16, u16) {
+/// Resolve a child's cross-axis alignment against its parent.
+fn effective_align(parent: &LayoutStyle, child: &LayoutNode) -> AlignItems {
+    match child.style.align_self {
+        AlignSelf::Auto => parent.align_items,
+        AlignSelf::FlexStart => AlignItems::FlexStart,
+        AlignSelf::FlexEnd => AlignItems::FlexEnd,
+        AlignSelf::Center => AlignItems::Center,
+        AlignSelf::Stretch => AlignItems::Stretch,
+        AlignSelf::Baseline => AlignItems::Baseline,
+    }
+}
+
+/// Height of an auto-height text leaf laid out at `width`, if it has one.
+fn remeasure_height(child: &LayoutNode, width: u16) -> Option<u16> {
     let style = &child.style;
+    if child.measure.is_none() || !matches!(style.height, Size::Auto) {
+        return None;
+    }
     let padding_h = style.padding.horizontal() + style.border_width * 2;
     let padding_v = style.padding.vertical() + style.border_width * 2;
+    let height = child.leaf_content_size(width.saturating_sub(padding_h)).1 + padding_v;
+    Some(apply_constraints(height, style.min_height, style.max_height))
+}
 
-    // Calculate base width
-    let base_width = match style.width {
-        Size::Fixed(w) => w,
-        Size::Percent(p) => ((parent_width as f32) * p / 100.0).round() as u16,
-        Size::Auto => {
-            if child.children.is_empty() {
-                child.content_size.0 + padding_h
-            } else {
-                // For containers, use minimum content width
-                padding_h
-            }
-        }
-        Size::Fill => {
-            if is_row {
-                0 // Will be calculated based on flex_grow
-            } else {
-                parent_width
+/// Smallest main-axis size a child may be shrunk to.
+fn min_main_size(child: &LayoutNode, size: (u16, u16), is_row: bool) -> u16 {
+    let style = &child.style;
+    let (explicit_min, padding) = if is_row {
+        (style.min_width, style.padding.horizontal() + style.border_width * 2)
+    } else {
+        (style.min_height, style.padding.vertical() + style.border_width * 2)
+    };
+    if let Some(min) = explicit_min {
+        return min;
+    }
+    if !child.children.is_empty() {
+        return padding;
+    }
+    match (&child.measure, is_row) {
+        (Some(measure), true) => measure.min_content_width() + padding,
+        // In a column, text cannot be shorter than its wrapped line count
+        (Some(_), false) => size.1,
+        (None, true) => child.content_size.0 + padding,
+        (None, false) => child.content_size.1 + padding,
+    }
+}
+
+/// Distribute main-axis overflow across children by `flex_shrink`.
+///
+/// As in CSS, each item shrinks in proportion to `flex_shrink * base size`;
+/// items that reach their minimum size are frozen and the remaining
+/// overflow is redistributed among the others.
+fn shrink_children(
+    children: &[&LayoutNode],
+    sizes: &mut [(u16, u16)],
+    overflow: u16,
+    is_row: bool,
+) {
+    let main = |size: (u16, u16)| if is_row { size.0 } else { size.1 };
+    let mins: Vec<u16> = children
+        .iter()
+        .zip(sizes.iter())
+        .map(|(child, size)| min_main_size(child, *size, is_row))
+        .collect();
+    let mut frozen: Vec<bool> = children
+        .iter()
+        .zip(sizes.iter())
+        .zip(&mins)
+        .map(|((child, size), min)| child.style.flex_shrink <= 0.0 || main(*size) <= *min)
+        .collect();
+    let mut overflow = f32::from(overflow);
+
+    while overflow >= 0.5 {
+        let total_scaled: f32 = children
+            .iter()
+            .zip(sizes.iter())
+            .zip(&frozen)
+            .filter(|(_, frozen)| !**frozen)
+            .map(|((child, size), _)| child.style.flex_shrink * f32::from(main(*size)))
+            .sum();
+        if total_scaled <= 0.0 {
+            break;
+        }
+
+        let mut removed = 0.0;
+        for (i, child) in children.iter().enumerate() {
+            if frozen[i] {
+                continue;
+            }
+            let current = main(sizes[i]);
+            let share = overflow * child.style.flex_shrink * f32::from(current) / total_scaled;
+            let target = (f32::from(current) - share).round().max(f32::from(mins[i]));
+            let target = target as u16;
+            if target <= mins[i] {
+                frozen[i] = true;
+            }
+            removed += f32::from(current - target);
+            if is_row {
+                sizes[i].0 = target;
+            } else {
+                sizes[i].1 = target;
+            }
+        }
+
+        if removed <= 0.0 {
+            break;
+        }
+        overflow -= removed;
+    }
+}
+
+fn calculate_child_base_size(
+    child: &LayoutNode,
+    parent_width: u16,
+    parent_height: u16,
+    is_row: bool,
+    stretch: bool,
+) -> (u16, u16) {
+    let style = &child.style;
+    let padding_h = style.padding.horizontal() + style.border_width * 2;
+    let padding_v = style.padding.vertical() + style.border_width * 2;
+    // Room left across a column once the child's own margins are taken out
+    let fit_width = parent_width.saturating_sub(style.margin.horizontal());
+
+    // Calculate base width
+    let base_width = match style.width {
+        Size::Fixed(w) => w,
+        Size::Percent(p) => ((parent_width as f32) * p / 100.0).round() as u16,
+        Size::Auto => {
+            if child.children.is_empty() {
+                let natural = child.content_size.0 + padding_h;
+                if child.measure.is_some() && !is_row {
+                    // Text in a column wraps within the cross axis
+                    natural.min(fit_width)
+                } else {
+                    natural
+                }
+            } else {
+                // Containers take the natural size of their content
+                let natural = intrinsic_size(child, fit_width).0;
+                if is_row {
+                    natural
+                } else {
+                    natural.min(fit_width)
+                }
+            }
+        }
+        Size::Fill => {
+            if is_row {
+                0 // Will be calculated based on flex_grow
+            } else {
+                parent_width
             }
         }
     };
@@ -706,11 +1482,16 @@ fn calculate_child_base_size(
         Size::Fixed(h) => h,
         Size::Percent(p) => ((parent_height as f32) * p / 100.0).round() as u16,
         Size::Auto => {
+            // Measure content at the width it will be laid out in
+            let measure_width = if !is_row && stretch && matches!(style.width, Size::Auto) {
+                apply_constraints(fit_width, style.min_width, style.max_width)
+            } else {
+                apply_constraints(base_width, style.min_width, style.max_width)
+            };
             if child.children.is_empty() {
-                child.content_size.1 + padding_v
+                child.leaf_content_size(measure_width.saturating_sub(padding_h)).1 + padding_v
             } else {
-                // For containers, use minimum content height
-                padding_v
+                intrinsic_size(child, measure_width).1
             }
         }
         Size::Fill => {
@@ -764,6 +1545,692 @@ fn apply_constraints(value: u16, min: Option<u16>, max: Option<u16>) -> u16 {
     result
 }
 
+// =============================================================================
+// Layout Cache
+// =============================================================================
+
+/// Everything a subtree's layout depends on, besides its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PlacementKey {
+    fingerprint: u64,
+    width: u16,
+    height: u16,
+    /// Containing block relative to the node's flow position, when absolute
+    /// descendants are placed against it
+    containing_block: Option<(i32, i32, u16, u16)>,
+}
+
+/// A descendant's layout relative to the cached node's flow position.
+#[derive(Debug, Clone, Copy)]
+struct RelativeLayout {
+    id: u64,
+    dx: i32,
+    dy: i32,
+    width: u16,
+    height: u16,
+}
+
+#[derive(Debug, Clone)]
+struct CachedPlacement {
+    key: PlacementKey,
+    layouts: Vec<RelativeLayout>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedIntrinsic {
+    fingerprint: u64,
+    /// `(max_width, size)`, most recent last
+    sizes: Vec<(u16, (u16, u16))>,
+}
+
+/// Intrinsic sizes remembered per node, across different width limits.
+const INTRINSIC_CACHE_WIDTHS: usize = 8;
+
+/// Layout work kept between frames.
+///
+/// Each subtree is fingerprinted from its ids, styles and text. When a
+/// container's fingerprint, size and containing block match an earlier
+/// layout, its stored descendant layouts are reused (shifted if the
+/// container moved) without visiting its children. Intrinsic sizes are
+/// remembered the same way, so an unchanged table next to a ticking
+/// spinner is neither measured nor laid out again.
+#[derive(Debug, Default)]
+pub struct LayoutCache {
+    placements: HashMap<u64, CachedPlacement>,
+    intrinsic: HashMap<u64, CachedIntrinsic>,
+    hits: usize,
+    misses: usize,
+}
+
+impl LayoutCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget all cached layouts.
+    pub fn clear(&mut self) {
+        self.placements.clear();
+        self.intrinsic.clear();
+    }
+
+    /// Number of subtrees with a cached layout.
+    pub fn len(&self) -> usize {
+        self.placements.len()
+    }
+
+    /// Whether nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+
+    /// Subtrees reused during the last calculation.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Subtrees laid out from scratch during the last calculation.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+/// A subtree's hash, whether absolute descendants escape it to be
+/// placed against an outer containing block, and whether it holds static
+/// nodes pinned to a screen position.
+#[derive(Debug, Clone, Copy)]
+struct Fingerprint {
+    hash: u64,
+    uses_containing_block: bool,
+    pinned: bool,
+}
+
+/// The cache in use by the current `calculate_layout_cached` call.
+struct ActiveCache {
+    cache: LayoutCache,
+    fingerprints: HashMap<u64, Fingerprint>,
+}
+
+thread_local! {
+    static ACTIVE_CACHE: RefCell<Option<ActiveCache>> = const { RefCell::new(None) };
+}
+
+/// Calculate layout, reusing unchanged subtrees from earlier calls.
+///
+/// Produces the same result as [`calculate_layout`].
+pub fn calculate_layout_cached(
+    node: &LayoutNode,
+    available_width: u16,
+    available_height: u16,
+    cache: &mut LayoutCache,
+) -> HashMap<u64, ComputedLayout> {
+    let mut fingerprints = HashMap::new();
+    fingerprint(node, &mut fingerprints);
+
+    let mut active = std::mem::take(cache);
+    active.hits = 0;
+    active.misses = 0;
+    ACTIVE_CACHE.with(|a| {
+        *a.borrow_mut() = Some(ActiveCache { cache: active, fingerprints });
+    });
+
+    let layouts = calculate_layout(node, available_width, available_height);
+
+    if let Some(ActiveCache { cache: mut done, fingerprints }) = ACTIVE_CACHE.with(|a| a.borrow_mut().take()) {
+        // Drop entries for nodes that are gone
+        done.placements.retain(|id, _| fingerprints.contains_key(id));
+        done.intrinsic.retain(|id, _| fingerprints.contains_key(id));
+        *cache = done;
+    }
+    layouts
+}
+
+/// Hash a subtree's ids, styles and content, recording every node's hash.
+fn fingerprint(node: &LayoutNode, out: &mut HashMap<u64, Fingerprint>) -> Fingerprint {
+    let mut hasher = DefaultHasher::new();
+    node.id.hash(&mut hasher);
+    node.style.hash(&mut hasher);
+    node.content_size.hash(&mut hasher);
+    node.measure.hash(&mut hasher);
+
+    let mut escapes = false;
+    let mut pinned = node.style.position == Position::Static
+        && (node.style.position_x.is_some() || node.style.position_y.is_some());
+    for child in &node.children {
+        let child_print = fingerprint(child, out);
+        child_print.hash.hash(&mut hasher);
+        escapes |= child.style.position == Position::Absolute || child_print.uses_containing_block;
+        pinned |= child_print.pinned;
+    }
+
+    // Positioned nodes contain their own absolute descendants
+    let print = Fingerprint {
+        hash: hasher.finish(),
+        uses_containing_block: escapes && node.style.position == Position::Static,
+        pinned,
+    };
+    out.insert(node.id, print);
+    print
+}
+
+fn with_active_cache<R>(f: impl FnOnce(&mut ActiveCache) -> R) -> Option<R> {
+    ACTIVE_CACHE.with(|a| a.borrow_mut().as_mut().map(f))
+}
+
+fn placement_key(
+    node: &LayoutNode,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    containing_block: ComputedLayout,
+) -> Option<PlacementKey> {
+    // Leaves are cheaper to place than to look up
+    if node.children.is_empty() {
+        return None;
+    }
+    let print = with_active_cache(|active| active.fingerprints.get(&node.id).copied())??;
+    // Pinned nodes don't move with the subtree, so it can't be shifted
+    if print.pinned {
+        return None;
+    }
+    Some(PlacementKey {
+        fingerprint: print.hash,
+        width,
+        height,
+        containing_block: print.uses_containing_block.then(|| {
+            (
+                i32::from(containing_block.x) - i32::from(x),
+                i32::from(containing_block.y) - i32::from(y),
+                containing_block.width,
+                containing_block.height,
+            )
+        }),
+    })
+}
+
+fn reuse_placement(id: u64, key: PlacementKey, x: u16, y: u16, layouts: &mut HashMap<u64, ComputedLayout>) -> bool {
+    with_active_cache(|active| {
+        let Some(cached) = active.cache.placements.get(&id).filter(|c| c.key == key) else {
+            active.cache.misses += 1;
+            return false;
+        };
+        active.cache.hits += 1;
+        for layout in &cached.layouts {
+            let shift = |origin: u16, delta: i32| (i32::from(origin) + delta).clamp(0, i32::from(u16::MAX)) as u16;
+            layouts.insert(
+                layout.id,
+                ComputedLayout {
+                    x: shift(x, layout.dx),
+                    y: shift(y, layout.dy),
+                    width: layout.width,
+                    height: layout.height,
+                },
+            );
+        }
+        true
+    })
+    .unwrap_or(false)
+}
+
+fn remember_placement(node: &LayoutNode, key: PlacementKey, x: u16, y: u16, layouts: &HashMap<u64, ComputedLayout>) {
+    fn collect(node: &LayoutNode, x: u16, y: u16, layouts: &HashMap<u64, ComputedLayout>, out: &mut Vec<RelativeLayout>) {
+        if let Some(layout) = layouts.get(&node.id) {
+            out.push(RelativeLayout {
+                id: node.id,
+                dx: i32::from(layout.x) - i32::from(x),
+                dy: i32::from(layout.y) - i32::from(y),
+                width: layout.width,
+                height: layout.height,
+            });
+        }
+        for child in &node.children {
+            collect(child, x, y, layouts, out);
+        }
+    }
+
+    let mut relative = Vec::new();
+    collect(node, x, y, layouts, &mut relative);
+    with_active_cache(|active| {
+        active.cache.placements.insert(node.id, CachedPlacement { key, layouts: relative });
+    });
+}
+
+fn cached_intrinsic(id: u64, max_width: u16) -> Option<(u16, u16)> {
+    with_active_cache(|active| {
+        let print = active.fingerprints.get(&id)?;
+        let entry = active.cache.intrinsic.get(&id).filter(|e| e.fingerprint == print.hash)?;
+        entry.sizes.iter().find(|(width, _)| *width == max_width).map(|(_, size)| *size)
+    })
+    .flatten()
+}
+
+fn remember_intrinsic(id: u64, max_width: u16, size: (u16, u16)) {
+    with_active_cache(|active| {
+        let Some(fingerprint) = active.fingerprints.get(&id).map(|print| print.hash) else {
+            return;
+        };
+        let entry = active.cache.intrinsic.entry(id).or_insert_with(|| CachedIntrinsic {
+            fingerprint,
+            sizes: Vec::new(),
+        });
+        if entry.fingerprint != fingerprint {
+            entry.fingerprint = fingerprint;
+            entry.sizes.clear();
+        }
+        if entry.sizes.len() == INTRINSIC_CACHE_WIDTHS {
+            entry.sizes.remove(0);
+        }
+        entry.sizes.push((max_width, size));
+    });
+}
+
+// =============================================================================
+// Grid Layout
+// =============================================================================
+
+/// Cells covered by a grid item, as half-open track ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GridArea {
+    col: usize,
+    col_end: usize,
+    row: usize,
+    row_end: usize,
+}
+
+impl GridArea {
+    fn new(col: usize, col_span: usize, row: usize, row_span: usize) -> Self {
+        Self {
+            col,
+            col_end: col + col_span,
+            row,
+            row_end: row + row_span,
+        }
+    }
+}
+
+/// Bounding cells of each name in a `grid_template_areas` list.
+fn template_areas(rows: &[String]) -> HashMap<&str, GridArea> {
+    let mut areas: HashMap<&str, GridArea> = HashMap::new();
+    for (row, line) in rows.iter().enumerate() {
+        for (col, name) in line.split_whitespace().enumerate() {
+            if name == "." {
+                continue;
+            }
+            areas
+                .entry(name)
+                .and_modify(|area| {
+                    area.col = area.col.min(col);
+                    area.col_end = area.col_end.max(col + 1);
+                    area.row = area.row.min(row);
+                    area.row_end = area.row_end.max(row + 1);
+                })
+                .or_insert_with(|| GridArea::new(col, 1, row, 1));
+        }
+    }
+    areas
+}
+
+fn is_free(occupied: &[Vec<bool>], area: GridArea) -> bool {
+    (area.row..area.row_end).all(|row| {
+        occupied
+            .get(row)
+            .map_or(true, |cells| (area.col..area.col_end).all(|col| !cells.get(col).copied().unwrap_or(false)))
+    })
+}
+
+fn occupy(occupied: &mut Vec<Vec<bool>>, area: GridArea, columns: usize) {
+    if occupied.len() < area.row_end {
+        occupied.resize(area.row_end, vec![false; columns]);
+    }
+    for cells in &mut occupied[area.row..area.row_end] {
+        for cell in cells.iter_mut().take(area.col_end).skip(area.col) {
+            *cell = true;
+        }
+    }
+}
+
+/// Assign every item to grid cells, returning the areas and the grid's
+/// column and row counts.
+///
+/// Items with a named area or both start lines are placed first; the rest
+/// fill the next free cells in row-major order, adding rows as needed.
+fn place_grid_items(style: &LayoutStyle, items: &[&LayoutNode]) -> (Vec<GridArea>, usize, usize) {
+    let named = template_areas(&style.grid_template_areas);
+    let area_columns = style
+        .grid_template_areas
+        .iter()
+        .map(|row| row.split_whitespace().count())
+        .max()
+        .unwrap_or(0);
+
+    // Explicit column lines beyond the template add implicit columns
+    let mut columns = style.grid_template_columns.len().max(area_columns);
+    for item in items {
+        let placement = item.style.grid_column;
+        let span = usize::from(placement.span.max(1));
+        let start = placement.start.map_or(0, |line| usize::from(line.max(1)) - 1);
+        columns = columns.max(start + span);
+    }
+    let columns = columns.max(1);
+
+    let mut occupied: Vec<Vec<bool>> = Vec::new();
+    let mut placed: Vec<Option<GridArea>> = vec![None; items.len()];
+
+    for (slot, item) in placed.iter_mut().zip(items) {
+        let style = &item.style;
+        let area = match style.grid_area.as_deref().and_then(|name| named.get(name)) {
+            Some(area) => Some(*area),
+            None => match (style.grid_column.start, style.grid_row.start) {
+                (Some(col), Some(row)) => Some(GridArea::new(
+                    usize::from(col.max(1)) - 1,
+                    usize::from(style.grid_column.span.max(1)),
+                    usize::from(row.max(1)) - 1,
+                    usize::from(style.grid_row.span.max(1)),
+                )),
+                _ => None,
+            },
+        };
+        if let Some(area) = area {
+            occupy(&mut occupied, area, columns);
+            *slot = Some(area);
+        }
+    }
+
+    let (mut cursor_row, mut cursor_col) = (0, 0);
+    for (slot, item) in placed.iter_mut().zip(items) {
+        if slot.is_some() {
+            continue;
+        }
+        let style = &item.style;
+        let col_span = usize::from(style.grid_column.span.max(1)).min(columns);
+        let row_span = usize::from(style.grid_row.span.max(1));
+
+        let area = match (style.grid_column.start, style.grid_row.start) {
+            // Fixed column: first row where it fits
+            (Some(col), _) => {
+                let col = usize::from(col.max(1)) - 1;
+                (0..)
+                    .map(|row| GridArea::new(col, col_span, row, row_span))
+                    .find(|area| is_free(&occupied, *area))
+                    .unwrap_or_else(|| GridArea::new(col, col_span, 0, row_span))
+            }
+            // Fixed row: first column where it fits, else past the end
+            (None, Some(row)) => {
+                let row = usize::from(row.max(1)) - 1;
+                (0..=columns - col_span)
+                    .map(|col| GridArea::new(col, col_span, row, row_span))
+                    .find(|area| is_free(&occupied, *area))
+                    .unwrap_or_else(|| GridArea::new(0, col_span, occupied.len().max(row), row_span))
+            }
+            (None, None) => loop {
+                if cursor_col + col_span > columns {
+                    cursor_col = 0;
+                    cursor_row += 1;
+                }
+                let area = GridArea::new(cursor_col, col_span, cursor_row, row_span);
+                if is_free(&occupied, area) {
+                    cursor_col = area.col_end;
+                    break area;
+                }
+                cursor_col += 1;
+            },
+        };
+        occupy(&mut occupied, area, columns);
+        *slot = Some(area);
+    }
+
+    let rows = style
+        .grid_template_rows
+        .len()
+        .max(style.grid_template_areas.len())
+        .max(occupied.len());
+    (placed.into_iter().flatten().collect(), columns, rows)
+}
+
+/// Resolve track sizes along one axis.
+///
+/// `contributions` holds `(first track, span, outer size)` per item. Auto
+/// and `fr` tracks grow to fit their items; with a definite `available`
+/// size, `fr` tracks then share what's left (never dropping below their
+/// content), or auto tracks stretch when there are no `fr` tracks.
+fn size_tracks(
+    template: &[GridTrack],
+    implicit: GridTrack,
+    count: usize,
+    available: Option<u16>,
+    gap: u16,
+    contributions: &[(usize, usize, u16)],
+) -> Vec<u16> {
+    let track = |i: usize| template.get(i).copied().unwrap_or(implicit);
+    let content_sized = |i: usize| match track(i) {
+        GridTrack::Auto | GridTrack::Fr(_) => true,
+        GridTrack::Percent(_) => available.is_none(),
+        GridTrack::Fixed(_) => false,
+    };
+
+    let mut sizes: Vec<u16> = (0..count)
+        .map(|i| match (track(i), available) {
+            (GridTrack::Fixed(v), _) => v,
+            (GridTrack::Percent(p), Some(space)) => ((space as f32) * p / 100.0).round() as u16,
+            _ => 0,
+        })
+        .collect();
+
+    // Single-track items size their track directly
+    for &(start, _, size) in contributions.iter().filter(|c| c.1 == 1) {
+        if content_sized(start) {
+            sizes[start] = sizes[start].max(size);
+        }
+    }
+
+    // Spanning items push any shortfall into their last flexible track
+    for &(start, span, size) in contributions.iter().filter(|c| c.1 > 1) {
+        let end = (start + span).min(count);
+        let current = sizes[start..end].iter().sum::<u16>() + gap * (end - start - 1) as u16;
+        if let Some(last) = (start..end).rev().find(|&i| content_sized(i)) {
+            sizes[last] += size.saturating_sub(current);
+        }
+    }
+
+    let Some(available) = available else {
+        return sizes;
+    };
+    let space = available.saturating_sub(gap * (count as u16).saturating_sub(1));
+
+    let flexible: Vec<usize> = (0..count).filter(|&i| matches!(track(i), GridTrack::Fr(_))).collect();
+    if flexible.is_empty() {
+        let auto: Vec<usize> = (0..count).filter(|&i| track(i) == GridTrack::Auto).collect();
+        let free = space.saturating_sub(sizes.iter().sum());
+        for (i, share) in auto.iter().zip(distribute(free, &vec![1.0; auto.len()])) {
+            sizes[*i] += share;
+        }
+        return sizes;
+    }
+
+    // Find the fr unit, freezing tracks whose content outgrows their share
+    let fr = |i: usize| match track(i) {
+        GridTrack::Fr(f) => f.max(0.0),
+        _ => 0.0,
+    };
+    let mut frozen = vec![false; count];
+    loop {
+        let fixed: u16 = (0..count)
+            .filter(|&i| !flexible.contains(&i) || frozen[i])
+            .map(|i| sizes[i])
+            .sum();
+        let free = space.saturating_sub(fixed);
+        let active: Vec<usize> = flexible.iter().copied().filter(|&i| !frozen[i]).collect();
+        let total: f32 = active.iter().map(|&i| fr(i)).sum();
+        if total <= 0.0 {
+            break;
+        }
+
+        let unit = free as f32 / total;
+        let mut changed = false;
+        for &i in &active {
+            if fr(i) * unit < sizes[i] as f32 {
+                frozen[i] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            let weights: Vec<f32> = active.iter().map(|&i| fr(i)).collect();
+            for (i, share) in active.iter().zip(distribute(free, &weights)) {
+                sizes[*i] = share;
+            }
+            break;
+        }
+    }
+    sizes
+}
+
+/// Offset of each track from the start of the grid.
+fn track_starts(sizes: &[u16], gap: u16) -> Vec<u16> {
+    let mut pos = 0;
+    sizes
+        .iter()
+        .map(|&size| {
+            let start = pos;
+            pos += size + gap;
+            start
+        })
+        .collect()
+}
+
+/// Length covered by tracks `start..end`, including the gaps between them.
+fn span_length(starts: &[u16], sizes: &[u16], start: usize, end: usize) -> u16 {
+    starts[end - 1] + sizes[end - 1] - starts[start]
+}
+
+/// Outer width an item asks for when its column is sized to content.
+fn grid_item_width(item: &LayoutNode, max_width: u16) -> u16 {
+    let margin = item.style.margin.horizontal();
+    intrinsic_size(item, max_width.saturating_sub(margin)).0 + margin
+}
+
+/// Outer height an item asks for at the given outer width.
+fn grid_item_height(item: &LayoutNode, width: u16) -> u16 {
+    let margin = &item.style.margin;
+    intrinsic_size(item, width.saturating_sub(margin.horizontal())).1 + margin.vertical()
+}
+
+/// Flow children of a grid and the cells each occupies, with sized tracks.
+struct GridTracks<'a> {
+    items: Vec<&'a LayoutNode>,
+    areas: Vec<GridArea>,
+    columns: Vec<u16>,
+    rows: Vec<u16>,
+}
+
+fn resolve_grid(node: &LayoutNode, width: Option<u16>, height: Option<u16>) -> GridTracks<'_> {
+    let style = &node.style;
+    let column_gap = style.column_gap.unwrap_or(style.gap);
+    let row_gap = style.row_gap.unwrap_or(style.gap);
+
+    let items: Vec<&LayoutNode> = node
+        .children
+        .iter()
+        .filter(|c| c.style.display && c.style.position != Position::Absolute)
+        .collect();
+    let (areas, column_count, row_count) = place_grid_items(style, &items);
+
+    let max_width = width.unwrap_or(u16::MAX);
+    let contributions: Vec<_> = items
+        .iter()
+        .zip(&areas)
+        .map(|(item, area)| (area.col, area.col_end - area.col, grid_item_width(item, max_width)))
+        .collect();
+    let columns = size_tracks(&style.grid_template_columns, GridTrack::Auto, column_count, width, column_gap, &contributions);
+
+    let column_starts = track_starts(&columns, column_gap);
+    let contributions: Vec<_> = items
+        .iter()
+        .zip(&areas)
+        .map(|(item, area)| {
+            let cell_width = span_length(&column_starts, &columns, area.col, area.col_end);
+            (area.row, area.row_end - area.row, grid_item_height(item, cell_width))
+        })
+        .collect();
+    let rows = size_tracks(&style.grid_template_rows, style.grid_auto_rows, row_count, height, row_gap, &contributions);
+
+    GridTracks { items, areas, columns, rows }
+}
+
+/// Content size of a grid container.
+///
+/// Grids with `fr` columns fill `max_width`, like block content; others
+/// size their columns to fit.
+fn grid_content_size(node: &LayoutNode, max_width: u16) -> (u16, u16) {
+    let style = &node.style;
+    let has_fr = style.grid_template_columns.iter().any(|t| matches!(t, GridTrack::Fr(_)));
+    let grid = resolve_grid(node, has_fr.then_some(max_width), None);
+
+    let total = |sizes: &[u16], gap: u16| sizes.iter().sum::<u16>() + gap * (sizes.len() as u16).saturating_sub(1);
+    (
+        total(&grid.columns, style.column_gap.unwrap_or(style.gap)),
+        total(&grid.rows, style.row_gap.unwrap_or(style.gap)),
+    )
+}
+
+/// Lay out the children of a `Display::Grid` container.
+fn layout_grid(
+    node: &LayoutNode,
+    inner_x: u16,
+    inner_y: u16,
+    inner_width: u16,
+    inner_height: u16,
+    containing_block: ComputedLayout,
+    layouts: &mut HashMap<u64, ComputedLayout>,
+) {
+    let style = &node.style;
+    let grid = resolve_grid(node, Some(inner_width), Some(inner_height));
+    let column_starts = track_starts(&grid.columns, style.column_gap.unwrap_or(style.gap));
+    let row_starts = track_starts(&grid.rows, style.row_gap.unwrap_or(style.gap));
+
+    for (item, area) in grid.items.iter().zip(&grid.areas) {
+        let cell_width = span_length(&column_starts, &grid.columns, area.col, area.col_end);
+        let cell_height = span_length(&row_starts, &grid.rows, area.row, area.row_end);
+        let margin = &item.style.margin;
+        let space_w = cell_width.saturating_sub(margin.horizontal());
+        let space_h = cell_height.saturating_sub(margin.vertical());
+
+        // Items fill their cell's width; height follows align-items/align-self
+        let width = match item.style.width {
+            Size::Fixed(w) => w,
+            Size::Percent(p) => ((cell_width as f32) * p / 100.0).round() as u16,
+            Size::Auto | Size::Fill => space_w,
+        };
+        let width = apply_constraints(width, item.style.min_width, item.style.max_width);
+
+        let align = effective_align(style, item);
+        let height = match item.style.height {
+            Size::Fixed(h) => h,
+            Size::Percent(p) => ((cell_height as f32) * p / 100.0).round() as u16,
+            Size::Fill => space_h,
+            Size::Auto if matches!(align, AlignItems::Stretch) => space_h,
+            Size::Auto => intrinsic_size(item, width).1,
+        };
+        let height = apply_constraints(height, item.style.min_height, item.style.max_height);
+
+        let offset_y = match align {
+            AlignItems::FlexEnd => space_h.saturating_sub(height),
+            AlignItems::Center => space_h.saturating_sub(height) / 2,
+            AlignItems::FlexStart | AlignItems::Stretch | AlignItems::Baseline => 0,
+        };
+
+        place_node(
+            item,
+            inner_x + column_starts[area.col] + margin.left,
+            inner_y + row_starts[area.row] + margin.top + offset_y,
+            width,
+            height,
+            containing_block,
+            layouts,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -852,4 +2319,461 @@ mod tests {
         assert_eq!(c.x, 5);
         assert_eq!(c.y, 5);
     }
+
+    #[test]
+    fn test_wrapped_text_height() {
+        let mut root = LayoutNode::new(0);
+        root.style.width = Size::Fixed(10);
+        root.style.flex_direction = FlexDirection::Column;
+
+        let para = LayoutNode::measured(1, TextMeasure::new("the quick brown fox", WrapMode::Word));
+        root.children = vec![para];
+
+        let layouts = calculate_layout(&root, 10, 24);
+
+        // "the quick" / "brown fox"
+        assert_eq!(layouts[&1].width, 10);
+        assert_eq!(layouts[&1].height, 2);
+    }
+
+    #[test]
+    fn test_truncate_measure() {
+        let measure = TextMeasure::new("Hello World", WrapMode::Truncate);
+        assert_eq!(measure.lines(6), vec!["Hello…"]);
+        assert_eq!(measure.measure(6), (6, 1));
+        assert_eq!(measure.min_content_width(), 0);
+    }
+
+    #[test]
+    fn test_flex_shrink_reflows_text() {
+        let mut root = LayoutNode::new(0);
+        root.style.width = Size::Fixed(20);
+        root.style.height = Size::Fixed(10);
+        root.style.align_items = AlignItems::FlexStart;
+
+        let mut label = LayoutNode::text(1, 8, 1);
+        label.style.flex_shrink = 0.0;
+        let para = LayoutNode::measured(2, TextMeasure::new("aaaa bbbb cccc dddd", WrapMode::Word));
+        root.children = vec![label, para];
+
+        let layouts = calculate_layout(&root, 20, 10);
+
+        // The label keeps its width; the paragraph shrinks into the rest and wraps
+        assert_eq!(layouts[&1].width, 8);
+        assert_eq!(layouts[&2].x, 8);
+        assert_eq!(layouts[&2].width, 12);
+        assert_eq!(layouts[&2].height, 2);
+    }
+
+    #[test]
+    fn test_flex_shrink_respects_min_content() {
+        let mut root = LayoutNode::new(0);
+        root.style.width = Size::Fixed(10);
+        root.style.height = Size::Fixed(5);
+
+        let a = LayoutNode::measured(1, TextMeasure::new("abcdefgh", WrapMode::None));
+        let b = LayoutNode::measured(2, TextMeasure::new("abcdefgh", WrapMode::Truncate));
+        root.children = vec![a, b];
+
+        let layouts = calculate_layout(&root, 10, 5);
+
+        // Unwrapped text cannot shrink, so the truncating sibling absorbs the overflow
+        assert_eq!(layouts[&1].width, 8);
+        assert_eq!(layouts[&2].width, 2);
+    }
+
+    #[test]
+    fn test_absolute_uses_nearest_positioned_ancestor() {
+        let mut root = LayoutNode::new(0);
+        root.style.width = Size::Fixed(40);
+        root.style.height = Size::Fixed(20);
+        root.style.padding = Edges::all(2);
+
+        let mut panel = LayoutNode::new(1);
+        panel.style.position = Position::Relative;
+        panel.style.width = Size::Fixed(20);
+        panel.style.height = Size::Fixed(10);
+        panel.style.border_width = 1;
+
+        let mut badge = LayoutNode::text(2, 3, 1);
+        badge.style.position = Position::Absolute;
+        badge.style.position_right = Some(0);
+        badge.style.position_y = Some(0);
+
+        let mut toast = LayoutNode::text(3, 5, 1);
+        toast.style.position = Position::Absolute;
+        toast.style.position_right = Some(1);
+        toast.style.position_bottom = Some(1);
+
+        let flow = LayoutNode::text(4, 4, 1);
+        panel.children = vec![badge, flow];
+        root.children = vec![panel, toast];
+
+        let layouts = calculate_layout(&root, 40, 20);
+
+        // Badge sits in the panel's top-right corner, inside its border
+        assert_eq!((layouts[&2].x, layouts[&2].y), (18, 3));
+        // The out-of-flow badge doesn't displace its sibling
+        assert_eq!((layouts[&4].x, layouts[&4].y), (3, 3));
+        // No positioned ancestor: placed against the screen
+        assert_eq!((layouts[&3].x, layouts[&3].y, layouts[&3].width), (34, 18, 5));
+    }
+
+    #[test]
+    fn test_static_offsets_pin_to_screen_position() {
+        let mut root = LayoutNode::new(0);
+        root.style.padding = Edges::all(2);
+
+        let mut pinned = LayoutNode::new(1);
+        pinned.style.position_x = Some(10);
+        pinned.style.position_y = Some(5);
+        pinned.children = vec![LayoutNode::text(2, 3, 1)];
+        root.children = vec![pinned];
+
+        let layouts = calculate_layout(&root, 30, 10);
+        assert_eq!((layouts[&1].x, layouts[&1].y), (10, 5));
+        assert_eq!((layouts[&2].x, layouts[&2].y), (10, 5));
+
+        let cached = calculate_layout_cached(&root, 30, 10, &mut LayoutCache::new());
+        assert_eq!(cached[&1], layouts[&1]);
+    }
+
+    #[test]
+    fn test_absolute_stretches_between_insets() {
+        let mut root = LayoutNode::new(0);
+        let mut overlay = LayoutNode::new(1);
+        overlay.style.position = Position::Absolute;
+        overlay.style.position_x = Some(2);
+        overlay.style.position_right = Some(2);
+        overlay.style.position_y = Some(1);
+        overlay.style.position_bottom = Some(1);
+        root.children = vec![overlay];
+
+        let layouts = calculate_layout(&root, 30, 10);
+        let o = &layouts[&1];
+        assert_eq!((o.x, o.y, o.width, o.height), (2, 1, 26, 8));
+
+        // Huge insets saturate instead of overflowing
+        let overlay = &mut root.children[0];
+        overlay.style.position_x = Some(u16::MAX);
+        overlay.style.position_y = None;
+        overlay.style.height = Size::Fixed(u16::MAX);
+        let layouts = calculate_layout(&root, 30, 10);
+        assert_eq!((layouts[&1].x, layouts[&1].y), (u16::MAX, 0));
+    }
+
+    #[test]
+    fn test_percent_width_resolved_once() {
+        let mut root = LayoutNode::new(0);
+        root.style.width = Size::Fixed(100);
+        root.style.height = Size::Fixed(10);
+
+        let mut child = LayoutNode::new(1);
+        child.style.width = Size::Percent(50.0);
+        root.children = vec![child];
+
+        let layouts = calculate_layout(&root, 100, 10);
+        assert_eq!(layouts[&1].width, 50);
+    }
+
+    #[test]
+    fn test_auto_container_takes_content_size() {
+        let mut root = LayoutNode::new(0);
+        root.style.flex_direction = FlexDirection::Column;
+
+        let mut card = LayoutNode::new(1);
+        card.style.flex_direction = FlexDirection::Column;
+        card.style.border_width = 1;
+        card.children = vec![LayoutNode::text(2, 5, 1), LayoutNode::text(3, 5, 1)];
+        root.children = vec![card, LayoutNode::text(4, 5, 1)];
+
+        let layouts = calculate_layout(&root, 20, 10);
+        assert_eq!(layouts[&1].height, 4);
+        assert_eq!(layouts[&4].y, 4);
+    }
+
+    // The expected values below are what a browser computes for the same
+    // flexbox, rounded to whole cells.
+
+    fn row_of(width: u16, height: u16, children: Vec<LayoutNode>) -> LayoutNode {
+        let mut root = LayoutNode::new(0);
+        root.style.width = Size::Fixed(width);
+        root.style.height = Size::Fixed(height);
+        root.children = children;
+        root
+    }
+
+    #[test]
+    fn test_css_flex_grow_ratio() {
+        let mut a = LayoutNode::text(1, 10, 1);
+        a.style.flex_grow = 1.0;
+        let mut b = LayoutNode::text(2, 10, 1);
+        b.style.flex_grow = 2.0;
+
+        let layouts = calculate_layout(&row_of(100, 10, vec![a, b]), 100, 10);
+        assert_eq!((layouts[&1].x, layouts[&1].width), (0, 37));
+        assert_eq!((layouts[&2].x, layouts[&2].width), (37, 63));
+    }
+
+    #[test]
+    fn test_css_flex_basis() {
+        let mut a = LayoutNode::text(1, 2, 1);
+        a.style.flex_basis = Size::Fixed(10);
+        a.style.flex_grow = 1.0;
+        let mut b = LayoutNode::text(2, 2, 1);
+        b.style.flex_basis = Size::Fixed(0);
+        b.style.flex_grow = 1.0;
+
+        let layouts = calculate_layout(&row_of(30, 1, vec![a, b]), 30, 1);
+        assert_eq!(layouts[&1].width, 20);
+        assert_eq!(layouts[&2].width, 10);
+    }
+
+    #[test]
+    fn test_css_row_reverse_packs_from_end() {
+        let mut root = row_of(20, 1, vec![LayoutNode::text(1, 5, 1), LayoutNode::text(2, 3, 1)]);
+        root.style.flex_direction = FlexDirection::RowReverse;
+
+        let layouts = calculate_layout(&root, 20, 1);
+        assert_eq!(layouts[&1].x, 15);
+        assert_eq!(layouts[&2].x, 12);
+    }
+
+    #[test]
+    fn test_css_space_around_and_evenly() {
+        let items = || vec![LayoutNode::text(1, 4, 1), LayoutNode::text(2, 4, 1)];
+
+        let mut around = row_of(20, 1, items());
+        around.style.justify_content = JustifyContent::SpaceAround;
+        let layouts = calculate_layout(&around, 20, 1);
+        assert_eq!((layouts[&1].x, layouts[&2].x), (3, 13));
+
+        let mut evenly = row_of(20, 1, items());
+        evenly.style.justify_content = JustifyContent::SpaceEvenly;
+        let layouts = calculate_layout(&evenly, 20, 1);
+        assert_eq!((layouts[&1].x, layouts[&2].x), (4, 12));
+    }
+
+    #[test]
+    fn test_css_margins_offset_siblings() {
+        let mut a = LayoutNode::text(1, 5, 1);
+        a.style.margin = Edges { top: 1, right: 1, bottom: 0, left: 2 };
+        let b = LayoutNode::text(2, 5, 1);
+
+        let mut root = row_of(20, 5, vec![a, b]);
+        root.style.align_items = AlignItems::FlexStart;
+        let layouts = calculate_layout(&root, 20, 5);
+        assert_eq!((layouts[&1].x, layouts[&1].y), (2, 1));
+        assert_eq!((layouts[&2].x, layouts[&2].y), (8, 0));
+    }
+
+    #[test]
+    fn test_css_align_self_and_stretch_limit() {
+        let mut end = LayoutNode::text(1, 2, 2);
+        end.style.align_self = AlignSelf::FlexEnd;
+        let mut capped = LayoutNode::new(2);
+        capped.style.width = Size::Fixed(2);
+        capped.style.max_height = Some(3);
+
+        let layouts = calculate_layout(&row_of(10, 10, vec![end, capped]), 10, 10);
+        assert_eq!(layouts[&1].y, 8);
+        assert_eq!(layouts[&2].height, 3);
+    }
+
+    #[test]
+    fn test_css_wrap_with_gaps_and_align_content() {
+        let items = (1..=3).map(|id| LayoutNode::text(id, 4, 1)).collect();
+        let mut root = row_of(10, 10, items);
+        root.style.flex_wrap = FlexWrap::Wrap;
+        root.style.align_items = AlignItems::FlexStart;
+        root.style.column_gap = Some(1);
+        root.style.row_gap = Some(2);
+        root.style.align_content = AlignContent::Center;
+
+        let layouts = calculate_layout(&root, 10, 10);
+        assert_eq!((layouts[&1].x, layouts[&1].y), (0, 3));
+        assert_eq!((layouts[&2].x, layouts[&2].y), (5, 3));
+        assert_eq!((layouts[&3].x, layouts[&3].y), (0, 6));
+    }
+
+    fn grid_of(width: u16, height: u16, columns: &str, children: Vec<LayoutNode>) -> LayoutNode {
+        let mut root = row_of(width, height, children);
+        root.style.display_mode = Display::Grid;
+        root.style.grid_template_columns = GridTrack::parse_list(columns);
+        root
+    }
+
+    fn rect(layout: &ComputedLayout) -> (u16, u16, u16, u16) {
+        (layout.x, layout.y, layout.width, layout.height)
+    }
+
+    #[test]
+    fn test_grid_track_list_parsing() {
+        assert_eq!(
+            GridTrack::parse_list("10 1fr 2.5fr auto 25%"),
+            vec![
+                GridTrack::Fixed(10),
+                GridTrack::Fr(1.0),
+                GridTrack::Fr(2.5),
+                GridTrack::Auto,
+                GridTrack::Percent(25.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grid_fixed_and_fr_columns() {
+        let items = (1..=3).map(|id| LayoutNode::text(id, 1, 1)).collect();
+        let mut root = grid_of(40, 3, "10 1fr 2fr", items);
+        root.style.column_gap = Some(1);
+
+        let layouts = calculate_layout(&root, 40, 3);
+        assert_eq!(rect(&layouts[&1]), (0, 0, 10, 3));
+        assert_eq!(rect(&layouts[&2]), (11, 0, 9, 3));
+        assert_eq!(rect(&layouts[&3]), (21, 0, 19, 3));
+    }
+
+    #[test]
+    fn test_grid_fr_never_shrinks_below_content() {
+        let items = vec![LayoutNode::text(1, 12, 1), LayoutNode::text(2, 1, 1)];
+        let layouts = calculate_layout(&grid_of(20, 1, "1fr 1fr", items), 20, 1);
+        assert_eq!(layouts[&1].width, 12);
+        assert_eq!((layouts[&2].x, layouts[&2].width), (12, 8));
+    }
+
+    #[test]
+    fn test_grid_percent_and_auto_columns() {
+        let items = vec![LayoutNode::text(1, 3, 1), LayoutNode::text(2, 3, 1)];
+        let layouts = calculate_layout(&grid_of(20, 1, "25% auto", items), 20, 1);
+        assert_eq!(layouts[&1].width, 5);
+        // Without fr tracks, auto tracks take the remaining space
+        assert_eq!((layouts[&2].x, layouts[&2].width), (5, 15));
+    }
+
+    #[test]
+    fn test_grid_auto_placement_and_spans() {
+        let mut wide = LayoutNode::text(1, 1, 1);
+        wide.style.grid_column = GridPlacement::span(2);
+        let items = vec![wide, LayoutNode::text(2, 1, 1), LayoutNode::text(3, 1, 1)];
+        let mut root = grid_of(30, 4, "1fr 1fr 1fr", items);
+        root.style.grid_template_rows = GridTrack::parse_list("1 1");
+
+        let layouts = calculate_layout(&root, 30, 4);
+        assert_eq!(rect(&layouts[&1]), (0, 0, 20, 1));
+        assert_eq!(rect(&layouts[&2]), (20, 0, 10, 1));
+        assert_eq!(rect(&layouts[&3]), (0, 1, 10, 1));
+    }
+
+    #[test]
+    fn test_grid_explicit_lines_skip_taken_cells() {
+        let mut pinned = LayoutNode::text(1, 1, 1);
+        pinned.style.grid_column = GridPlacement::line(1);
+        pinned.style.grid_row = GridPlacement::line(1);
+        let mut second_column = LayoutNode::text(2, 1, 1);
+        second_column.style.grid_column = GridPlacement::line(2);
+        let items = vec![pinned, second_column, LayoutNode::text(3, 1, 1)];
+        let mut root = grid_of(20, 10, "1fr 1fr", items);
+        root.style.grid_auto_rows = GridTrack::Fixed(2);
+
+        let layouts = calculate_layout(&root, 20, 10);
+        assert_eq!(rect(&layouts[&1]), (0, 0, 10, 2));
+        assert_eq!(rect(&layouts[&2]), (10, 0, 10, 2));
+        assert_eq!(rect(&layouts[&3]), (0, 2, 10, 2));
+    }
+
+    #[test]
+    fn test_grid_named_areas() {
+        let area = |id: u64, name: &str| {
+            let mut node = LayoutNode::text(id, 1, 1);
+            node.style.grid_area = Some(name.to_string());
+            node
+        };
+        let items = vec![area(1, "main"), area(2, "header"), area(3, "side")];
+        let mut root = grid_of(30, 10, "8 1fr", items);
+        root.style.grid_template_rows = GridTrack::parse_list("1 1fr");
+        root.style.grid_template_areas = vec!["header header".into(), "side main".into()];
+
+        let layouts = calculate_layout(&root, 30, 10);
+        assert_eq!(rect(&layouts[&2]), (0, 0, 30, 1));
+        assert_eq!(rect(&layouts[&3]), (0, 1, 8, 9));
+        assert_eq!(rect(&layouts[&1]), (8, 1, 22, 9));
+    }
+
+    #[test]
+    fn test_grid_auto_rows_fit_wrapped_content() {
+        let paragraph = LayoutNode::measured(1, TextMeasure::new("one two three", WrapMode::Word));
+        let mut root = grid_of(20, 10, "8 1fr", vec![paragraph, LayoutNode::text(2, 1, 1), LayoutNode::text(3, 1, 1)]);
+        root.style.row_gap = Some(1);
+        root.style.align_items = AlignItems::FlexStart;
+        root.style.height = Size::Auto;
+
+        let mut outer = LayoutNode::new(9);
+        outer.style.flex_direction = FlexDirection::Column;
+        outer.children = vec![root];
+
+        let layouts = calculate_layout(&outer, 20, 10);
+        assert_eq!(rect(&layouts[&1]), (0, 0, 8, 2));
+        assert_eq!(layouts[&2].height, 1);
+        assert_eq!(layouts[&3].y, 3);
+        assert_eq!(layouts[&0].height, 4);
+    }
+
+    fn dashboard(header: &str, spinner: &str) -> LayoutNode {
+        let mut table = LayoutNode::new(3);
+        table.style.flex_direction = FlexDirection::Column;
+        table.style.flex_shrink = 0.0;
+        table.children = (0..500)
+            .map(|i| LayoutNode::measured(1000 + i, TextMeasure::new(format!("row {i} of the table"), WrapMode::Word)))
+            .collect();
+
+        let mut root = LayoutNode::new(0);
+        root.style.flex_direction = FlexDirection::Column;
+        root.style.width = Size::Fixed(12);
+        root.children = vec![
+            LayoutNode::measured(1, TextMeasure::new(header, WrapMode::Word)),
+            LayoutNode::measured(2, TextMeasure::new(spinner, WrapMode::None)),
+            table,
+        ];
+        root
+    }
+
+    #[test]
+    fn test_cache_reuses_unchanged_subtrees() {
+        let mut cache = LayoutCache::new();
+
+        let first = dashboard("Title", "⠋");
+        assert_eq!(calculate_layout_cached(&first, 12, 40, &mut cache), calculate_layout(&first, 12, 40));
+        assert_eq!(cache.hits(), 0);
+
+        // Only the spinner changed: the table is reused as a whole
+        let second = dashboard("Title", "⠙");
+        assert_eq!(calculate_layout_cached(&second, 12, 40, &mut cache), calculate_layout(&second, 12, 40));
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.misses(), 1);
+    }
+
+    #[test]
+    fn test_cache_shifts_moved_subtrees() {
+        let mut cache = LayoutCache::new();
+        calculate_layout_cached(&dashboard("Title", "⠋"), 12, 40, &mut cache);
+
+        // A taller header pushes the unchanged table down
+        let moved = dashboard("A much longer title", "⠋");
+        let cached = calculate_layout_cached(&moved, 12, 40, &mut cache);
+        assert_eq!(cached, calculate_layout(&moved, 12, 40));
+        assert_eq!(cached[&3].y, 3);
+        assert_eq!(cache.hits(), 1);
+    }
+
+    #[test]
+    fn test_cache_relays_out_on_resize() {
+        let mut cache = LayoutCache::new();
+        let node = dashboard("Title", "⠋");
+        calculate_layout_cached(&node, 12, 40, &mut cache);
+
+        let mut narrow = node.clone();
+        narrow.style.width = Size::Fixed(8);
+        assert_eq!(calculate_layout_cached(&narrow, 8, 40, &mut cache), calculate_layout(&narrow, 8, 40));
+        assert_eq!(cache.hits(), 0);
+    }
 }
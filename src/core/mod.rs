@@ -10,6 +10,11 @@
 //! - **Focus**: Focus management and navigation
 //! - **Tick**: Global animation tick system
 //! - **Component**: Component trait and types
+//! - **Responsive**: Breakpoints on the terminal size
+//! - **Terminfo**: Compiled terminfo database reader
+//! - **Queries**: Asking the terminal about its features
+//! - **Export**: HTML and SVG screenshots of rendered output
+//! - **Graphics**: Inline images and image decoding
 
 pub mod signals;
 pub mod layout;
@@ -23,11 +28,15 @@ pub mod component;
 pub mod hotkeys;
 pub mod animation;
 pub mod capabilities;
+pub mod terminfo;
+pub mod queries;
 pub mod graphics;
 pub mod virtual_scroll;
 pub mod key_bindings;
 pub mod command_palette;
 pub mod screen;
+pub mod responsive;
+pub mod export;
 
 // Re-exports for convenience
 pub use signals::*;
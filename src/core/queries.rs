@@ -0,0 +1,488 @@
+//! Terminal Queries
+//!
+//! Asks the terminal about itself instead of trusting environment
+//! variables, which are often missing over SSH. Queries are written at
+//! start-up and the replies arrive later on stdin, where the input parser
+//! turns them into [`TerminalEvent::Reply`] instead of key presses:
+//!
+//! - **DA1** (`CSI c`): device attributes, e.g. Sixel support
+//! - **XTVERSION** (`CSI > q`): terminal name and version
+//! - **DECRQM** (`CSI ? Ps $ p`): whether a private mode is supported
+//! - **CPR** (`CSI 6 n`): cursor position
+//! - **OSC 10 / 11**: foreground and background colors
+//! - **Kitty keyboard** (`CSI ? u`): whether the Kitty keyboard protocol is
+//!   implemented, and its current flags
+//!
+//! Every terminal answers DA1, so it is sent last: once its reply arrives,
+//! queries that haven't been answered never will be.
+//!
+//! [`TerminalEvent::Reply`]: crate::core::terminal::TerminalEvent::Reply
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::core::terminal::{read_event, Terminal, TerminalEvent};
+
+/// How long to wait for replies before giving up.
+pub const QUERY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long queries settled by DA1 stay outstanding if its reply never
+/// comes, after which `ESC P` and `ESC ]` are keys again.
+pub const REPLY_EXPIRY: Duration = Duration::from_secs(30);
+
+/// Private modes asked about with DECRQM: synchronized output, bracketed
+/// paste and SGR mouse reporting.
+pub const QUERIED_MODES: [u16; 3] = [2026, 2004, 1006];
+
+// =============================================================================
+// Queries and Replies
+// =============================================================================
+
+/// A question the terminal can answer on stdin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Query {
+    /// Primary device attributes (DA1)
+    DeviceAttributes,
+    /// Terminal name and version (XTVERSION)
+    Version,
+    /// Private mode support (DECRQM)
+    Mode(u16),
+    /// Cursor position report (CPR)
+    CursorPosition,
+    /// Default foreground color (OSC 10)
+    Foreground,
+    /// Default background color (OSC 11)
+    Background,
+    /// Kitty keyboard protocol flags (`CSI ? u`)
+    KeyboardFlags,
+}
+
+impl Query {
+    /// Escape sequence that asks this query.
+    pub fn sequence(&self) -> String {
+        match self {
+            Query::DeviceAttributes => "\x1B[c".to_string(),
+            Query::Version => "\x1B[>0q".to_string(),
+            Query::Mode(mode) => format!("\x1B[?{mode}$p"),
+            Query::CursorPosition => "\x1B[6n".to_string(),
+            Query::Foreground => "\x1B]10;?\x1B\\".to_string(),
+            Query::Background => "\x1B]11;?\x1B\\".to_string(),
+            Query::KeyboardFlags => "\x1B[?u".to_string(),
+        }
+    }
+}
+
+/// State of a mode as reported by DECRPM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeState {
+    NotRecognized,
+    Set,
+    Reset,
+    PermanentlySet,
+    PermanentlyReset,
+}
+
+impl ModeState {
+    /// Whether the terminal implements the mode at all.
+    pub fn is_supported(&self) -> bool {
+        *self != ModeState::NotRecognized
+    }
+}
+
+/// A parsed reply to a [`Query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryReply {
+    /// DA1 attributes, e.g. `[62, 4, 22]` (4 = Sixel)
+    DeviceAttributes(Vec<u16>),
+    /// XTVERSION text, e.g. `"kitty(0.35.2)"`
+    Version(String),
+    /// DECRPM answer for a mode
+    Mode { mode: u16, state: ModeState },
+    /// Cursor position, 1-based
+    CursorPosition { row: u16, column: u16 },
+    /// Default foreground color
+    Foreground(u8, u8, u8),
+    /// Default background color
+    Background(u8, u8, u8),
+    /// Kitty keyboard protocol flags in effect
+    KeyboardFlags(u8),
+}
+
+// =============================================================================
+// Reply Parsing
+// =============================================================================
+
+fn numbers(params: &[u8]) -> Vec<u16> {
+    std::str::from_utf8(params)
+        .unwrap_or("")
+        .split(';')
+        .filter_map(|n| n.parse().ok())
+        .collect()
+}
+
+/// Parse a CSI reply from its parameter bytes and final byte.
+///
+/// Cursor position reports look like modified F3 presses
+/// (`CSI 1 ; 2 R`), so they are only recognized while a CPR query is
+/// outstanding.
+pub fn parse_csi_reply(params: &[u8], final_byte: u8) -> Option<QueryReply> {
+    match final_byte {
+        b'c' if params.first() == Some(&b'?') => Some(QueryReply::DeviceAttributes(numbers(&params[1..]))),
+        b'y' if params.last() == Some(&b'$') => {
+            let params = params.strip_prefix(b"?").unwrap_or(params);
+            let values = numbers(&params[..params.len() - 1]);
+            let state = match values.get(1)? {
+                1 => ModeState::Set,
+                2 => ModeState::Reset,
+                3 => ModeState::PermanentlySet,
+                4 => ModeState::PermanentlyReset,
+                _ => ModeState::NotRecognized,
+            };
+            Some(QueryReply::Mode { mode: *values.first()?, state })
+        }
+        b'u' if params.first() == Some(&b'?') => {
+            let flags = numbers(&params[1..]).first().copied().unwrap_or(0);
+            Some(QueryReply::KeyboardFlags(u8::try_from(flags).unwrap_or(u8::MAX)))
+        }
+        b'R' if is_awaiting(Query::CursorPosition) => match numbers(params)[..] {
+            [row, column] => Some(QueryReply::CursorPosition { row, column }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parse the body of a DCS (`P`) or OSC (`]`) reply, without the
+/// introducer and terminator.
+pub fn parse_string_reply(kind: u8, body: &[u8]) -> Option<QueryReply> {
+    let body = std::str::from_utf8(body).ok()?;
+    match kind {
+        b'P' => body.strip_prefix(">|").map(|version| QueryReply::Version(version.to_string())),
+        b']' => {
+            let (code, color) = body.split_once(';')?;
+            let (r, g, b) = parse_color_spec(color)?;
+            match code {
+                "10" => Some(QueryReply::Foreground(r, g, b)),
+                "11" => Some(QueryReply::Background(r, g, b)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Parse an X11 color spec: `rgb:R/G/B` with 1–4 hex digits per
+/// channel (or `rgba:R/G/B/A`), or `#RRGGBB`.
+pub fn parse_color_spec(spec: &str) -> Option<(u8, u8, u8)> {
+    let channel = |hex: &str| {
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len().clamp(1, 4))) - 1;
+        u8::try_from((value * 255 + max / 2) / max).ok()
+    };
+
+    let channels: Vec<&str> = if let Some(rest) = spec.strip_prefix("rgb:").or_else(|| spec.strip_prefix("rgba:")) {
+        rest.split('/').take(3).collect()
+    } else {
+        let hex = spec.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        vec![&hex[0..2], &hex[2..4], &hex[4..6]]
+    };
+
+    match channels[..] {
+        [r, g, b] if [r, g, b].iter().all(|c| (1..=4).contains(&c.len())) => Some((channel(r)?, channel(g)?, channel(b)?)),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// Results
+// =============================================================================
+
+/// What the terminal has told us so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryResults {
+    /// DA1 attributes
+    pub device_attributes: Option<Vec<u16>>,
+    /// XTVERSION text
+    pub version: Option<String>,
+    /// DECRPM answers by mode number
+    pub modes: HashMap<u16, ModeState>,
+    /// Cursor position (row, column), 1-based
+    pub cursor_position: Option<(u16, u16)>,
+    /// Default foreground color
+    pub foreground: Option<(u8, u8, u8)>,
+    /// Default background color
+    pub background: Option<(u8, u8, u8)>,
+    /// Kitty keyboard protocol flags, if the protocol is implemented
+    pub keyboard_flags: Option<u8>,
+}
+
+impl QueryResults {
+    /// Record a reply.
+    pub fn apply(&mut self, reply: QueryReply) {
+        match reply {
+            QueryReply::DeviceAttributes(attributes) => self.device_attributes = Some(attributes),
+            QueryReply::Version(version) => self.version = Some(version),
+            QueryReply::Mode { mode, state } => {
+                self.modes.insert(mode, state);
+            }
+            QueryReply::CursorPosition { row, column } => self.cursor_position = Some((row, column)),
+            QueryReply::Foreground(r, g, b) => self.foreground = Some((r, g, b)),
+            QueryReply::Background(r, g, b) => self.background = Some((r, g, b)),
+            QueryReply::KeyboardFlags(flags) => self.keyboard_flags = Some(flags),
+        }
+    }
+
+    /// Whether DA1 reports an attribute (e.g. 4 for Sixel).
+    pub fn has_attribute(&self, attribute: u16) -> bool {
+        self.device_attributes
+            .as_ref()
+            .is_some_and(|attributes| attributes.contains(&attribute))
+    }
+
+    /// DECRPM answer for a mode, `None` if it wasn't answered.
+    pub fn mode(&self, mode: u16) -> Option<ModeState> {
+        self.modes.get(&mode).copied()
+    }
+
+    /// Whether the background is dark, judged by its perceived brightness.
+    pub fn dark_background(&self) -> Option<bool> {
+        self.background.map(|(r, g, b)| {
+            let luma = 299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b);
+            luma < 128 * 1000
+        })
+    }
+}
+
+// =============================================================================
+// Query State
+// =============================================================================
+
+#[derive(Debug, Default)]
+struct QueryState {
+    /// Queries sent and not yet answered
+    awaiting: Vec<Query>,
+    deadline: Option<Instant>,
+    results: QueryResults,
+}
+
+thread_local! {
+    static STATE: RefCell<QueryState> = RefCell::new(QueryState::default());
+}
+
+fn with_state<R>(f: impl FnOnce(&mut QueryState) -> R) -> R {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            state.awaiting.clear();
+            state.deadline = None;
+        }
+        f(&mut state)
+    })
+}
+
+/// Record queries as sent, expecting replies until `timeout` passes.
+///
+/// Queries ending in [`Query::DeviceAttributes`] stay outstanding until its
+/// reply settles them, however slow the terminal is, so late replies are
+/// never read as keys; only [`REPLY_EXPIRY`] gives up on them.
+pub fn expect_replies(queries: &[Query], timeout: Duration) {
+    let timeout = if queries.contains(&Query::DeviceAttributes) { timeout.max(REPLY_EXPIRY) } else { timeout };
+    with_state(|state| {
+        state.awaiting.extend_from_slice(queries);
+        state.deadline = Some(Instant::now() + timeout);
+    });
+}
+
+/// Whether a reply to `query` may still arrive.
+pub fn is_awaiting(query: Query) -> bool {
+    with_state(|state| state.awaiting.contains(&query))
+}
+
+/// Whether any reply may still arrive.
+pub fn is_pending() -> bool {
+    with_state(|state| !state.awaiting.is_empty())
+}
+
+/// Whether a DCS or OSC reply may still arrive. While one may, `ESC P`
+/// and `ESC ]` start a reply rather than Alt+P / Alt+].
+pub fn is_awaiting_string_reply() -> bool {
+    with_state(|state| {
+        state
+            .awaiting
+            .iter()
+            .any(|query| matches!(query, Query::Version | Query::Foreground | Query::Background))
+    })
+}
+
+/// Record a reply. Returns `true` once all outstanding queries are settled.
+pub fn handle_reply(reply: QueryReply) -> bool {
+    with_state(|state| {
+        let answered = match &reply {
+            QueryReply::DeviceAttributes(_) => Query::DeviceAttributes,
+            QueryReply::Version(_) => Query::Version,
+            QueryReply::Mode { mode, .. } => Query::Mode(*mode),
+            QueryReply::CursorPosition { .. } => Query::CursorPosition,
+            QueryReply::Foreground(..) => Query::Foreground,
+            QueryReply::Background(..) => Query::Background,
+            QueryReply::KeyboardFlags(_) => Query::KeyboardFlags,
+        };
+        state.awaiting.retain(|query| *query != answered);
+        // Replies come in order, so nothing sent before DA1 is still coming
+        if answered == Query::DeviceAttributes {
+            state.awaiting.clear();
+        }
+        state.results.apply(reply);
+        state.awaiting.is_empty()
+    })
+}
+
+/// Everything the terminal has answered so far.
+pub fn query_results() -> QueryResults {
+    with_state(|state| state.results.clone())
+}
+
+/// Forget outstanding queries and results.
+pub fn reset_queries() {
+    with_state(|state| *state = QueryState::default());
+}
+
+// =============================================================================
+// Sending
+// =============================================================================
+
+/// The start-up queries, DA1 last.
+pub fn startup_queries() -> Vec<Query> {
+    let mut queries = vec![Query::Version];
+    queries.extend(QUERIED_MODES.iter().map(|&mode| Query::Mode(mode)));
+    queries.extend([
+        Query::KeyboardFlags,
+        Query::CursorPosition,
+        Query::Foreground,
+        Query::Background,
+        Query::DeviceAttributes,
+    ]);
+    queries
+}
+
+/// Send the start-up queries without waiting for replies.
+///
+/// Replies arrive as [`TerminalEvent::Reply`] events; pass them to
+/// [`handle_reply`].
+pub fn send_queries(terminal: &mut Terminal, timeout: Duration) -> io::Result<()> {
+    ask(terminal, &startup_queries(), timeout)
+}
+
+/// Send queries without waiting for replies; end them with
+/// [`Query::DeviceAttributes`] so unanswered ones are known to be settled.
+pub fn ask(terminal: &mut Terminal, queries: &[Query], timeout: Duration) -> io::Result<()> {
+    let sequences: String = queries.iter().map(Query::sequence).collect();
+    expect_replies(queries, timeout);
+    terminal.write(&sequences)?;
+    terminal.flush()
+}
+
+/// Send the start-up queries and wait for the replies or `timeout`.
+///
+/// Requires raw mode. Other input read while waiting is kept for the
+/// next [`read_event`] call.
+pub fn query_terminal(terminal: &mut Terminal, timeout: Duration) -> io::Result<QueryResults> {
+    send_queries(terminal, timeout)?;
+
+    let deadline = Instant::now() + timeout;
+    let mut deferred = Vec::new();
+    while is_pending() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match read_event(Some(remaining))? {
+            Some(TerminalEvent::Reply(reply)) => {
+                handle_reply(reply);
+            }
+            Some(event) => deferred.push(event),
+            None => {}
+        }
+    }
+
+    crate::core::terminal::push_events(deferred);
+    Ok(query_results())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csi_replies() {
+        assert_eq!(
+            parse_csi_reply(b"?62;4;22", b'c'),
+            Some(QueryReply::DeviceAttributes(vec![62, 4, 22]))
+        );
+        assert_eq!(
+            parse_csi_reply(b"?2026;2$", b'y'),
+            Some(QueryReply::Mode { mode: 2026, state: ModeState::Reset })
+        );
+        assert_eq!(
+            parse_csi_reply(b"?2004;0$", b'y'),
+            Some(QueryReply::Mode { mode: 2004, state: ModeState::NotRecognized })
+        );
+        assert_eq!(parse_csi_reply(b"?15", b'u'), Some(QueryReply::KeyboardFlags(15)));
+        // Plain keys are not replies
+        assert_eq!(parse_csi_reply(b"1;5", b'A'), None);
+        assert_eq!(parse_csi_reply(b"97;5", b'u'), None);
+        assert_eq!(parse_csi_reply(b"62", b'c'), None);
+    }
+
+    #[test]
+    fn test_parse_string_replies() {
+        assert_eq!(
+            parse_string_reply(b'P', b">|kitty(0.35.2)"),
+            Some(QueryReply::Version("kitty(0.35.2)".to_string()))
+        );
+        assert_eq!(
+            parse_string_reply(b']', b"11;rgb:1e1e/1e1e/2e2e"),
+            Some(QueryReply::Background(30, 30, 46))
+        );
+        assert_eq!(parse_string_reply(b']', b"10;rgb:f/f/f"), Some(QueryReply::Foreground(255, 255, 255)));
+        assert_eq!(parse_string_reply(b']', b"11;#fdf6e3"), Some(QueryReply::Background(253, 246, 227)));
+        assert_eq!(parse_string_reply(b']', b"11;rgb:zz/00/00"), None);
+        assert_eq!(parse_string_reply(b']', b"52;c;aGVsbG8="), None);
+    }
+
+    #[test]
+    fn test_results_and_pending_state() {
+        reset_queries();
+        expect_replies(&startup_queries(), Duration::from_secs(5));
+        assert!(is_awaiting(Query::CursorPosition));
+        assert!(is_awaiting_string_reply());
+
+        let cpr = parse_csi_reply(b"12;40", b'R').unwrap();
+        assert!(!handle_reply(cpr));
+        assert!(!handle_reply(QueryReply::Background(250, 250, 250)));
+        assert!(!is_awaiting(Query::CursorPosition));
+        // Once nothing is awaited, CPR-shaped input is a key again
+        assert!(handle_reply(QueryReply::DeviceAttributes(vec![62, 4])));
+        assert!(!is_pending());
+        assert_eq!(parse_csi_reply(b"1;2", b'R'), None);
+
+        let results = query_results();
+        assert_eq!(results.cursor_position, Some((12, 40)));
+        assert_eq!(results.dark_background(), Some(false));
+        assert!(results.has_attribute(4));
+        assert_eq!(results.version, None);
+        reset_queries();
+    }
+
+    #[test]
+    fn test_queries_time_out() {
+        reset_queries();
+        expect_replies(&[Query::Version], Duration::ZERO);
+        assert!(!is_awaiting_string_reply());
+        reset_queries();
+    }
+}
@@ -3,9 +3,22 @@
 //! Converts the component tree into terminal output with ANSI escape codes.
 //! Handles double-buffering, diffing, and efficient updates.
 
-use std::collections::HashMap;
-use crate::core::layout::{ComputedLayout, LayoutNode, calculate_layout};
-use crate::core::component::{VNode, Color, NamedColor, BorderStyle, TextStyle};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use crate::core::layout::{
+    ComputedLayout, Edges, LayoutCache, LayoutNode, Overflow, Position, Size, TextMeasure, calculate_layout_cached,
+};
+use crate::core::component::{
+    Backdrop, BorderStyle, BorderTitle, BoxNode, BoxStyle, Color, CursorShape, Gradient, GradientAxis, ImageNode, NamedColor, TextNode, TextStyle, TitleAlign,
+    TitlePosition, UnderlineStyle, VNode,
+};
+use crate::core::capabilities::ColorSupport;
+use crate::core::graphics::{self, GraphicsProtocol, KittyImages};
+use crate::utils::glyphs::ascii_fallback;
+use crate::utils::sanitize::control_picture;
+use crate::utils::text::{char_width, truncate_text, visible_width};
+use crate::core::terminal::{MouseEvent, MouseEventKind};
 
 // =============================================================================
 // Render Context
@@ -24,12 +37,12 @@ pub struct RenderContext {
     pub cursor_y: u16,
     /// Whether to use colors
     pub use_colors: bool,
-    /// Whether to use Unicode
+    /// Whether to use Unicode; borders and glyphs fall back to ASCII otherwise
     pub use_unicode: bool,
 }
 
 impl RenderContext {
-    /// Create a new render context.
+    /// Create a new render context for the current render mode.
     pub fn new(width: u16, height: u16) -> Self {
         Self {
             width,
@@ -37,7 +50,7 @@ impl RenderContext {
             cursor_x: 0,
             cursor_y: 0,
             use_colors: true,
-            use_unicode: true,
+            use_unicode: crate::core::capabilities::use_unicode(),
         }
     }
 }
@@ -46,6 +59,9 @@ impl RenderContext {
 // Output Buffer
 // =============================================================================
 
+/// Character held by the cell under the right half of a wide character.
+const WIDE_CONTINUATION: char = '\0';
+
 /// A cell in the output buffer.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Cell {
@@ -60,8 +76,13 @@ pub struct Cell {
     pub dim: bool,
     pub italic: bool,
     pub underline: bool,
+    pub underline_style: UnderlineStyle,
+    /// Underline color (`Default` follows the text color)
+    pub underline_color: Color,
     pub inverse: bool,
     pub strikethrough: bool,
+    /// URL of the hyperlink this cell belongs to
+    pub hyperlink: Option<Rc<str>>,
 }
 
 impl Default for Cell {
@@ -74,8 +95,11 @@ impl Default for Cell {
             dim: false,
             italic: false,
             underline: false,
+            underline_style: UnderlineStyle::Single,
+            underline_color: Color::Default,
             inverse: false,
             strikethrough: false,
+            hyperlink: None,
         }
     }
 }
@@ -89,10 +113,34 @@ impl Cell {
         }
     }
 
+    /// Whether this cell is covered by the wide character to its left.
+    pub fn is_continuation(&self) -> bool {
+        self.char == WIDE_CONTINUATION
+    }
+
+    /// Cell for the right half of this cell's wide character.
+    pub(crate) fn continuation(&self) -> Self {
+        Self { char: WIDE_CONTINUATION, ..self.clone() }
+    }
+
     /// Check if this cell differs from another (needs redraw).
     pub fn differs_from(&self, other: &Cell) -> bool {
         self != other
     }
+
+    /// Whether two cells are drawn with the same SGR attributes.
+    pub(crate) fn same_style(&self, other: &Cell) -> bool {
+        self.fg == other.fg
+            && self.bg == other.bg
+            && self.bold == other.bold
+            && self.dim == other.dim
+            && self.italic == other.italic
+            && self.underline == other.underline
+            && (!self.underline
+                || (self.underline_style == other.underline_style && self.underline_color == other.underline_color))
+            && self.inverse == other.inverse
+            && self.strikethrough == other.strikethrough
+    }
 }
 
 /// Output buffer for double-buffering.
@@ -104,6 +152,39 @@ pub struct OutputBuffer {
     height: u16,
     /// Cell data
     cells: Vec<Cell>,
+    /// Writes outside this rectangle are discarded
+    clip: Option<ComputedLayout>,
+    /// Draw with Unicode; otherwise text and borders are mapped to ASCII
+    unicode: bool,
+    /// Where the terminal cursor should be shown, if anywhere
+    cursor: Option<CursorPlacement>,
+    /// Images drawn with a pixel protocol over blank cells
+    graphics: Vec<GraphicPlacement>,
+}
+
+/// Image escape sequence drawn over a block of cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphicPlacement {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    /// Kitty, iTerm2 or Sixel sequence; for Kitty it only transmits the
+    /// image, which is then placed by ID
+    pub sequence: Rc<str>,
+    /// Kitty image ID
+    pub kitty_id: Option<u32>,
+}
+
+/// Terminal cursor position and look requested by a focused input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorPlacement {
+    /// Column (0-indexed)
+    pub x: u16,
+    /// Row (0-indexed)
+    pub y: u16,
+    pub shape: CursorShape,
+    pub blinking: bool,
 }
 
 impl OutputBuffer {
@@ -114,7 +195,66 @@ impl OutputBuffer {
             width,
             height,
             cells: vec![Cell::default(); size],
+            clip: None,
+            unicode: crate::core::capabilities::use_unicode(),
+            cursor: None,
+            graphics: Vec::new(),
+        }
+    }
+
+    /// Create a buffer sized and configured by a render context.
+    pub fn for_context(context: &RenderContext) -> Self {
+        let mut buffer = Self::new(context.width, context.height);
+        buffer.set_unicode(context.use_unicode);
+        buffer
+    }
+
+    /// Whether this buffer draws with Unicode.
+    pub fn unicode(&self) -> bool {
+        self.unicode
+    }
+
+    /// Draw with Unicode, or map text and borders to ASCII.
+    pub fn set_unicode(&mut self, unicode: bool) {
+        self.unicode = unicode;
+    }
+
+    /// Where the terminal cursor should be shown after drawing this buffer.
+    pub fn cursor(&self) -> Option<CursorPlacement> {
+        self.cursor
+    }
+
+    /// Request the terminal cursor at a cell; ignored outside the buffer
+    /// or the clip rectangle, so hidden inputs don't show a cursor.
+    pub fn set_cursor(&mut self, cursor: Option<CursorPlacement>) {
+        self.cursor = match cursor {
+            Some(c) if c.x >= self.width || c.y >= self.height || !self.in_clip(c.x, c.y) => return,
+            cursor => cursor,
+        };
+    }
+
+    /// Images placed with a pixel protocol.
+    pub fn graphics(&self) -> &[GraphicPlacement] {
+        &self.graphics
+    }
+
+    /// Place an image over blank cells. Terminals can't crop protocol
+    /// images, so this fails unless the whole block is visible.
+    pub fn place_graphic(&mut self, graphic: GraphicPlacement) -> bool {
+        let (right, bottom) = (graphic.x as u32 + graphic.width as u32, graphic.y as u32 + graphic.height as u32);
+        let visible = graphic.width > 0
+            && graphic.height > 0
+            && right <= self.width as u32
+            && bottom <= self.height as u32
+            && self.in_clip(graphic.x, graphic.y)
+            && self.in_clip((right - 1) as u16, (bottom - 1) as u16);
+        if !visible {
+            return false;
         }
+        self.fill_rect(graphic.x, graphic.y, graphic.width, graphic.height, Cell::default());
+        self.graphics.retain(|other| (other.x, other.y) != (graphic.x, graphic.y));
+        self.graphics.push(graphic);
+        true
     }
 
     /// Get buffer dimensions.
@@ -132,9 +272,23 @@ impl OutputBuffer {
         }
     }
 
-    /// Get a mutable cell at position.
+    /// Current clip rectangle.
+    pub fn clip(&self) -> Option<ComputedLayout> {
+        self.clip
+    }
+
+    /// Set the clip rectangle, returning the previous one.
+    pub fn set_clip(&mut self, clip: Option<ComputedLayout>) -> Option<ComputedLayout> {
+        std::mem::replace(&mut self.clip, clip)
+    }
+
+    fn in_clip(&self, x: u16, y: u16) -> bool {
+        self.clip.map_or(true, |clip| clip.contains(x, y))
+    }
+
+    /// Get a mutable cell at position (`None` outside the clip rectangle).
     pub fn get_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell> {
-        if x < self.width && y < self.height {
+        if x < self.width && y < self.height && self.in_clip(x, y) {
             let idx = (y as usize) * (self.width as usize) + (x as usize);
             self.cells.get_mut(idx)
         } else {
@@ -144,7 +298,7 @@ impl OutputBuffer {
 
     /// Set a cell at position.
     pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
-        if x < self.width && y < self.height {
+        if x < self.width && y < self.height && self.in_clip(x, y) {
             let idx = (y as usize) * (self.width as usize) + (x as usize);
             if idx < self.cells.len() {
                 self.cells[idx] = cell;
@@ -160,14 +314,33 @@ impl OutputBuffer {
     }
 
     /// Write a string at position.
+    ///
+    /// Wide characters take two cells, the second holding a continuation
+    /// marker; one that can't be drawn whole is replaced by spaces. Control
+    /// characters are written as visible placeholders, so text can never
+    /// smuggle escape sequences into the output.
     pub fn write_str(&mut self, x: u16, y: u16, s: &str, style: &TextStyle) {
         let mut curr_x = x;
+        let unicode = self.unicode;
+        let hyperlink: Option<Rc<str>> = style.hyperlink.as_deref().map(Rc::from);
         for c in s.chars() {
             if curr_x >= self.width {
                 break;
             }
-            if let Some(cell) = self.get_mut(curr_x, y) {
-                cell.char = c;
+            let c = control_picture(c).unwrap_or(c);
+            let c = if unicode { c } else { ascii_fallback(c) };
+            let width = char_width(c);
+            let glyphs = match width {
+                0 => continue,
+                1 => [Some(c), None],
+                _ if self.writable(curr_x, y) && self.writable(curr_x + 1, y) => [Some(c), Some(WIDE_CONTINUATION)],
+                _ => [Some(' '), Some(' ')],
+            };
+            for (glyph, gx) in glyphs.into_iter().zip(curr_x..) {
+                let (Some(glyph), Some(cell)) = (glyph, self.get_mut(gx, y)) else {
+                    continue;
+                };
+                cell.char = glyph;
                 if let Some(color) = style.color {
                     cell.fg = color;
                 }
@@ -178,11 +351,36 @@ impl OutputBuffer {
                 cell.dim = style.dim;
                 cell.italic = style.italic;
                 cell.underline = style.underline;
+                cell.underline_style = style.underline_style.unwrap_or_default();
+                cell.underline_color = style.underline_color.unwrap_or_default();
                 cell.inverse = style.inverse;
                 cell.strikethrough = style.strikethrough;
+                cell.hyperlink = hyperlink.clone();
             }
-            curr_x += 1;
+            curr_x = curr_x.saturating_add(width as u16);
+        }
+    }
+
+    fn writable(&self, x: u16, y: u16) -> bool {
+        x < self.width && y < self.height && self.in_clip(x, y)
+    }
+
+    /// Character to output for a cell: nothing for the right half of a wide
+    /// character, and a space for half of one that was drawn over.
+    pub(crate) fn output_char(&self, x: u16, y: u16) -> Option<char> {
+        let cell = self.get(x, y)?;
+        if cell.is_continuation() {
+            let lead = x.checked_sub(1).and_then(|lx| self.get(lx, y));
+            return match lead {
+                Some(lead) if char_width(lead.char) > 1 => None,
+                _ => Some(' '),
+            };
         }
+        if char_width(cell.char) > 1 && !self.get(x + 1, y).is_some_and(Cell::is_continuation) {
+            return Some(' ');
+        }
+        // Cells set directly may still hold controls
+        Some(control_picture(cell.char).unwrap_or(cell.char))
     }
 
     /// Fill a rectangle with a cell.
@@ -194,6 +392,22 @@ impl OutputBuffer {
         }
     }
 
+    /// Blend a color over the cells in a rectangle, keeping their text.
+    ///
+    /// Both text and background fade toward `color`, like a translucent
+    /// layer. Terminal default colors blend as the reported (or assumed)
+    /// default foreground and background.
+    pub fn blend_rect(&mut self, x: u16, y: u16, width: u16, height: u16, color: Color, alpha: f32) {
+        let defaults = default_colors();
+        for dy in 0..height {
+            for dx in 0..width {
+                if let Some(cell) = self.get_mut(x.saturating_add(dx), y.saturating_add(dy)) {
+                    cell.blend(color, alpha, defaults);
+                }
+            }
+        }
+    }
+
     /// Draw a border around a rectangle.
     pub fn draw_border(
         &mut self,
@@ -207,25 +421,120 @@ impl OutputBuffer {
         if width < 2 || height < 2 {
             return;
         }
+        self.draw_border_sides(x, y, width, height, style, &BorderSides::all(color));
+    }
+
+    /// Draw some sides of a border, each in its own color.
+    ///
+    /// Corners appear where two drawn sides meet. Border characters drawn
+    /// over others of the same set merge into junctions (`┼`), and a side
+    /// whose end is left open connects to a border line just beyond it
+    /// (`├`, `┬`), so boxes that only draw a divider join their parent's
+    /// frame.
+    pub fn draw_border_sides(
+        &mut self,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        style: BorderStyle,
+        sides: &BorderSides,
+    ) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let chars = if self.unicode { get_border_chars(style) } else { get_ascii_border_chars(style) };
+        let right = x + width - 1;
+        let bottom = y + height - 1;
+
+        // Horizontal sides own the corners
+        for (row, color, top) in [(y, sides.top, true), (bottom, sides.bottom, false)] {
+            let Some(color) = color else {
+                continue;
+            };
+            for cx in x..=right {
+                let c = match (cx == x && sides.left.is_some(), cx == right && sides.right.is_some(), top) {
+                    (true, _, true) => chars.top_left,
+                    (true, _, false) => chars.bottom_left,
+                    (_, true, true) => chars.top_right,
+                    (_, true, false) => chars.bottom_right,
+                    _ => chars.horizontal,
+                };
+                self.merge_border_char(cx, row, c, color, &chars);
+            }
+            if sides.left.is_none() && x > 0 {
+                self.connect_border(x - 1, row, ARM_RIGHT, &chars);
+            }
+            if sides.right.is_none() {
+                self.connect_border(right.saturating_add(1), row, ARM_LEFT, &chars);
+            }
+        }
 
-        let chars = get_border_chars(style);
+        for (column, color) in [(x, sides.left), (right, sides.right)] {
+            let Some(color) = color else {
+                continue;
+            };
+            let first = if sides.top.is_some() { y + 1 } else { y };
+            let last = if sides.bottom.is_some() { bottom.saturating_sub(1) } else { bottom };
+            for cy in first..=last {
+                self.merge_border_char(column, cy, chars.vertical, color, &chars);
+            }
+            if sides.top.is_none() && y > 0 {
+                self.connect_border(column, y - 1, ARM_DOWN, &chars);
+            }
+            if sides.bottom.is_none() {
+                self.connect_border(column, bottom.saturating_add(1), ARM_UP, &chars);
+            }
+        }
+    }
 
-        // Corners
-        self.set_styled_char(x, y, chars.top_left, color);
-        self.set_styled_char(x + width - 1, y, chars.top_right, color);
-        self.set_styled_char(x, y + height - 1, chars.bottom_left, color);
-        self.set_styled_char(x + width - 1, y + height - 1, chars.bottom_right, color);
+    /// Draw a title into a border row drawn by `draw_border_sides`.
+    pub fn draw_border_title(&mut self, x: u16, y: u16, width: u16, title: &BorderTitle, color: Color) {
+        // Keep the corners and one line character on each side
+        let span = usize::from(width.saturating_sub(4));
+        let label = truncate_text(&format!(" {} ", title.text), span, "");
+        let len = visible_width(&label) as u16;
+        if len == 0 {
+            return;
+        }
+        let start = match title.align {
+            TitleAlign::Left => x + 2,
+            TitleAlign::Center => x + (width - len) / 2,
+            TitleAlign::Right => x + width - 2 - len,
+        };
+        let style = TextStyle { color: Some(title.color.unwrap_or(color)), ..Default::default() };
+        self.write_str(start, y, &label, &style);
+    }
 
-        // Horizontal lines
-        for dx in 1..width - 1 {
-            self.set_styled_char(x + dx, y, chars.horizontal, color);
-            self.set_styled_char(x + dx, y + height - 1, chars.horizontal, color);
+    /// Draw a border character, merging it with one already there.
+    fn merge_border_char(&mut self, x: u16, y: u16, c: char, color: Color, chars: &BorderChars) {
+        if let Some(cell) = self.get_mut(x, y) {
+            cell.char = match (chars.arms(cell.char), chars.arms(c)) {
+                (Some(existing), Some(new)) => chars.with_arms(existing | new),
+                _ => c,
+            };
+            cell.fg = color;
         }
+    }
 
-        // Vertical lines
-        for dy in 1..height - 1 {
-            self.set_styled_char(x, y + dy, chars.vertical, color);
-            self.set_styled_char(x + width - 1, y + dy, chars.vertical, color);
+    /// Extend a border line at `(x, y)`, if there is one, by an arm.
+    ///
+    /// The line may belong to the frame of the box clipping this one, so
+    /// cells one beyond the clip rectangle count too.
+    fn connect_border(&mut self, x: u16, y: u16, arm: u8, chars: &BorderChars) {
+        let near_clip = self.clip.map_or(true, |clip| {
+            (clip.x.saturating_sub(1)..=clip.x + clip.width).contains(&x)
+                && (clip.y.saturating_sub(1)..=clip.y + clip.height).contains(&y)
+        });
+        if x >= self.width || y >= self.height || !near_clip {
+            return;
+        }
+        let idx = (y as usize) * (self.width as usize) + (x as usize);
+        if let Some(cell) = self.cells.get_mut(idx) {
+            if let Some(arms) = chars.arms(cell.char) {
+                cell.char = chars.with_arms(arms | arm);
+            }
         }
     }
 
@@ -236,11 +545,101 @@ impl OutputBuffer {
         }
     }
 
+    /// Copy a region of another buffer onto this one.
+    ///
+    /// Blank cells in the source are skipped and a source cell without a
+    /// background keeps the background already painted underneath, so the
+    /// copy composites like drawing the content in place would.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit(
+        &mut self,
+        src: &OutputBuffer,
+        src_x: u16,
+        src_y: u16,
+        dst_x: u16,
+        dst_y: u16,
+        width: u16,
+        height: u16,
+    ) {
+        let blank = Cell::default();
+        for dy in 0..height {
+            for dx in 0..width {
+                let Some(cell) = src.get(src_x + dx, src_y + dy) else {
+                    continue;
+                };
+                if *cell == blank {
+                    continue;
+                }
+                if let Some(target) = self.get_mut(dst_x + dx, dst_y + dy) {
+                    let bg = target.bg;
+                    *target = cell.clone();
+                    if cell.bg == Color::Default {
+                        target.bg = bg;
+                    }
+                }
+            }
+        }
+
+        // Images and the cursor travel with the content they sit in
+        for graphic in &src.graphics {
+            let inside = graphic.x >= src_x
+                && graphic.y >= src_y
+                && graphic.x as u32 + graphic.width as u32 <= src_x as u32 + width as u32
+                && graphic.y as u32 + graphic.height as u32 <= src_y as u32 + height as u32;
+            if inside {
+                self.place_graphic(GraphicPlacement {
+                    x: graphic.x - src_x + dst_x,
+                    y: graphic.y - src_y + dst_y,
+                    ..graphic.clone()
+                });
+            }
+        }
+        if let Some(cursor) = src.cursor {
+            let inside = (src_x..src_x.saturating_add(width)).contains(&cursor.x)
+                && (src_y..src_y.saturating_add(height)).contains(&cursor.y);
+            if inside {
+                self.set_cursor(Some(CursorPlacement {
+                    x: cursor.x - src_x + dst_x,
+                    y: cursor.y - src_y + dst_y,
+                    ..cursor
+                }));
+            }
+        }
+    }
+
+    /// Number of rows up to the last one with anything drawn on it.
+    pub fn content_height(&self) -> u16 {
+        let blank = Cell::default();
+        (0..self.height)
+            .rev()
+            .find(|&y| (0..self.width).any(|x| self.get(x, y).is_some_and(|cell| *cell != blank)))
+            .map_or(0, |y| y + 1)
+    }
+
+    /// Drop rows below `height`.
+    pub fn truncate_height(&mut self, height: u16) {
+        self.height = self.height.min(height);
+        self.cells.truncate(usize::from(self.width) * usize::from(self.height));
+    }
+
+    /// Render buffer to plain text, without escape codes or trailing spaces.
+    pub fn to_plain_string(&self) -> String {
+        let lines: Vec<String> = (0..self.height)
+            .map(|y| {
+                let line: String = (0..self.width).filter_map(|x| self.output_char(x, y)).collect();
+                line.trim_end().to_string()
+            })
+            .collect();
+        lines.join("\n")
+    }
+
     /// Clear the buffer.
     pub fn clear(&mut self) {
         for cell in &mut self.cells {
             *cell = Cell::default();
         }
+        self.cursor = None;
+        self.graphics.clear();
     }
 
     /// Generate diff between two buffers.
@@ -260,25 +659,62 @@ impl OutputBuffer {
         changes
     }
 
-    /// Render buffer to a string.
+    /// Render buffer to a string, with colors reduced to what the terminal supports.
     pub fn to_string(&self) -> String {
-        let mut output = String::new();
-        let mut last_fg = Color::Default;
-        let mut last_bg = Color::Default;
-        let mut last_bold = false;
-        let mut last_dim = false;
+        self.to_ansi_string(crate::core::capabilities::color_support())
+    }
+
+    /// Render buffer to a string for a given color depth.
+    ///
+    /// Colors are downsampled to the palette; `ColorSupport::None` keeps
+    /// text attributes but emits no color codes. Runs of cells with the
+    /// same hyperlink are wrapped in OSC 8 sequences, closed at line ends.
+    pub fn to_ansi_string(&self, support: ColorSupport) -> String {
+        self.ansi_string(support, None, false)
+    }
+
+    /// Render buffer to a string for a terminal that keeps the Kitty images
+    /// of earlier frames: those are placed again by ID rather than sent
+    /// again, and images no longer shown are deleted.
+    pub fn to_frame_string(&self, support: ColorSupport, images: &mut KittyImages) -> String {
+        self.ansi_string(support, Some(images), false)
+    }
+
+    /// With `trim`, blank cells at the end of each line are left out.
+    fn ansi_string(&self, support: ColorSupport, images: Option<&mut KittyImages>, trim: bool) -> String {
+        // Without a record of the terminal's images, every frame sends them
+        let (transmit, deleted): (Vec<bool>, String) = match images {
+            Some(images) => {
+                let transmit = self.graphics.iter().map(|g| g.kitty_id.map_or(true, |id| images.show(id))).collect();
+                (transmit, images.end_frame())
+            }
+            None => (vec![true; self.graphics.len()], String::new()),
+        };
+
+        let styled_underlines = crate::core::capabilities::styled_underlines_supported();
+        let mut output = deleted;
+        let default_cell = Cell::default();
+        let mut last = &default_cell;
+        let mut last_link: Option<&Rc<str>> = None;
 
         for y in 0..self.height {
             if y > 0 {
+                if last_link.take().is_some() {
+                    output.push_str(&hyperlink_sequence(None));
+                }
                 output.push('\n');
             }
 
-            for x in 0..self.width {
-                if let Some(cell) = self.get(x, y) {
+            let end = if trim { self.line_end(y) } else { self.width };
+            for x in 0..end {
+                if let (Some(cell), Some(c)) = (self.get(x, y), self.output_char(x, y)) {
+                    if cell.hyperlink.as_ref() != last_link {
+                        output.push_str(&hyperlink_sequence(cell.hyperlink.as_deref()));
+                        last_link = cell.hyperlink.as_ref();
+                    }
+
                     // Apply style changes
-                    if cell.fg != last_fg || cell.bg != last_bg
-                        || cell.bold != last_bold || cell.dim != last_dim
-                    {
+                    if !cell.same_style(last) {
                         output.push_str("\x1B[0m"); // Reset
 
                         if cell.bold {
@@ -291,7 +727,10 @@ impl OutputBuffer {
                             output.push_str("\x1B[3m");
                         }
                         if cell.underline {
-                            output.push_str("\x1B[4m");
+                            output.push_str(underline_to_ansi(cell.underline_style, styled_underlines));
+                            if styled_underlines {
+                                output.push_str(&color_to_ansi_underline(cell.underline_color.downsample(support)));
+                            }
                         }
                         if cell.inverse {
                             output.push_str("\x1B[7m");
@@ -300,30 +739,140 @@ impl OutputBuffer {
                             output.push_str("\x1B[9m");
                         }
 
-                        output.push_str(&color_to_ansi_fg(cell.fg));
-                        output.push_str(&color_to_ansi_bg(cell.bg));
+                        output.push_str(&color_to_ansi_fg(cell.fg.downsample(support)));
+                        output.push_str(&color_to_ansi_bg(cell.bg.downsample(support)));
 
-                        last_fg = cell.fg;
-                        last_bg = cell.bg;
-                        last_bold = cell.bold;
-                        last_dim = cell.dim;
+                        last = cell;
                     }
 
-                    output.push(cell.char);
+                    output.push(c);
+                }
+
+                // Images go in once their blank cells are written, from the
+                // bottom-right corner so positions stay relative
+                for (graphic, &transmit) in self.graphics.iter().zip(&transmit) {
+                    if x + 1 == graphic.x + graphic.width && y + 1 == graphic.y + graphic.height {
+                        output.push_str(&graphic_sequence(graphic, transmit));
+                    }
                 }
             }
         }
 
         // Reset at end
+        if last_link.is_some() {
+            output.push_str(&hyperlink_sequence(None));
+        }
         output.push_str("\x1B[0m");
         output
     }
 }
 
+impl OutputBuffer {
+    /// Column just past the last non-blank cell, or image ending, on a row.
+    fn line_end(&self, y: u16) -> u16 {
+        let blank = Cell::default();
+        let text = (0..self.width)
+            .rev()
+            .find(|&x| self.get(x, y).is_some_and(|cell| *cell != blank))
+            .map_or(0, |x| x + 1);
+        self.graphics
+            .iter()
+            .filter(|graphic| graphic.y + graphic.height == y + 1)
+            .map(|graphic| graphic.x + graphic.width)
+            .fold(text, u16::max)
+    }
+}
+
+/// Draw an image from just after its bottom-right cell, then come back.
+///
+/// Moves are relative to the start of the line, so this works both for
+/// full-screen frames and for output printed inline.
+fn graphic_sequence(graphic: &GraphicPlacement, transmit: bool) -> String {
+    let mut output = String::from("\x1B7\r");
+    if graphic.height > 1 {
+        output.push_str(&format!("\x1B[{}A", graphic.height - 1));
+    }
+    if graphic.x > 0 {
+        output.push_str(&format!("\x1B[{}C", graphic.x));
+    }
+    if transmit {
+        output.push_str(&graphic.sequence);
+    }
+    if let Some(id) = graphic.kitty_id {
+        output.push_str(&graphics::kitty_place(id, graphic.width, graphic.height));
+    }
+    output.push_str("\x1B8");
+    output
+}
+
+impl Cell {
+    /// Fade this cell's colors toward `color`, given the terminal's default
+    /// foreground and background.
+    fn blend(&mut self, color: Color, alpha: f32, (default_fg, default_bg): ((u8, u8, u8), (u8, u8, u8))) {
+        let over = color.to_rgb().unwrap_or(default_bg);
+        self.fg = self.fg.blend(over, alpha, default_fg);
+        self.bg = self.bg.blend(over, alpha, default_bg);
+        if self.underline_color != Color::Default {
+            self.underline_color = self.underline_color.blend(over, alpha, default_fg);
+        }
+    }
+}
+
+/// Terminal default foreground and background for blending: what the
+/// terminal reported, or light grey on black.
+fn default_colors() -> ((u8, u8, u8), (u8, u8, u8)) {
+    let results = crate::core::queries::query_results();
+    (results.foreground.unwrap_or((229, 229, 229)), results.background.unwrap_or((0, 0, 0)))
+}
+
+/// OSC 8 sequence opening a hyperlink, or closing one for `None`.
+///
+/// Only printable ASCII may appear in the URL, so anything else is
+/// dropped rather than letting it end the sequence early.
+pub fn hyperlink_sequence(url: Option<&str>) -> String {
+    let url: String = url.unwrap_or("").chars().filter(|c| matches!(c, ' '..='~')).collect();
+    format!("\x1B]8;;{}\x1B\\", url)
+}
+
 // =============================================================================
 // Border Characters
 // =============================================================================
 
+/// Which sides of a border to draw, with their colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BorderSides {
+    pub top: Option<Color>,
+    pub right: Option<Color>,
+    pub bottom: Option<Color>,
+    pub left: Option<Color>,
+}
+
+impl BorderSides {
+    /// All four sides in one color.
+    pub fn all(color: Color) -> Self {
+        Self { top: Some(color), right: Some(color), bottom: Some(color), left: Some(color) }
+    }
+
+    /// Sides and colors a box style asks for.
+    pub fn for_style(style: &BoxStyle) -> Self {
+        let [top, right, bottom, left] = style.border_sides();
+        let color = style.border_color.unwrap_or(Color::Default);
+        let side = |drawn: bool, color_override: Option<Color>| drawn.then(|| color_override.unwrap_or(color));
+        Self {
+            top: side(top, style.border_top_color),
+            right: side(right, style.border_right_color),
+            bottom: side(bottom, style.border_bottom_color),
+            left: side(left, style.border_left_color),
+        }
+    }
+}
+
+/// Line directions out of a border character's cell.
+const ARM_UP: u8 = 1;
+const ARM_RIGHT: u8 = 2;
+const ARM_DOWN: u8 = 4;
+const ARM_LEFT: u8 = 8;
+
 /// Border character set.
 #[derive(Debug, Clone, Copy)]
 pub struct BorderChars {
@@ -340,6 +889,48 @@ pub struct BorderChars {
     pub cross: char,
 }
 
+impl BorderChars {
+    /// Directions a character of this set reaches out in, if it is one.
+    fn arms(&self, c: char) -> Option<u8> {
+        [
+            (self.horizontal, ARM_LEFT | ARM_RIGHT),
+            (self.vertical, ARM_UP | ARM_DOWN),
+            (self.top_left, ARM_RIGHT | ARM_DOWN),
+            (self.top_right, ARM_DOWN | ARM_LEFT),
+            (self.bottom_left, ARM_UP | ARM_RIGHT),
+            (self.bottom_right, ARM_UP | ARM_LEFT),
+            (self.t_left, ARM_UP | ARM_RIGHT | ARM_DOWN),
+            (self.t_right, ARM_UP | ARM_DOWN | ARM_LEFT),
+            (self.t_top, ARM_RIGHT | ARM_DOWN | ARM_LEFT),
+            (self.t_bottom, ARM_UP | ARM_RIGHT | ARM_LEFT),
+            (self.cross, ARM_UP | ARM_RIGHT | ARM_DOWN | ARM_LEFT),
+        ]
+        .iter()
+        .find(|(candidate, _)| *candidate == c && c != ' ')
+        .map(|(_, arms)| *arms)
+    }
+
+    /// Character of this set reaching out in the given directions.
+    fn with_arms(&self, arms: u8) -> char {
+        const VERTICAL: u8 = ARM_UP | ARM_DOWN;
+        const HORIZONTAL: u8 = ARM_LEFT | ARM_RIGHT;
+        match arms {
+            0 => ' ',
+            ARM_UP | ARM_DOWN | VERTICAL => self.vertical,
+            ARM_LEFT | ARM_RIGHT | HORIZONTAL => self.horizontal,
+            a if a == ARM_RIGHT | ARM_DOWN => self.top_left,
+            a if a == ARM_DOWN | ARM_LEFT => self.top_right,
+            a if a == ARM_UP | ARM_RIGHT => self.bottom_left,
+            a if a == ARM_UP | ARM_LEFT => self.bottom_right,
+            a if a == VERTICAL | ARM_RIGHT => self.t_left,
+            a if a == VERTICAL | ARM_LEFT => self.t_right,
+            a if a == HORIZONTAL | ARM_DOWN => self.t_top,
+            a if a == HORIZONTAL | ARM_UP => self.t_bottom,
+            _ => self.cross,
+        }
+    }
+}
+
 /// Get border characters for a style.
 pub fn get_border_chars(style: BorderStyle) -> BorderChars {
     match style {
@@ -450,6 +1041,30 @@ pub fn get_border_chars(style: BorderStyle) -> BorderChars {
     }
 }
 
+/// ASCII border characters for a style.
+pub fn get_ascii_border_chars(style: BorderStyle) -> BorderChars {
+    let (horizontal, vertical) = match style {
+        BorderStyle::None | BorderStyle::Hidden => return get_border_chars(style),
+        BorderStyle::Double | BorderStyle::Bold => ('=', '|'),
+        BorderStyle::Dotted => ('.', ':'),
+        BorderStyle::Single | BorderStyle::Round | BorderStyle::Dashed | BorderStyle::Classic => ('-', '|'),
+    };
+    let corner = if style == BorderStyle::Dotted { '.' } else { '+' };
+    BorderChars {
+        top_left: corner,
+        top_right: corner,
+        bottom_left: corner,
+        bottom_right: corner,
+        horizontal,
+        vertical,
+        t_left: corner,
+        t_right: corner,
+        t_top: corner,
+        t_bottom: corner,
+        cross: corner,
+    }
+}
+
 // =============================================================================
 // Color Conversion
 // =============================================================================
@@ -472,6 +1087,34 @@ fn color_to_ansi_bg(color: Color) -> String {
     }
 }
 
+/// SGR for an underline style; terminals without styled underlines get
+/// a plain one, since they may misread `4:x` as other attributes.
+fn underline_to_ansi(style: UnderlineStyle, styled: bool) -> &'static str {
+    if !styled {
+        return "\x1B[4m";
+    }
+    match style {
+        UnderlineStyle::Single => "\x1B[4m",
+        UnderlineStyle::Double => "\x1B[4:2m",
+        UnderlineStyle::Curly => "\x1B[4:3m",
+        UnderlineStyle::Dotted => "\x1B[4:4m",
+        UnderlineStyle::Dashed => "\x1B[4:5m",
+    }
+}
+
+fn color_to_ansi_underline(color: Color) -> String {
+    match color {
+        Color::Default => String::new(),
+        Color::Named(c) => {
+            let code = named_color_to_fg_code(c);
+            let index = if code >= 90 { code - 90 + 8 } else { code - 30 };
+            format!("\x1B[58;5;{}m", index)
+        }
+        Color::Ansi256(n) => format!("\x1B[58;5;{}m", n),
+        Color::Rgb(r, g, b) => format!("\x1B[58;2;{};{};{}m", r, g, b),
+    }
+}
+
 fn named_color_to_fg_code(color: NamedColor) -> u8 {
     match color {
         NamedColor::Black => 30,
@@ -497,84 +1140,714 @@ fn named_color_to_bg_code(color: NamedColor) -> u8 {
     named_color_to_fg_code(color) + 10
 }
 
+// =============================================================================
+// Scroll State
+// =============================================================================
+
+/// Lines scrolled per mouse wheel step.
+pub const SCROLL_STEP: u16 = 3;
+
+/// Scroll position and extent of a rendered `Overflow::Scroll` box.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrollRegion {
+    /// Visible area (inside the border)
+    pub viewport: ComputedLayout,
+    /// Width of the scrollable content
+    pub content_width: u16,
+    /// Height of the scrollable content
+    pub content_height: u16,
+    /// Horizontal scroll offset
+    pub offset_x: u16,
+    /// Vertical scroll offset
+    pub offset_y: u16,
+}
+
+impl ScrollRegion {
+    /// Largest valid horizontal offset.
+    pub fn max_offset_x(&self) -> u16 {
+        self.content_width.saturating_sub(self.viewport.width)
+    }
+
+    /// Largest valid vertical offset.
+    pub fn max_offset_y(&self) -> u16 {
+        self.content_height.saturating_sub(self.viewport.height)
+    }
+}
+
+thread_local! {
+    static SCROLL_REGIONS: RefCell<HashMap<u64, ScrollRegion>> = RefCell::new(HashMap::new());
+    /// Scroll boxes drawn in the frame being rendered
+    static RENDERED_SCROLL_REGIONS: RefCell<HashSet<u64>> = RefCell::new(HashSet::new());
+}
+
+/// Forget the scroll boxes a frame left out, so boxes that have left the
+/// tree stop catching wheel events.
+fn prune_scroll_regions() {
+    let rendered = RENDERED_SCROLL_REGIONS.with(RefCell::take);
+    SCROLL_REGIONS.with(|regions| regions.borrow_mut().retain(|id, _| rendered.contains(id)));
+}
+
+/// Get the last rendered scroll state of a box.
+pub fn scroll_region(id: u64) -> Option<ScrollRegion> {
+    SCROLL_REGIONS.with(|regions| regions.borrow().get(&id).copied())
+}
+
+/// Scroll a box to an offset (clamped to its content on the next render).
+pub fn scroll_to(id: u64, x: u16, y: u16) {
+    SCROLL_REGIONS.with(|regions| {
+        let mut regions = regions.borrow_mut();
+        let region = regions.entry(id).or_default();
+        region.offset_x = x;
+        region.offset_y = y;
+    });
+}
+
+/// Scroll a box by a relative amount, clamped to its content.
+pub fn scroll_by(id: u64, dx: i32, dy: i32) {
+    SCROLL_REGIONS.with(|regions| {
+        if let Some(region) = regions.borrow_mut().get_mut(&id) {
+            region.offset_x = offset_add(region.offset_x, dx, region.max_offset_x());
+            region.offset_y = offset_add(region.offset_y, dy, region.max_offset_y());
+        }
+    });
+}
+
+fn offset_add(offset: u16, delta: i32, max: u16) -> u16 {
+    (i32::from(offset) + delta).clamp(0, i32::from(max)) as u16
+}
+
+/// Route a mouse wheel event to the innermost scroll box under the pointer.
+///
+/// Returns `true` if a box was scrolled and the UI should be redrawn.
+pub fn handle_scroll_event(event: &MouseEvent) -> bool {
+    let (dx, dy) = match event.kind {
+        MouseEventKind::ScrollUp => (0, -i32::from(SCROLL_STEP)),
+        MouseEventKind::ScrollDown => (0, i32::from(SCROLL_STEP)),
+        MouseEventKind::ScrollLeft => (-i32::from(SCROLL_STEP), 0),
+        MouseEventKind::ScrollRight => (i32::from(SCROLL_STEP), 0),
+        _ => return false,
+    };
+    // Mouse coordinates are 1-indexed
+    let x = event.x.saturating_sub(1);
+    let y = event.y.saturating_sub(1);
+
+    let target = SCROLL_REGIONS.with(|regions| {
+        regions
+            .borrow()
+            .iter()
+            .filter(|(_, region)| region.viewport.contains(x, y))
+            .min_by_key(|(_, region)| u32::from(region.viewport.width) * u32::from(region.viewport.height))
+            .map(|(id, region)| (*id, *region))
+    });
+
+    match target {
+        Some((id, before)) => {
+            scroll_by(id, dx, dy);
+            scroll_region(id) != Some(before)
+        }
+        None => false,
+    }
+}
+
 // =============================================================================
 // Render Functions
 // =============================================================================
 
+thread_local! {
+    /// Layouts from earlier frames, reused for unchanged subtrees
+    static LAYOUT_CACHE: RefCell<LayoutCache> = RefCell::new(LayoutCache::new());
+}
+
 /// Render a VNode tree to a string.
 pub fn render_to_string(node: &VNode, width: u16, height: u16) -> String {
-    let mut buffer = OutputBuffer::new(width, height);
+    render_with_context(node, &RenderContext::new(width, height))
+}
+
+/// Render a VNode tree to a string with explicit render settings.
+pub fn render_with_context(node: &VNode, context: &RenderContext) -> String {
+    render_to_buffer(node, context).to_string()
+}
+
+/// Lay out and draw a VNode tree into a fresh buffer.
+pub fn render_to_buffer(node: &VNode, context: &RenderContext) -> OutputBuffer {
+    let (width, height) = (context.width, context.height);
+    let mut buffer = OutputBuffer::for_context(context);
+    let layout_node = vnode_to_layout_node(node, 0);
+    let layouts = LAYOUT_CACHE.with(|cache| {
+        calculate_layout_cached(&layout_node, width, height, &mut cache.borrow_mut())
+    });
+
+    render_vnode_to_buffer(node, &layouts, 0, &mut buffer);
+    prune_scroll_regions();
+    buffer
+}
+
+/// Options for rendering to text outside the terminal UI, e.g. for
+/// output piped into logs and files.
+#[derive(Debug, Clone, Default)]
+pub struct TextRenderOptions {
+    /// Width in columns; `COLUMNS`, then the terminal width, then 80 when unset
+    pub width: Option<u16>,
+    /// Keep ANSI styling; by default only when stdout is a terminal
+    pub ansi: Option<bool>,
+    /// Palette for ANSI styling; true color when unset
+    pub color_support: Option<ColorSupport>,
+}
+
+impl TextRenderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn ansi(mut self, ansi: bool) -> Self {
+        self.ansi = Some(ansi);
+        self
+    }
+
+    pub fn color_support(mut self, support: ColorSupport) -> Self {
+        self.color_support = Some(support);
+        self
+    }
+
+    /// Width to render at, after falling back to the environment.
+    pub fn resolved_width(&self) -> u16 {
+        self.width
+            .or_else(|| std::env::var("COLUMNS").ok().and_then(|v| v.trim().parse().ok()).filter(|&w| w > 0))
+            .or_else(|| {
+                crate::core::capabilities::stdout_is_tty()
+                    .then(|| crate::core::terminal::get_terminal_size().ok().map(|(w, _)| w))
+                    .flatten()
+            })
+            .unwrap_or(80)
+    }
+
+    /// Whether to keep ANSI styling, after falling back to the environment.
+    pub fn resolved_ansi(&self) -> bool {
+        self.ansi.unwrap_or_else(crate::core::capabilities::stdout_is_tty)
+    }
+}
+
+/// Render a VNode tree as text at its natural height.
+///
+/// Unlike [`render_to_string`] the height follows the content, trailing
+/// blank lines are dropped and ANSI styling can be left out, so the
+/// result can go straight into a pipe or a file.
+pub fn render_to_text(node: &VNode, options: &TextRenderOptions) -> String {
+    let width = options.resolved_width();
     let layout_node = vnode_to_layout_node(node, 0);
-    let layouts = calculate_layout(&layout_node, width, height);
+    let margin = layout_node.style.margin.vertical();
+    let height = crate::core::layout::intrinsic_size(&layout_node, width).1.saturating_add(margin).max(1);
 
+    let layouts = crate::core::layout::calculate_layout(&layout_node, width, height);
+    let mut buffer = OutputBuffer::new(width, height);
     render_vnode_to_buffer(node, &layouts, 0, &mut buffer);
+    buffer.truncate_height(buffer.content_height());
 
-    buffer.to_string()
+    if options.resolved_ansi() {
+        // Not `to_string`: the terminal's palette means nothing in a pipe
+        let support = options.color_support.unwrap_or(ColorSupport::TrueColor);
+        buffer.ansi_string(support, None, true)
+    } else {
+        buffer.to_plain_string()
+    }
 }
 
 /// Render a VNode to an output buffer.
+///
+/// Absolutely positioned boxes and boxes with a z-index are drawn after
+/// the rest of the tree, in ascending z-index order (tree order breaks
+/// ties), so they float above normal content and escape ancestor clipping.
 pub fn render_vnode_to_buffer(
     node: &VNode,
     layouts: &HashMap<u64, ComputedLayout>,
     id: u64,
     buffer: &mut OutputBuffer,
+) {
+    let mut layers = LayerQueue::default();
+    render_node(node, layouts, id, buffer, &mut layers);
+
+    while let Some(layer) = layers.pop() {
+        if let Some(layout) = layouts.get(&layer.id) {
+            let outer_clip = buffer.set_clip(None);
+            render_box(layer.node, layer.id, layout, layouts, buffer, &mut layers);
+            buffer.set_clip(outer_clip);
+        }
+    }
+}
+
+/// A box deferred to be drawn above normal content.
+struct Layer<'a> {
+    z_index: i32,
+    order: usize,
+    id: u64,
+    node: &'a BoxNode,
+}
+
+/// Pending layers, drawn lowest z-index first.
+#[derive(Default)]
+struct LayerQueue<'a> {
+    layers: Vec<Layer<'a>>,
+    next_order: usize,
+}
+
+impl<'a> LayerQueue<'a> {
+    fn push(&mut self, node: &'a BoxNode, id: u64) {
+        self.layers.push(Layer {
+            z_index: node.style.z_index.unwrap_or(0),
+            order: self.next_order,
+            id,
+            node,
+        });
+        self.next_order += 1;
+    }
+
+    fn pop(&mut self) -> Option<Layer<'a>> {
+        let index = self
+            .layers
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, layer)| (layer.z_index, layer.order))
+            .map(|(index, _)| index)?;
+        Some(self.layers.remove(index))
+    }
+}
+
+fn is_layer(box_node: &BoxNode) -> bool {
+    box_node.style.position == Some(Position::Absolute) || box_node.style.z_index.is_some()
+}
+
+fn render_node<'a>(
+    node: &'a VNode,
+    layouts: &HashMap<u64, ComputedLayout>,
+    id: u64,
+    buffer: &mut OutputBuffer,
+    layers: &mut LayerQueue<'a>,
 ) {
     match node {
         VNode::Box(box_node) => {
             let node_id = box_node.id.unwrap_or(id);
-            if let Some(layout) = layouts.get(&node_id) {
-                // Draw background
-                if let Some(bg) = box_node.style.background {
-                    buffer.fill_rect(
-                        layout.x,
-                        layout.y,
-                        layout.width,
-                        layout.height,
-                        Cell {
-                            char: ' ',
-                            bg,
-                            ..Default::default()
-                        },
-                    );
-                }
-
-                // Draw border
-                if let Some(border_style) = box_node.style.border_style {
-                    if !matches!(border_style, BorderStyle::None | BorderStyle::Hidden) {
-                        buffer.draw_border(
-                            layout.x,
-                            layout.y,
-                            layout.width,
-                            layout.height,
-                            border_style,
-                            box_node.style.border_color.unwrap_or(Color::Default),
-                        );
-                    }
-                }
-
-                // Render children
-                for (i, child) in box_node.children.iter().enumerate() {
-                    render_vnode_to_buffer(child, layouts, node_id * 1000 + i as u64, buffer);
-                }
+            if is_layer(box_node) {
+                layers.push(box_node, node_id);
+            } else if let Some(layout) = layouts.get(&node_id) {
+                render_box(box_node, node_id, layout, layouts, buffer, layers);
             }
         }
         VNode::Text(text_node) => {
             if let Some(layout) = layouts.get(&id) {
-                buffer.write_str(layout.x, layout.y, &text_node.content, &text_node.style);
+                let measure = text_measure(text_node);
+                let lines = measure.lines(layout.width);
+                if let Some(cursor) = text_node.style.cursor {
+                    let (column, row) = caret_cell(&lines, cursor.offset);
+                    if row < layout.height.max(1) {
+                        buffer.set_cursor(Some(CursorPlacement {
+                            x: layout.x.saturating_add(column),
+                            y: layout.y + row,
+                            shape: cursor.shape,
+                            blinking: cursor.blinking,
+                        }));
+                    }
+                }
+                let spans = (!text_node.spans.is_empty()).then(|| span_owners(text_node, &lines));
+                for (dy, line) in lines.iter().enumerate() {
+                    let dy = dy as u16;
+                    if dy >= layout.height {
+                        break;
+                    }
+                    match &spans {
+                        Some((styles, owners)) => {
+                            let mut utf8 = [0; 4];
+                            let mut x = layout.x;
+                            for (c, owner) in line.chars().zip(&owners[usize::from(dy)]) {
+                                buffer.write_str(x, layout.y + dy, c.encode_utf8(&mut utf8), &styles[*owner]);
+                                x = x.saturating_add(char_width(c) as u16);
+                            }
+                        }
+                        None => buffer.write_str(layout.x, layout.y + dy, line, &text_node.style),
+                    }
+                }
+                if let Some(gradient) = &text_node.style.gradient {
+                    paint_text_gradient(gradient, &lines, layout, buffer);
+                }
             }
         }
         VNode::Spacer(_) => {
             // Spacers don't render anything visible
         }
+        VNode::Image(image_node) => {
+            if let Some(layout) = layouts.get(&id) {
+                render_image(image_node, layout.x, layout.y, layout.width, layout.height, buffer);
+            }
+        }
         VNode::Fragment(children) => {
             for (i, child) in children.iter().enumerate() {
-                render_vnode_to_buffer(child, layouts, id * 1000 + i as u64, buffer);
+                render_node(child, layouts, child_id(id, i), buffer, layers);
             }
         }
         VNode::Empty => {}
     }
 }
 
+/// Draw an image with a pixel protocol when it fits, otherwise with
+/// block characters.
+fn render_image(node: &ImageNode, x: u16, y: u16, width: u16, height: u16, buffer: &mut OutputBuffer) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let protocol = node.protocol.unwrap_or_else(crate::core::capabilities::graphics_protocol);
+    // Sixel moves the cursor below the image, which scrolls on the last row
+    let room = protocol != GraphicsProtocol::Sixel || (y as u32 + height as u32) < buffer.height as u32;
+    if room {
+        if let Some(sequence) = graphics::encode(&node.image, protocol, width, height) {
+            let kitty_id = (protocol == GraphicsProtocol::Kitty).then(|| graphics::kitty_image_id(&node.image, width, height));
+            if buffer.place_graphic(GraphicPlacement { x, y, width, height, sequence, kitty_id }) {
+                return;
+            }
+        }
+    }
+
+    let cells = graphics::block_cells(&node.image, protocol, width, height, buffer.unicode);
+    for (i, cell) in cells.into_iter().enumerate() {
+        let (dx, dy) = ((i % width as usize) as u16, (i / width as usize) as u16);
+        if let Some(target) = buffer.get_mut(x.saturating_add(dx), y.saturating_add(dy)) {
+            *target = cell;
+        }
+    }
+}
+
+/// Column and row of a caret, in characters into wrapped text.
+///
+/// The column counts display width, so wide characters before the caret
+/// move it two cells. A caret past the end sits just after the last line.
+fn caret_cell(lines: &[String], offset: usize) -> (u16, u16) {
+    let width = |line: &str, chars: usize| line.chars().take(chars).map(char_width).sum::<usize>() as u16;
+    let mut remaining = offset;
+    for (row, line) in lines.iter().enumerate() {
+        let len = line.chars().count();
+        if remaining <= len {
+            return (width(line, remaining), row as u16);
+        }
+        remaining -= len;
+    }
+    let last = lines.last().map_or(0, |line| width(line, usize::MAX));
+    (last, lines.len().saturating_sub(1) as u16)
+}
+
+fn render_box<'a>(
+    box_node: &'a BoxNode,
+    node_id: u64,
+    layout: &ComputedLayout,
+    layouts: &HashMap<u64, ComputedLayout>,
+    buffer: &mut OutputBuffer,
+    layers: &mut LayerQueue<'a>,
+) {
+    // Tint everything drawn so far, which is what lies beneath this box
+    if let Some(backdrop) = box_node.style.backdrop {
+        draw_backdrop(backdrop, buffer);
+    }
+
+    // Draw background
+    let opacity = box_node.style.opacity.unwrap_or(1.0);
+    if let Some(gradient) = &box_node.style.background_gradient {
+        paint_background_gradient(gradient, layout, opacity, buffer);
+    } else if let (Some(bg), true) = (box_node.style.background, opacity < 1.0) {
+        buffer.blend_rect(layout.x, layout.y, layout.width, layout.height, bg, opacity);
+    } else if let Some(bg) = box_node.style.background {
+        buffer.fill_rect(
+            layout.x,
+            layout.y,
+            layout.width,
+            layout.height,
+            Cell {
+                char: ' ',
+                bg,
+                ..Default::default()
+            },
+        );
+    }
+
+    // Draw border
+    if let Some(border_style) = box_node.style.border_style {
+        if !matches!(border_style, BorderStyle::None | BorderStyle::Hidden) {
+            draw_box_border(box_node, border_style, layout, buffer);
+        }
+    }
+
+    let overflow = box_node.style.overflow.unwrap_or_default();
+    if overflow == Overflow::Visible {
+        for (i, child) in box_node.children.iter().enumerate() {
+            render_node(child, layouts, child_id(node_id, i), buffer, layers);
+        }
+        return;
+    }
+
+    // Clip children to the area inside the border
+    let viewport = layout.inner(&border_edges(&box_node.style));
+    let outer_clip = buffer.clip();
+    let clip = outer_clip.map_or(viewport, |outer| outer.intersect(&viewport));
+    buffer.set_clip(Some(clip));
+
+    if overflow == Overflow::Scroll {
+        render_scroll_content(box_node, node_id, viewport, layouts, buffer);
+    } else {
+        for (i, child) in box_node.children.iter().enumerate() {
+            render_node(child, layouts, child_id(node_id, i), buffer, layers);
+        }
+    }
+
+    buffer.set_clip(outer_clip);
+}
+
+/// Styles of a rich text's spans layered over the node style, and which
+/// span each character of the wrapped lines came from.
+///
+/// Wrapping only collapses or drops whitespace and may add an ellipsis, so
+/// characters are matched back to the content in order.
+fn span_owners(node: &TextNode, lines: &[String]) -> (Vec<TextStyle>, Vec<Vec<usize>>) {
+    let styles = node.spans.iter().map(|span| node.style.merge(&span.style)).collect();
+    let content: Vec<(char, usize)> = node
+        .spans
+        .iter()
+        .enumerate()
+        .flat_map(|(i, span)| span.text.chars().map(move |c| (c, i)))
+        .collect();
+
+    let mut pos = 0;
+    let owners = lines
+        .iter()
+        .map(|line| {
+            line.chars()
+                .map(|c| {
+                    while content.get(pos).is_some_and(|&(o, _)| o != c && o.is_whitespace()) {
+                        pos += 1;
+                    }
+                    match content.get(pos) {
+                        Some(&(o, owner)) if o == c => {
+                            pos += 1;
+                            owner
+                        }
+                        other => other.or_else(|| content.last()).map_or(0, |&(_, owner)| owner),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    (styles, owners)
+}
+
+/// Color the cells a text wrote along a gradient, spread over its longest
+/// line or its line count.
+fn paint_text_gradient(gradient: &Gradient, lines: &[String], layout: &ComputedLayout, buffer: &mut OutputBuffer) {
+    let rows = (lines.len() as u16).min(layout.height);
+    let columns = lines.iter().map(|line| visible_width(line) as u16).max().unwrap_or(0);
+    for (dy, line) in lines.iter().take(usize::from(rows)).enumerate() {
+        let dy = dy as u16;
+        for dx in 0..visible_width(line) as u16 {
+            let color = match gradient.axis {
+                GradientAxis::Horizontal => gradient.step(dx, columns),
+                GradientAxis::Vertical => gradient.step(dy, rows),
+            };
+            if let Some(cell) = buffer.get_mut(layout.x.saturating_add(dx), layout.y.saturating_add(dy)) {
+                cell.fg = color;
+            }
+        }
+    }
+}
+
+/// Fill a box with a gradient, blended over what's beneath below full
+/// opacity.
+fn paint_background_gradient(gradient: &Gradient, layout: &ComputedLayout, opacity: f32, buffer: &mut OutputBuffer) {
+    let defaults = default_colors();
+    for dy in 0..layout.height {
+        for dx in 0..layout.width {
+            let color = match gradient.axis {
+                GradientAxis::Horizontal => gradient.step(dx, layout.width),
+                GradientAxis::Vertical => gradient.step(dy, layout.height),
+            };
+            let (x, y) = (layout.x + dx, layout.y + dy);
+            if opacity < 1.0 {
+                if let Some(cell) = buffer.get_mut(x, y) {
+                    cell.blend(color, opacity, defaults);
+                }
+            } else {
+                buffer.set(x, y, Cell { bg: color, ..Cell::default() });
+            }
+        }
+    }
+}
+
+/// Blend a backdrop over the whole visible buffer. Without colors it
+/// falls back to the dim attribute.
+fn draw_backdrop(backdrop: Backdrop, buffer: &mut OutputBuffer) {
+    if crate::core::capabilities::color_support() == ColorSupport::None {
+        for y in 0..buffer.height {
+            for x in 0..buffer.width {
+                if let Some(cell) = buffer.get_mut(x, y) {
+                    cell.dim = true;
+                }
+            }
+        }
+    } else {
+        buffer.blend_rect(0, 0, buffer.width, buffer.height, backdrop.color, backdrop.opacity);
+    }
+}
+
+/// Draw a box's border sides and titles.
+fn draw_box_border(box_node: &BoxNode, border_style: BorderStyle, layout: &ComputedLayout, buffer: &mut OutputBuffer) {
+    let sides = BorderSides::for_style(&box_node.style);
+    // A full frame needs room for its corners
+    let full = sides.top.is_some() && sides.right.is_some() && sides.bottom.is_some() && sides.left.is_some();
+    if full && (layout.width < 2 || layout.height < 2) {
+        return;
+    }
+    buffer.draw_border_sides(layout.x, layout.y, layout.width, layout.height, border_style, &sides);
+
+    for title in box_node.style.border_titles.iter().flatten() {
+        let (row, color) = match title.position {
+            TitlePosition::Top => (layout.y, sides.top),
+            TitlePosition::Bottom => (layout.y + layout.height.saturating_sub(1), sides.bottom),
+        };
+        if let Some(color) = color {
+            buffer.draw_border_title(layout.x, row, layout.width, title, color);
+        }
+    }
+}
+
+/// Space the drawn border sides take up around a box's content.
+fn border_edges(style: &BoxStyle) -> Edges {
+    let [top, right, bottom, left] = style.border_sides().map(u16::from);
+    Edges { top, right, bottom, left }
+}
+
+/// Render the children of a scroll box through its viewport.
+///
+/// Content is drawn into an offscreen buffer in layout coordinates, then the
+/// visible window at the current scroll offset is copied into `buffer`.
+fn render_scroll_content(
+    box_node: &BoxNode,
+    node_id: u64,
+    viewport: ComputedLayout,
+    layouts: &HashMap<u64, ComputedLayout>,
+    buffer: &mut OutputBuffer,
+) {
+    // The scrollbar sits in the last column, outside the scrolled viewport
+    let scrollbar = has_scrollbar(&box_node.style) && viewport.width > 0;
+    let viewport = ComputedLayout { width: viewport.width - u16::from(scrollbar), ..viewport };
+
+    let mut right = viewport.x + viewport.width;
+    let mut bottom = viewport.y + viewport.height;
+    for (i, child) in box_node.children.iter().enumerate() {
+        content_extent(child, child_id(node_id, i), layouts, &mut right, &mut bottom);
+    }
+
+    let mut region = scroll_region(node_id).unwrap_or_default();
+    region.viewport = viewport;
+    region.content_width = right - viewport.x;
+    region.content_height = bottom - viewport.y;
+    region.offset_x = box_node.style.scroll_x.unwrap_or(region.offset_x).min(region.max_offset_x());
+    region.offset_y = box_node.style.scroll_y.unwrap_or(region.offset_y).min(region.max_offset_y());
+    SCROLL_REGIONS.with(|regions| regions.borrow_mut().insert(node_id, region));
+    RENDERED_SCROLL_REGIONS.with(|rendered| rendered.borrow_mut().insert(node_id));
+
+    let mut offscreen = OutputBuffer::new(right, bottom);
+    offscreen.set_unicode(buffer.unicode);
+    for (i, child) in box_node.children.iter().enumerate() {
+        render_vnode_to_buffer(child, layouts, child_id(node_id, i), &mut offscreen);
+    }
+    buffer.blit(
+        &offscreen,
+        viewport.x + region.offset_x,
+        viewport.y + region.offset_y,
+        viewport.x,
+        viewport.y,
+        viewport.width,
+        viewport.height,
+    );
+
+    if scrollbar {
+        draw_scrollbar(&region, buffer);
+    }
+}
+
+/// Whether a box draws a scrollbar, which takes up its own column.
+fn has_scrollbar(style: &BoxStyle) -> bool {
+    style.overflow == Some(Overflow::Scroll) && style.scrollbar == Some(true)
+}
+
+/// Grow `right`/`bottom` to cover every laid-out descendant of `node`.
+fn content_extent(
+    node: &VNode,
+    id: u64,
+    layouts: &HashMap<u64, ComputedLayout>,
+    right: &mut u16,
+    bottom: &mut u16,
+) {
+    let (node_id, children) = match node {
+        VNode::Box(box_node) => (box_node.id.unwrap_or(id), box_node.children.as_slice()),
+        VNode::Fragment(children) => (id, children.as_slice()),
+        _ => (id, &[][..]),
+    };
+    if let Some(layout) = layouts.get(&node_id) {
+        *right = (*right).max(layout.x + layout.width);
+        *bottom = (*bottom).max(layout.y + layout.height);
+    }
+    for (i, child) in children.iter().enumerate() {
+        content_extent(child, child_id(node_id, i), layouts, right, bottom);
+    }
+}
+
+/// Draw a vertical scrollbar in the column right of a scroll viewport.
+fn draw_scrollbar(region: &ScrollRegion, buffer: &mut OutputBuffer) {
+    let viewport = region.viewport;
+    if region.max_offset_y() == 0 || viewport.height == 0 {
+        return;
+    }
+
+    let track = u32::from(viewport.height);
+    let content = u32::from(region.content_height);
+    let thumb = (track * track / content).max(1);
+    let thumb_pos = u32::from(region.offset_y) * (track - thumb) / u32::from(region.max_offset_y());
+
+    let x = viewport.x + viewport.width;
+    for dy in 0..viewport.height {
+        let in_thumb = (thumb_pos..thumb_pos + thumb).contains(&u32::from(dy));
+        let c = match (in_thumb, buffer.unicode) {
+            (true, true) => '█',
+            (false, true) => '░',
+            (true, false) => '#',
+            (false, false) => '|',
+        };
+        buffer.set_styled_char(x, viewport.y + dy, c, Color::Default);
+    }
+}
+
+/// Derive the layout ID of the `index`-th child of `parent`.
+///
+/// Offset by one so the first child never collides with its parent.
+fn child_id(parent: u64, index: usize) -> u64 {
+    parent * 1000 + index as u64 + 1
+}
+
+/// Build the layout measure for a text node.
+fn text_measure(text_node: &TextNode) -> TextMeasure {
+    TextMeasure::new(text_node.content.clone(), text_node.style.wrap.unwrap_or_default())
+}
+
+/// Combine an all-sides shorthand with per-side overrides (top, right, bottom, left).
+fn resolve_edges(all: Option<u16>, sides: [Option<u16>; 4]) -> Edges {
+    let base = all.unwrap_or(0);
+    Edges {
+        top: sides[0].unwrap_or(base),
+        right: sides[1].unwrap_or(base),
+        bottom: sides[2].unwrap_or(base),
+        left: sides[3].unwrap_or(base),
+    }
+}
+
 /// Convert VNode to LayoutNode for layout calculation.
 fn vnode_to_layout_node(node: &VNode, id: u64) -> LayoutNode {
     match node {
@@ -583,6 +1856,9 @@ fn vnode_to_layout_node(node: &VNode, id: u64) -> LayoutNode {
             let mut layout = LayoutNode::new(node_id);
 
             // Apply style
+            if box_node.style.hidden == Some(true) {
+                layout.style.display = false;
+            }
             if let Some(fd) = box_node.style.flex_direction {
                 layout.style.flex_direction = fd;
             }
@@ -595,44 +1871,128 @@ fn vnode_to_layout_node(node: &VNode, id: u64) -> LayoutNode {
             if let Some(gap) = box_node.style.gap {
                 layout.style.gap = gap;
             }
+            if let Some(ac) = box_node.style.align_content {
+                layout.style.align_content = ac;
+            }
+            if let Some(wrap) = box_node.style.flex_wrap {
+                layout.style.flex_wrap = wrap;
+            }
+            layout.style.row_gap = box_node.style.row_gap;
+            layout.style.column_gap = box_node.style.column_gap;
+
+            // Grid
+            if let Some(display) = box_node.style.display {
+                layout.style.display_mode = display;
+            }
+            if let Some(columns) = &box_node.style.grid_template_columns {
+                layout.style.grid_template_columns.clone_from(columns);
+            }
+            if let Some(rows) = &box_node.style.grid_template_rows {
+                layout.style.grid_template_rows.clone_from(rows);
+            }
+            if let Some(areas) = &box_node.style.grid_template_areas {
+                layout.style.grid_template_areas.clone_from(areas);
+            }
+            if let Some(auto_rows) = box_node.style.grid_auto_rows {
+                layout.style.grid_auto_rows = auto_rows;
+            }
+            if let Some(column) = box_node.style.grid_column {
+                layout.style.grid_column = column;
+            }
+            if let Some(row) = box_node.style.grid_row {
+                layout.style.grid_row = row;
+            }
+            layout.style.grid_area.clone_from(&box_node.style.grid_area);
+
+            // Flex item
             if let Some(fg) = box_node.style.flex_grow {
                 layout.style.flex_grow = fg;
             }
+            if let Some(fs) = box_node.style.flex_shrink {
+                layout.style.flex_shrink = fs;
+            }
+            if let Some(basis) = box_node.style.flex_basis {
+                layout.style.flex_basis = basis;
+            }
+            if let Some(align) = box_node.style.align_self {
+                layout.style.align_self = align;
+            }
+
+            // Sizing
             if let Some(w) = box_node.style.width {
                 layout.style.width = w;
             }
             if let Some(h) = box_node.style.height {
                 layout.style.height = h;
             }
+            layout.style.min_width = box_node.style.min_width;
+            layout.style.min_height = box_node.style.min_height;
+            layout.style.max_width = box_node.style.max_width;
+            layout.style.max_height = box_node.style.max_height;
 
-            // Padding
-            if let Some(p) = box_node.style.padding {
-                layout.style.padding = crate::core::layout::Edges::all(p);
-            }
+            // Spacing: per-side values override the shorthand
+            let style = &box_node.style;
+            layout.style.padding = resolve_edges(
+                style.padding,
+                [style.padding_top, style.padding_right, style.padding_bottom, style.padding_left],
+            );
+            layout.style.margin = resolve_edges(
+                style.margin,
+                [style.margin_top, style.margin_right, style.margin_bottom, style.margin_left],
+            );
 
-            // Border
-            if box_node.style.border_style.is_some() {
+            // Border; a partial border is laid out as padding on its sides
+            let border = border_edges(&box_node.style);
+            if border == Edges::all(1) {
                 layout.style.border_width = 1;
+            } else {
+                layout.style.padding.top += border.top;
+                layout.style.padding.right += border.right;
+                layout.style.padding.bottom += border.bottom;
+                layout.style.padding.left += border.left;
+            }
+
+            if let Some(overflow) = box_node.style.overflow {
+                layout.style.overflow = overflow;
+            }
+            // Keep the scrollbar's column clear of content
+            if has_scrollbar(&box_node.style) {
+                layout.style.padding.right += 1;
             }
 
+            // Position
+            if let Some(position) = box_node.style.position {
+                layout.style.position = position;
+            }
+            layout.style.position_x = box_node.style.left;
+            layout.style.position_y = box_node.style.top;
+            layout.style.position_right = box_node.style.right;
+            layout.style.position_bottom = box_node.style.bottom;
+
             // Children
             for (i, child) in box_node.children.iter().enumerate() {
-                layout.children.push(vnode_to_layout_node(child, node_id * 1000 + i as u64));
+                layout.children.push(vnode_to_layout_node(child, child_id(node_id, i)));
             }
 
             layout
         }
-        VNode::Text(text_node) => {
-            let width = text_node.content.chars().count() as u16;
-            LayoutNode::text(id, width, 1)
-        }
+        VNode::Text(text_node) => LayoutNode::measured(id, text_measure(text_node)),
         VNode::Spacer(spacer) => {
             LayoutNode::text(id, spacer.x, spacer.y.max(1))
         }
+        VNode::Image(image_node) => {
+            // Images keep their size rather than stretching or shrinking
+            let (width, height) = image_node.cell_size();
+            let mut layout = LayoutNode::text(id, width, height);
+            layout.style.width = Size::Fixed(width);
+            layout.style.height = Size::Fixed(height);
+            layout.style.flex_shrink = 0.0;
+            layout
+        }
         VNode::Fragment(children) => {
             let mut layout = LayoutNode::new(id);
             for (i, child) in children.iter().enumerate() {
-                layout.children.push(vnode_to_layout_node(child, id * 1000 + i as u64));
+                layout.children.push(vnode_to_layout_node(child, child_id(id, i)));
             }
             layout
         }
@@ -660,10 +2020,695 @@ mod tests {
         assert_eq!(buffer.get(4, 0).map(|c| c.char), Some('o'));
     }
 
+    #[test]
+    fn test_to_ansi_string_downsamples_colors() {
+        let mut buffer = OutputBuffer::new(2, 1);
+        buffer.set(0, 0, Cell { char: 'x', fg: Color::Rgb(255, 0, 0), bold: true, ..Cell::default() });
+
+        assert!(buffer.to_ansi_string(ColorSupport::TrueColor).contains("\x1B[38;2;255;0;0m"));
+        assert!(buffer.to_ansi_string(ColorSupport::Colors256).contains("\x1B[38;5;196m"));
+        assert!(buffer.to_ansi_string(ColorSupport::Colors16).contains("\x1B[91m"));
+
+        let plain = buffer.to_ansi_string(ColorSupport::None);
+        assert!(plain.contains("\x1B[1m"));
+        assert!(!plain.contains("38;"));
+        assert!(!plain.contains("\x1B[91m"));
+    }
+
+    #[test]
+    fn test_underline_styles() {
+        use crate::core::capabilities::set_styled_underlines_supported;
+
+        let style = TextStyle {
+            underline: true,
+            underline_style: Some(UnderlineStyle::Curly),
+            underline_color: Some(Color::Rgb(255, 0, 0)),
+            ..Default::default()
+        };
+        let mut buffer = OutputBuffer::new(3, 1);
+        buffer.write_str(0, 0, "ab", &style);
+        buffer.write_str(2, 0, "c", &TextStyle { italic: true, ..Default::default() });
+
+        set_styled_underlines_supported(true);
+        let output = buffer.to_ansi_string(ColorSupport::Colors256);
+        assert!(output.contains("\x1B[4:3m\x1B[58;5;196mab"));
+        // Style changes beyond color, bold and dim are picked up
+        assert!(output.contains("\x1B[0m\x1B[3mc"));
+
+        set_styled_underlines_supported(false);
+        let output = buffer.to_ansi_string(ColorSupport::Colors256);
+        assert!(output.contains("\x1B[4mab"));
+        assert!(!output.contains("58;"));
+    }
+
+    #[test]
+    fn test_hyperlink_spans() {
+        let style = TextStyle { hyperlink: Some("https://a.example/\x1Bx".to_string()), ..Default::default() };
+        let mut buffer = OutputBuffer::new(4, 2);
+        buffer.write_str(1, 0, "ab", &style);
+        buffer.write_str(3, 0, "c", &style);
+        buffer.write_str(0, 1, "d", &style);
+
+        let output = buffer.to_ansi_string(ColorSupport::None);
+        let open = "\x1B]8;;https://a.example/x\x1B\\";
+        let close = "\x1B]8;;\x1B\\";
+        // One span per line, closed before the line break and at the end
+        assert_eq!(output.matches(open).count(), 2);
+        assert_eq!(output.matches(close).count(), 2);
+        assert!(output.contains(&format!("{open}abc{close}\n")));
+        assert!(output.contains(&format!("{open}d{close}   ")));
+    }
+
     #[test]
     fn test_border_chars() {
         let chars = get_border_chars(BorderStyle::Round);
         assert_eq!(chars.top_left, '╭');
         assert_eq!(chars.top_right, '╮');
     }
+
+    #[test]
+    fn test_render_wrapped_and_truncated_text() {
+        use crate::core::component::{BoxNode, BoxStyle, WrapMode};
+        use crate::core::layout::Size;
+
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                width: Some(Size::Fixed(8)),
+                ..BoxStyle::column()
+            },
+            children: vec![
+                VNode::styled_text("one two three", TextStyle { wrap: Some(WrapMode::Word), ..Default::default() }),
+                VNode::styled_text("truncated text", TextStyle { wrap: Some(WrapMode::Truncate), ..Default::default() }),
+            ],
+            ..Default::default()
+        }));
+
+        let buffer = render_test_buffer(&node, 8, 5);
+        assert_eq!(row_text(&buffer, 0), "one two ");
+        assert_eq!(row_text(&buffer, 1), "three   ");
+        assert_eq!(row_text(&buffer, 2), "truncat…");
+    }
+
+    #[test]
+    fn test_render_wide_text_in_bordered_box() {
+        use crate::core::component::{BoxNode, BoxStyle, WrapMode};
+        use crate::core::layout::Size;
+
+        let text = |wrap| VNode::styled_text("漢字漢字漢字", TextStyle { wrap: Some(wrap), ..Default::default() });
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                width: Some(Size::Fixed(10)),
+                ..BoxStyle::column().with_border(BorderStyle::Single)
+            },
+            children: vec![text(WrapMode::Char), text(WrapMode::Truncate)],
+            ..Default::default()
+        }));
+
+        let buffer = render_test_buffer(&node, 10, 5);
+        assert_eq!(
+            buffer.to_plain_string(),
+            "┌────────┐\n│漢字漢字│\n│漢字    │\n│漢字漢… │\n└────────┘"
+        );
+        // Each wide character fills its cell and the one after it
+        assert_eq!(buffer.get(1, 1).map(|c| c.char), Some('漢'));
+        assert!(buffer.get(2, 1).is_some_and(Cell::is_continuation));
+        assert_eq!(buffer.get(9, 1).map(|c| c.char), Some('│'));
+    }
+
+    fn render_test_buffer(node: &VNode, width: u16, height: u16) -> OutputBuffer {
+        let layout_node = vnode_to_layout_node(node, 0);
+        let layouts = crate::core::layout::calculate_layout(&layout_node, width, height);
+        let mut buffer = OutputBuffer::new(width, height);
+        buffer.set_unicode(true);
+        render_vnode_to_buffer(node, &layouts, 0, &mut buffer);
+        buffer
+    }
+
+    fn row_text(buffer: &OutputBuffer, y: u16) -> String {
+        (0..buffer.size().0).filter_map(|x| buffer.output_char(x, y)).collect()
+    }
+
+    fn lines_box(id: u64, overflow: Overflow, lines: usize) -> VNode {
+        use crate::core::component::BoxStyle;
+        use crate::core::layout::Size;
+
+        VNode::Box(Box::new(BoxNode {
+            id: Some(id),
+            style: BoxStyle {
+                width: Some(Size::Fixed(6)),
+                height: Some(Size::Fixed(4)),
+                overflow: Some(overflow),
+                ..BoxStyle::column().with_border(BorderStyle::Single)
+            },
+            children: (0..lines).map(|i| VNode::text(format!("row{i}-"))).collect(),
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn test_overflow_hidden_clips_children() {
+        // Without clipping, content draws over the border
+        let visible = render_test_buffer(&lines_box(1, Overflow::Visible, 4), 6, 6);
+        assert_eq!(row_text(&visible, 1), "│row0-");
+        assert_eq!(row_text(&visible, 3), "└row2-");
+
+        let hidden = render_test_buffer(&lines_box(2, Overflow::Hidden, 4), 6, 6);
+        assert_eq!(row_text(&hidden, 1), "│row0│");
+        assert_eq!(row_text(&hidden, 3), "└────┘");
+        assert_eq!(row_text(&hidden, 4), "      ");
+    }
+
+    #[test]
+    fn test_overflow_scroll_offset_and_wheel() {
+        let node = lines_box(3, Overflow::Scroll, 6);
+        let buffer = render_test_buffer(&node, 6, 6);
+        assert_eq!(row_text(&buffer, 1), "│row0│");
+        let region = scroll_region(3).unwrap();
+        assert_eq!(region.content_height, 6);
+        assert_eq!(region.max_offset_y(), 4);
+
+        let wheel = MouseEvent {
+            x: 2,
+            y: 2,
+            kind: MouseEventKind::ScrollDown,
+            modifiers: crate::core::terminal::KeyModifiers::NONE,
+        };
+        assert!(handle_scroll_event(&wheel));
+        assert_eq!(scroll_region(3).unwrap().offset_y, SCROLL_STEP);
+
+        let buffer = render_test_buffer(&node, 6, 6);
+        assert_eq!(row_text(&buffer, 1), "│row3│");
+        assert_eq!(row_text(&buffer, 2), "│row4│");
+        assert_eq!(row_text(&buffer, 3), "└────┘");
+
+        // Offsets are clamped to the content
+        scroll_by(3, 0, 100);
+        assert_eq!(scroll_region(3).unwrap().offset_y, 4);
+    }
+
+    #[test]
+    fn test_scroll_content_follows_ascii_mode() {
+        use crate::core::component::BoxStyle;
+
+        let node = VNode::Box(Box::new(BoxNode {
+            id: Some(6),
+            style: BoxStyle {
+                overflow: Some(Overflow::Scroll),
+                ..BoxStyle::column().with_border(BorderStyle::Single)
+            },
+            children: vec![VNode::text("a─b")],
+            ..Default::default()
+        }));
+        let layouts = crate::core::layout::calculate_layout(&vnode_to_layout_node(&node, 0), 6, 4);
+        let mut buffer = OutputBuffer::new(6, 4);
+        buffer.set_unicode(false);
+        render_vnode_to_buffer(&node, &layouts, 0, &mut buffer);
+        assert_eq!(row_text(&buffer, 1), "|a-b |");
+    }
+
+    #[test]
+    fn test_scrollbar() {
+        use crate::core::component::BoxStyle;
+        use crate::core::layout::Size;
+
+        let node = VNode::Box(Box::new(BoxNode {
+            id: Some(4),
+            style: BoxStyle {
+                width: Some(Size::Fixed(4)),
+                height: Some(Size::Fixed(2)),
+                overflow: Some(Overflow::Scroll),
+                scroll_y: Some(2),
+                scrollbar: Some(true),
+                ..BoxStyle::column()
+            },
+            children: (0..4)
+                .map(|i| {
+                    VNode::Box(Box::new(BoxNode {
+                        style: BoxStyle {
+                            justify_content: Some(crate::core::layout::JustifyContent::FlexEnd),
+                            ..BoxStyle::row()
+                        },
+                        children: vec![VNode::text(format!("{i}"))],
+                        ..Default::default()
+                    }))
+                })
+                .collect(),
+            ..Default::default()
+        }));
+        // Content keeps clear of the scrollbar's column
+        let buffer = render_test_buffer(&node, 4, 2);
+        assert_eq!(row_text(&buffer, 0), "  2░");
+        assert_eq!(row_text(&buffer, 1), "  3█");
+        assert_eq!(scroll_region(4).unwrap().viewport.width, 3);
+    }
+
+    #[test]
+    fn test_removed_scroll_boxes_are_forgotten() {
+        let node = lines_box(5, Overflow::Scroll, 6);
+        render_to_buffer(&node, &RenderContext::new(6, 6));
+        assert!(scroll_region(5).is_some());
+
+        let wheel = MouseEvent {
+            x: 2,
+            y: 2,
+            kind: MouseEventKind::ScrollDown,
+            modifiers: crate::core::terminal::KeyModifiers::NONE,
+        };
+        render_to_buffer(&VNode::text("gone"), &RenderContext::new(6, 6));
+        assert_eq!(scroll_region(5), None);
+        assert!(!handle_scroll_event(&wheel));
+    }
+
+    #[test]
+    fn test_layers_draw_by_z_index() {
+        use crate::core::component::BoxStyle;
+
+        let layer = |z: i32, left: u16, label: &str| {
+            VNode::Box(Box::new(BoxNode {
+                style: BoxStyle {
+                    position: Some(Position::Absolute),
+                    left: Some(left),
+                    top: Some(0),
+                    z_index: Some(z),
+                    ..Default::default()
+                },
+                children: vec![VNode::text(label)],
+                ..Default::default()
+            }))
+        };
+        // Declared first but with the higher z-index, so it ends up on top
+        let node = VNode::Box(Box::new(BoxNode {
+            children: vec![layer(2, 2, "BBB"), layer(1, 0, "AAAA"), VNode::text("........")],
+            ..Default::default()
+        }));
+
+        let buffer = render_test_buffer(&node, 8, 1);
+        assert_eq!(row_text(&buffer, 0), "AABBB...");
+    }
+
+    #[test]
+    fn test_absolute_escapes_overflow_hidden() {
+        use crate::core::component::BoxStyle;
+        use crate::core::layout::Size;
+
+        let popup = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                position: Some(Position::Absolute),
+                top: Some(2),
+                left: Some(0),
+                ..Default::default()
+            },
+            children: vec![VNode::text("popup")],
+            ..Default::default()
+        }));
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                width: Some(Size::Fixed(6)),
+                height: Some(Size::Fixed(1)),
+                overflow: Some(Overflow::Hidden),
+                position: Some(Position::Relative),
+                ..Default::default()
+            },
+            children: vec![VNode::text("field"), popup],
+            ..Default::default()
+        }));
+
+        let buffer = render_test_buffer(&node, 6, 3);
+        assert_eq!(row_text(&buffer, 0), "field ");
+        assert_eq!(row_text(&buffer, 2), "popup ");
+    }
+
+    #[test]
+    fn test_box_style_spacing_reaches_layout() {
+        use crate::core::component::BoxStyle;
+
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                margin_left: Some(2),
+                padding: Some(1),
+                padding_left: Some(0),
+                ..BoxStyle::row()
+            },
+            children: vec![VNode::text("ab")],
+            ..Default::default()
+        }));
+
+        let buffer = render_test_buffer(&node, 8, 3);
+        assert_eq!(row_text(&buffer, 1), "  ab    ");
+    }
+
+    #[test]
+    fn test_ascii_mode_borders_and_glyphs() {
+        use crate::core::component::BoxStyle;
+
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                border_style: Some(BorderStyle::Round),
+                ..Default::default()
+            },
+            children: vec![VNode::text("a…→")],
+            ..Default::default()
+        }));
+        let layout_node = vnode_to_layout_node(&node, 0);
+        let layouts = crate::core::layout::calculate_layout(&layout_node, 5, 3);
+        let mut buffer = OutputBuffer::new(5, 3);
+        buffer.set_unicode(false);
+        render_vnode_to_buffer(&node, &layouts, 0, &mut buffer);
+
+        assert_eq!(row_text(&buffer, 0), "+---+");
+        assert_eq!(row_text(&buffer, 1), "|a.>|");
+        assert_eq!(row_text(&buffer, 2), "+---+");
+    }
+
+    #[test]
+    fn test_render_to_text_natural_height() {
+        use crate::core::component::BoxStyle;
+
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                border_style: Some(BorderStyle::Single),
+                ..BoxStyle::column()
+            },
+            children: vec![VNode::text("one"), VNode::text("two")],
+            ..Default::default()
+        }));
+        let options = TextRenderOptions::new().width(7).ansi(false);
+        assert_eq!(render_to_text(&node, &options), "┌─────┐\n│one  │\n│two  │\n└─────┘");
+
+        // Trailing blank rows are dropped, styling is kept on request
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle::column(),
+            children: vec![VNode::text("hi"), VNode::text("")],
+            ..Default::default()
+        }));
+        assert_eq!(render_to_text(&node, &options), "hi");
+        let styled = render_to_text(&node, &TextRenderOptions::new().width(4).ansi(true));
+        assert!(styled.starts_with("hi") && styled.ends_with("\x1B[0m") && !styled.contains('\n'));
+    }
+
+    #[test]
+    fn test_render_to_text_ansi_ignores_terminal_colors() {
+        use crate::core::capabilities::set_color_support;
+        use crate::core::component::{BoxStyle, NamedColor};
+
+        // As when stdout is a pipe
+        set_color_support(ColorSupport::None);
+        let red = TextStyle { color: Some(Color::Named(NamedColor::Red)), ..Default::default() };
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle::column(),
+            children: vec![VNode::styled_text("hot", red), VNode::text("ok")],
+            ..Default::default()
+        }));
+
+        let options = TextRenderOptions::new().width(6).ansi(true);
+        let styled = render_to_text(&node, &options);
+        assert_eq!(styled, "\x1B[0m\x1B[31mhot\n\x1B[0mok\x1B[0m");
+        let plain = render_to_text(&node, &options.color_support(ColorSupport::None));
+        assert!(!plain.contains("31m"));
+    }
+
+    #[test]
+    fn test_border_titles_sides_and_junctions() {
+        use crate::core::component::BoxStyle;
+        use crate::core::layout::Size;
+
+        let pane = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                width: Some(Size::Fixed(5)),
+                border_style: Some(BorderStyle::Single),
+                border_top: Some(false),
+                border_bottom: Some(false),
+                border_left: Some(false),
+                border_right_color: Some(Color::Named(NamedColor::Red)),
+                ..Default::default()
+            },
+            children: vec![VNode::text("abc")],
+            ..Default::default()
+        }));
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle::row()
+                .with_border(BorderStyle::Single)
+                .with_title(BorderTitle::new("Hi").right().bottom()),
+            children: vec![pane, VNode::text("def")],
+            ..Default::default()
+        }));
+
+        let buffer = render_test_buffer(&node, 13, 4);
+        assert_eq!(row_text(&buffer, 0), "┌────┬──────┐");
+        assert_eq!(row_text(&buffer, 1), "│abc │def   │");
+        assert_eq!(row_text(&buffer, 2), "│    │      │");
+        assert_eq!(row_text(&buffer, 3), "└────┴─ Hi ─┘");
+        assert_eq!(buffer.get(5, 1).unwrap().fg, Color::Named(NamedColor::Red));
+
+        // A bottom-only border splits a column into rows
+        let row = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                border_style: Some(BorderStyle::Single),
+                border_top: Some(false),
+                border_right: Some(false),
+                border_left: Some(false),
+                ..Default::default()
+            },
+            children: vec![VNode::text("a")],
+            ..Default::default()
+        }));
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle::column().with_border(BorderStyle::Single).with_title(BorderTitle::new("T").center()),
+            children: vec![row, VNode::text("b")],
+            ..Default::default()
+        }));
+
+        let buffer = render_test_buffer(&node, 7, 5);
+        assert_eq!(row_text(&buffer, 0), "┌─ T ─┐");
+        assert_eq!(row_text(&buffer, 1), "│a    │");
+        assert_eq!(row_text(&buffer, 2), "├─────┤");
+        assert_eq!(row_text(&buffer, 3), "│b    │");
+    }
+
+    #[test]
+    fn test_border_junctions_across_clips_and_siblings() {
+        use crate::core::component::BoxStyle;
+        use crate::primitives::row;
+
+        // The parent's frame lies outside the clip of its hidden overflow
+        let pane = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                width: Some(Size::Fixed(5)),
+                border_style: Some(BorderStyle::Single),
+                border_top: Some(false),
+                border_bottom: Some(false),
+                border_left: Some(false),
+                ..Default::default()
+            },
+            children: vec![VNode::text("abc")],
+            ..Default::default()
+        }));
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle { overflow: Some(Overflow::Hidden), ..BoxStyle::row().with_border(BorderStyle::Single) },
+            children: vec![pane, VNode::text("def")],
+            ..Default::default()
+        }));
+        let buffer = render_test_buffer(&node, 13, 3);
+        assert_eq!(row_text(&buffer, 0), "┌────┬──────┐");
+        assert_eq!(row_text(&buffer, 2), "└────┴──────┘");
+
+        // Fully bordered siblings share the edge between them
+        let node = row()
+            .collapse_borders()
+            .children([
+                row().border_single().width(5).child(VNode::text("a")).build(),
+                row().border_single().width(5).child(VNode::text("b")).build(),
+            ])
+            .build();
+        let buffer = render_test_buffer(&node, 10, 3);
+        assert_eq!(row_text(&buffer, 0), "┌───┬────┐");
+        assert_eq!(row_text(&buffer, 1), "│a  │b   │");
+        assert_eq!(row_text(&buffer, 2), "└───┴────┘");
+
+        // Titles are cut to the columns they take up, not their characters
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle::row().with_border(BorderStyle::Single).with_title(BorderTitle::new("漢字漢字").right()),
+            ..Default::default()
+        }));
+        let buffer = render_test_buffer(&node, 10, 2);
+        assert_eq!(row_text(&buffer, 0), "┌── 漢字─┐");
+        assert!(buffer.get(5, 0).is_some_and(Cell::is_continuation));
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle::row().with_border(BorderStyle::Single).with_title(BorderTitle::new("漢").center()),
+            ..Default::default()
+        }));
+        let buffer = render_test_buffer(&node, 10, 2);
+        assert_eq!(row_text(&buffer, 0), "┌── 漢 ──┐");
+    }
+
+    #[test]
+    fn test_focused_input_places_cursor() {
+        use crate::atoms::TextInput;
+
+        let context = RenderContext::new(12, 3);
+        let input = TextInput::new().value("hello").focused(true).cursor(2).build();
+        let cursor = render_to_buffer(&input, &context).cursor().unwrap();
+        assert_eq!((cursor.x, cursor.y), (3, 1));
+        assert_eq!((cursor.shape, cursor.blinking), (CursorShape::Bar, true));
+
+        // Caret at the end sits after the last character; unfocused shows none
+        let input = TextInput::new().value("hello").focused(true).cursor_shape(CursorShape::Block).build();
+        let cursor = render_to_buffer(&input, &context).cursor().unwrap();
+        assert_eq!((cursor.x, cursor.y, cursor.shape), (6, 1, CursorShape::Block));
+        assert!(render_to_buffer(&TextInput::new().value("hello").build(), &context).cursor().is_none());
+
+        // Wide characters before the caret take two columns each
+        let input = TextInput::new().value("漢字ab").focused(true).cursor(3).build();
+        let buffer = render_to_buffer(&input, &context);
+        let cursor = buffer.cursor().unwrap();
+        assert_eq!((cursor.x, cursor.y), (6, 1));
+        let cell = |x| buffer.get(x, 1).unwrap();
+        assert_eq!((cell(1).char, cell(3).char, cell(5).char, cell(6).char), ('漢', '字', 'a', 'b'));
+        assert!(cell(2).is_continuation() && cell(4).is_continuation());
+        // On a wide character the caret covers its first cell
+        let input = TextInput::new().value("漢字ab").focused(true).cursor(1).build();
+        let buffer = render_to_buffer(&input, &context);
+        let cursor = buffer.cursor().unwrap();
+        assert_eq!(buffer.get(cursor.x, cursor.y).map(|c| c.char), Some('字'));
+        assert_eq!(caret_cell(&["日本".to_string(), "語".to_string()], 3), (2, 1));
+        assert_eq!(CursorShape::Underline.decscusr(true), 3);
+    }
+
+    #[test]
+    fn test_image_protocols_and_fallback() {
+        use crate::core::graphics::RgbaImage;
+        use crate::primitives::Image;
+
+        let pixels = RgbaImage::from_fn(4, 4, |_, y| if y < 2 { [255, 0, 0, 255] } else { [0, 0, 255, 255] });
+        let node = |protocol| {
+            VNode::Box(Box::new(BoxNode {
+                style: BoxStyle { padding_left: Some(1), ..BoxStyle::column() },
+                children: vec![Image::new(pixels.clone()).width(2).height(2).protocol(protocol).build()],
+                ..Default::default()
+            }))
+        };
+        let context = RenderContext::new(4, 3);
+
+        // Kitty draws over blank cells, moving up from the bottom-right one
+        let buffer = render_to_buffer(&node(GraphicsProtocol::Kitty), &context);
+        let graphic = &buffer.graphics()[0];
+        assert_eq!((graphic.x, graphic.y, graphic.width, graphic.height), (1, 0, 2, 2));
+        let output = buffer.to_ansi_string(ColorSupport::TrueColor);
+        assert!(output.contains("\x1B7\r\x1B[1A\x1B[1C\x1B_Ga=t,f=32,"));
+        assert!(output.contains(",C=1,q=2\x1B\\\x1B8"));
+        assert!(buffer.to_plain_string().trim().is_empty());
+
+        // Frames send the pixels once, then only place the image by ID
+        let mut images = KittyImages::new();
+        let first = buffer.to_frame_string(ColorSupport::TrueColor, &mut images);
+        let second = buffer.to_frame_string(ColorSupport::TrueColor, &mut images);
+        assert!(first.contains("a=t,") && first.contains("a=p,"));
+        assert!(!second.contains("a=t,") && second.contains("a=p,"));
+        // and delete it once it has left the tree
+        let empty = OutputBuffer::new(4, 3).to_frame_string(ColorSupport::TrueColor, &mut images);
+        assert!(empty.starts_with("\x1B_Ga=d,d=I,"));
+
+        // Sixel needs a row below it, so a full-height image falls back
+        let buffer = render_to_buffer(&node(GraphicsProtocol::Sixel), &RenderContext::new(4, 2));
+        assert!(buffer.graphics().is_empty());
+        let cell = buffer.get(1, 0).unwrap();
+        assert_eq!((cell.char, cell.fg, cell.bg), ('\u{2580}', Color::Rgb(255, 0, 0), Color::Rgb(255, 0, 0)));
+        assert_eq!(buffer.get(2, 1).map(|c| c.bg), Some(Color::Rgb(0, 0, 255)));
+    }
+
+    #[test]
+    fn test_translucent_background_tints_beneath() {
+        let highlight = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle {
+                position: Some(Position::Absolute),
+                width: Some(Size::Fixed(2)),
+                height: Some(Size::Fixed(1)),
+                background: Some(Color::Rgb(255, 255, 255)),
+                opacity: Some(0.25),
+                ..Default::default()
+            },
+            ..Default::default()
+        }));
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle { background: Some(Color::Rgb(0, 0, 0)), ..BoxStyle::column() },
+            children: vec![VNode::text("abc"), highlight],
+            ..Default::default()
+        }));
+
+        let buffer = render_to_buffer(&node, &RenderContext::new(3, 1));
+        let cell = buffer.get(0, 0).unwrap();
+        assert_eq!((cell.char, cell.bg), ('a', Color::Rgb(63, 63, 63)));
+        assert_eq!(buffer.get(2, 0).map(|c| c.bg), Some(Color::Rgb(0, 0, 0)));
+    }
+
+    #[test]
+    fn test_gradient_text_and_background() {
+        use crate::atoms::ProgressBar;
+        use crate::core::component::{Gradient, TextNode};
+
+        let red = Color::Rgb(255, 0, 0);
+        let blue = Color::Rgb(0, 0, 255);
+        let node = VNode::Box(Box::new(BoxNode {
+            style: BoxStyle { background_gradient: Some(Gradient::vertical(red, blue)), ..BoxStyle::column() },
+            children: vec![VNode::Text(TextNode {
+                content: "abc".into(),
+                style: TextStyle { gradient: Some(Gradient::horizontal(red, blue)), ..Default::default() },
+                ..Default::default()
+            })],
+            ..Default::default()
+        }));
+        let buffer = render_to_buffer(&node, &RenderContext::new(4, 3));
+        let fg: Vec<Color> = (0..3).map(|x| buffer.get(x, 0).unwrap().fg).collect();
+        assert_eq!(fg, [red, Color::Rgb(127, 0, 127), blue]);
+        let bg: Vec<Color> = (0..3).map(|y| buffer.get(3, y).unwrap().bg).collect();
+        assert_eq!(bg, [red, Color::Rgb(127, 0, 127), blue]);
+
+        // Wide characters take up two columns of the gradient
+        let node = VNode::Text(TextNode {
+            content: "漢a".into(),
+            style: TextStyle { gradient: Some(Gradient::horizontal(red, blue)), ..Default::default() },
+            ..Default::default()
+        });
+        let buffer = render_to_buffer(&node, &RenderContext::new(4, 1));
+        assert_eq!((buffer.get(0, 0).unwrap().fg, buffer.get(2, 0).unwrap().fg), (red, blue));
+
+        // HSL keeps full saturation halfway between red and blue
+        assert_eq!(Gradient::horizontal(red, blue).hsl().at(0.5), Color::Rgb(255, 0, 255));
+        assert_eq!(Color::Rgb(127, 0, 127).downsample(ColorSupport::Colors256), Color::Ansi256(90));
+
+        // A half-full bar only reaches the middle of its gradient
+        let bar = ProgressBar::new().value(50.0).width(5).show_percentage(false).gradient(Gradient::horizontal(red, blue));
+        let buffer = render_to_buffer(&bar.build(), &RenderContext::new(5, 1));
+        assert_eq!(buffer.get(1, 0).map(|c| c.fg), Some(Color::Rgb(191, 0, 63)));
+        // The empty track keeps the bar's color
+        assert_eq!(buffer.get(2, 0).map(|c| c.fg), Some(Color::Named(NamedColor::Cyan)));
+    }
+
+    #[test]
+    fn test_rich_text_spans_wrap_as_one() {
+        use crate::primitives::Text;
+
+        let text = Text::markup("[bold red]error[/] in [cyan]main[/]").wrap_word().build();
+        let buffer = render_to_buffer(&text, &RenderContext::new(9, 2));
+        assert_eq!(buffer.to_plain_string().lines().map(str::trim_end).collect::<Vec<_>>(), ["error in", "main"]);
+
+        let red = Color::Named(NamedColor::Red);
+        let cyan = Color::Named(NamedColor::Cyan);
+        let cell = |x, y| buffer.get(x, y).unwrap();
+        assert!(cell(0, 0).bold && cell(4, 0).bold && cell(0, 0).fg == red);
+        assert!(!cell(6, 0).bold && cell(6, 0).fg == Color::Default);
+        assert_eq!((0..4).map(|x| cell(x, 1).fg).collect::<Vec<_>>(), [cyan; 4]);
+    }
+
+    #[test]
+    fn test_control_characters_never_reach_output() {
+        let mut buffer = OutputBuffer::new(12, 1);
+        buffer.write_str(0, 0, "\x1B]0;pwned\x07", &TextStyle::default());
+        assert_eq!(buffer.get(0, 0).map(|cell| cell.char), Some('␛'));
+        buffer.set_char(11, 0, '\u{9B}');
+
+        let output = buffer.to_ansi_string(ColorSupport::TrueColor);
+        assert!(output.contains("␛]0;pwned␇") && output.contains('�'));
+        assert!(!output.contains('\x07') && !output.contains('\u{9B}'));
+    }
 }
@@ -0,0 +1,205 @@
+//! Responsive Breakpoints
+//!
+//! Lets layouts adapt to the terminal size. The current viewport is kept in
+//! a signal that the app updates on resize, so anything built from it is
+//! re-evaluated on the next redraw:
+//!
+//! ```ignore
+//! let narrow = Breakpoint::width_below(60);
+//! box_()
+//!     .row()
+//!     .responsive(narrow, |b| b.column())
+//!     .child(box_().width(24).hidden_when(Breakpoint::width_below(100)))
+//!     .child(content)
+//! ```
+
+use std::cell::RefCell;
+
+use crate::core::signals::{create_signal, ReadSignal, WriteSignal};
+
+// =============================================================================
+// Viewport
+// =============================================================================
+
+type ViewportSignal = (ReadSignal<(u16, u16)>, WriteSignal<(u16, u16)>);
+
+thread_local! {
+    static VIEWPORT: RefCell<Option<ViewportSignal>> = const { RefCell::new(None) };
+}
+
+/// Size assumed before an app reports the real terminal size.
+pub const DEFAULT_VIEWPORT: (u16, u16) = (80, 24);
+
+fn viewport_signal() -> ViewportSignal {
+    VIEWPORT.with(|v| {
+        v.borrow_mut()
+            .get_or_insert_with(|| create_signal(DEFAULT_VIEWPORT))
+            .clone()
+    })
+}
+
+/// Current viewport size (columns, rows).
+///
+/// Reading it inside an effect or memo tracks it as a dependency.
+pub fn viewport() -> (u16, u16) {
+    viewport_signal().0.get()
+}
+
+/// The viewport as a signal, for effects that react to resizes.
+pub fn viewport_reader() -> ReadSignal<(u16, u16)> {
+    viewport_signal().0
+}
+
+/// Update the viewport size. Called by the app on start-up and resize.
+pub fn set_viewport(width: u16, height: u16) {
+    let (read, write) = viewport_signal();
+    if read.get_untracked() != (width, height) {
+        write.set((width, height));
+    }
+}
+
+// =============================================================================
+// Breakpoint
+// =============================================================================
+
+/// A range of viewport sizes, like a CSS media query.
+///
+/// Lower bounds are inclusive and upper bounds exclusive, so
+/// `width_below(100)` and `width_at_least(100)` never overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Breakpoint {
+    /// Minimum width in columns (inclusive)
+    pub min_width: Option<u16>,
+    /// Width must be below this many columns
+    pub max_width: Option<u16>,
+    /// Minimum height in rows (inclusive)
+    pub min_height: Option<u16>,
+    /// Height must be below this many rows
+    pub max_height: Option<u16>,
+}
+
+impl Breakpoint {
+    /// Viewports narrower than `columns`.
+    pub fn width_below(columns: u16) -> Self {
+        Self { max_width: Some(columns), ..Default::default() }
+    }
+
+    /// Viewports at least `columns` wide.
+    pub fn width_at_least(columns: u16) -> Self {
+        Self { min_width: Some(columns), ..Default::default() }
+    }
+
+    /// Viewports shorter than `rows`.
+    pub fn height_below(rows: u16) -> Self {
+        Self { max_height: Some(rows), ..Default::default() }
+    }
+
+    /// Viewports at least `rows` tall.
+    pub fn height_at_least(rows: u16) -> Self {
+        Self { min_height: Some(rows), ..Default::default() }
+    }
+
+    /// Match only when both breakpoints match.
+    pub fn and(self, other: Breakpoint) -> Self {
+        let tighter_min = |a: Option<u16>, b: Option<u16>| a.max(b);
+        let tighter_max = |a: Option<u16>, b: Option<u16>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Self {
+            min_width: tighter_min(self.min_width, other.min_width),
+            max_width: tighter_max(self.max_width, other.max_width),
+            min_height: tighter_min(self.min_height, other.min_height),
+            max_height: tighter_max(self.max_height, other.max_height),
+        }
+    }
+
+    /// Check a size against this breakpoint.
+    pub fn matches(&self, width: u16, height: u16) -> bool {
+        self.min_width.map_or(true, |min| width >= min)
+            && self.max_width.map_or(true, |max| width < max)
+            && self.min_height.map_or(true, |min| height >= min)
+            && self.max_height.map_or(true, |max| height < max)
+    }
+
+    /// Check the current viewport against this breakpoint.
+    pub fn is_active(&self) -> bool {
+        let (width, height) = viewport();
+        self.matches(width, height)
+    }
+}
+
+// =============================================================================
+// Responsive Values
+// =============================================================================
+
+/// A value that changes with the viewport.
+///
+/// Later rules win when several match, like rules later in a stylesheet.
+#[derive(Debug, Clone)]
+pub struct Responsive<T> {
+    base: T,
+    rules: Vec<(Breakpoint, T)>,
+}
+
+impl<T: Clone> Responsive<T> {
+    /// Use `base` unless a breakpoint matches.
+    pub fn new(base: T) -> Self {
+        Self { base, rules: Vec::new() }
+    }
+
+    /// Use `value` when `breakpoint` matches.
+    pub fn at(mut self, breakpoint: Breakpoint, value: T) -> Self {
+        self.rules.push((breakpoint, value));
+        self
+    }
+
+    /// Resolve against a given size.
+    pub fn resolve_for(&self, width: u16, height: u16) -> T {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(breakpoint, _)| breakpoint.matches(width, height))
+            .map_or_else(|| self.base.clone(), |(_, value)| value.clone())
+    }
+
+    /// Resolve against the current viewport.
+    pub fn resolve(&self) -> T {
+        let (width, height) = viewport();
+        self.resolve_for(width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breakpoint_bounds() {
+        let narrow = Breakpoint::width_below(100);
+        assert!(narrow.matches(99, 24));
+        assert!(!narrow.matches(100, 24));
+        assert!(Breakpoint::width_at_least(100).matches(100, 24));
+
+        let small = narrow.and(Breakpoint::height_below(30)).and(Breakpoint::width_below(60));
+        assert!(small.matches(59, 29));
+        assert!(!small.matches(80, 29));
+        assert!(!small.matches(59, 30));
+    }
+
+    #[test]
+    fn test_responsive_value_follows_viewport() {
+        let columns = Responsive::new(3)
+            .at(Breakpoint::width_below(100), 2)
+            .at(Breakpoint::width_below(60), 1);
+
+        assert_eq!(columns.resolve_for(300, 50), 3);
+        assert_eq!(columns.resolve_for(80, 24), 2);
+        assert_eq!(columns.resolve_for(40, 24), 1);
+
+        set_viewport(50, 20);
+        assert_eq!(viewport(), (50, 20));
+        assert_eq!(columns.resolve(), 1);
+        set_viewport(DEFAULT_VIEWPORT.0, DEFAULT_VIEWPORT.1);
+    }
+}
@@ -7,10 +7,15 @@
 //! - Screen buffer management
 //! - Cursor control
 
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::io::{self, Read, Write, Stdout, stdin, stdout};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+use crate::core::queries::{self, QueryReply};
+use crate::core::component::CursorShape;
+
 #[cfg(unix)]
 use std::os::unix::io::AsRawFd;
 
@@ -21,11 +26,18 @@ use std::os::unix::io::AsRawFd;
 static RAW_MODE_ENABLED: AtomicBool = AtomicBool::new(false);
 static MOUSE_ENABLED: AtomicBool = AtomicBool::new(false);
 static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+/// Set by the SIGWINCH handler, taken by `read_event`
+static RESIZED: AtomicBool = AtomicBool::new(false);
 
 // Store original termios for restoration
 #[cfg(unix)]
 static mut ORIGINAL_TERMIOS: Option<libc::termios> = None;
 
+// SIGWINCH action replaced by the resize handler, restored with the termios
+#[cfg(unix)]
+static mut PREVIOUS_SIGWINCH: Option<libc::sigaction> = None;
+
 // =============================================================================
 // Terminal Struct
 // =============================================================================
@@ -104,6 +116,18 @@ impl Terminal {
         Ok(())
     }
 
+    /// Set the cursor shape (DECSCUSR).
+    pub fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) -> io::Result<()> {
+        write!(self.stdout, "\x1B[{} q", shape.decscusr(blinking))?;
+        Ok(())
+    }
+
+    /// Restore the terminal's default cursor shape.
+    pub fn reset_cursor_shape(&mut self) -> io::Result<()> {
+        write!(self.stdout, "\x1B[0 q")?;
+        Ok(())
+    }
+
     /// Save cursor position.
     pub fn save_cursor(&mut self) -> io::Result<()> {
         write!(self.stdout, "\x1B7")?;
@@ -153,6 +177,27 @@ impl Terminal {
         Ok(())
     }
 
+    /// Push Kitty keyboard protocol flags (`CSI > flags u`).
+    ///
+    /// Only send this once the terminal has answered the `CSI ? u` query;
+    /// the previous flags come back with [`Terminal::pop_keyboard_enhancements`].
+    pub fn push_keyboard_enhancements(&mut self, enhancements: KeyboardEnhancements) -> io::Result<()> {
+        if !KEYBOARD_ENHANCED.swap(true, Ordering::SeqCst) {
+            write!(self.stdout, "\x1B[>{}u", enhancements.bits())?;
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Pop the Kitty keyboard protocol flags pushed earlier.
+    pub fn pop_keyboard_enhancements(&mut self) -> io::Result<()> {
+        if KEYBOARD_ENHANCED.swap(false, Ordering::SeqCst) {
+            write!(self.stdout, "\x1B[<u")?;
+            self.flush()?;
+        }
+        Ok(())
+    }
+
     /// Enable raw mode.
     pub fn enable_raw_mode(&self) -> io::Result<()> {
         enable_raw_mode()
@@ -183,6 +228,7 @@ impl Default for Terminal {
 impl Drop for Terminal {
     fn drop(&mut self) {
         // Cleanup on drop
+        let _ = self.pop_keyboard_enhancements();
         let _ = self.disable_mouse();
         let _ = self.leave_alternate_screen();
         let _ = self.show_cursor();
@@ -223,11 +269,27 @@ pub fn enable_raw_mode() -> io::Result<()> {
         if libc::tcsetattr(fd, libc::TCSAFLUSH, &termios) != 0 {
             return Err(io::Error::last_os_error());
         }
+
+        // Resizes arrive as `TerminalEvent::Resize`; SA_RESTART keeps the
+        // signal from failing reads and writes elsewhere in the program
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_resize as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        let mut previous: libc::sigaction = std::mem::zeroed();
+        if libc::sigaction(libc::SIGWINCH, &action, &mut previous) == 0 {
+            PREVIOUS_SIGWINCH = Some(previous);
+        }
     }
 
     Ok(())
 }
 
+#[cfg(unix)]
+extern "C" fn on_resize(_signal: libc::c_int) {
+    RESIZED.store(true, Ordering::SeqCst);
+}
+
 /// Disable raw mode, restoring original settings.
 #[cfg(unix)]
 pub fn disable_raw_mode() -> io::Result<()> {
@@ -236,6 +298,10 @@ pub fn disable_raw_mode() -> io::Result<()> {
     }
 
     unsafe {
+        if let Some(previous) = PREVIOUS_SIGWINCH {
+            libc::sigaction(libc::SIGWINCH, &previous, std::ptr::null_mut());
+            PREVIOUS_SIGWINCH = None;
+        }
         if let Some(termios) = ORIGINAL_TERMIOS {
             let fd = stdin().as_raw_fd();
             if libc::tcsetattr(fd, libc::TCSAFLUSH, &termios) != 0 {
@@ -342,8 +408,12 @@ pub struct KeyModifiers {
     pub ctrl: bool,
     /// Alt/Option key
     pub alt: bool,
-    /// Meta/Super/Windows key
+    /// Meta key
     pub meta: bool,
+    /// Super/Windows/Command key
+    pub super_key: bool,
+    /// Hyper key, only reported with the Kitty keyboard protocol
+    pub hyper: bool,
 }
 
 impl KeyModifiers {
@@ -353,14 +423,46 @@ impl KeyModifiers {
         ctrl: false,
         alt: false,
         meta: false,
+        super_key: false,
+        hyper: false,
     };
 
+    /// Modifiers from the bits of an xterm or Kitty modifier parameter
+    /// (the parameter minus one): 1 Shift, 2 Alt, 4 Ctrl, 8 Super, 16 Hyper,
+    /// 32 Meta, as the Kitty protocol defines them; xterm's own "Meta" (8)
+    /// is read as Super too. Caps Lock and Num Lock (64, 128) are ignored.
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            shift: bits & 1 != 0,
+            alt: bits & 2 != 0,
+            ctrl: bits & 4 != 0,
+            super_key: bits & 8 != 0,
+            hyper: bits & 16 != 0,
+            meta: bits & 32 != 0,
+        }
+    }
+
     /// Check if any modifier is pressed.
     pub fn any(&self) -> bool {
-        self.shift || self.ctrl || self.alt || self.meta
+        self.shift || self.ctrl || self.alt || self.meta || self.super_key || self.hyper
     }
 }
 
+/// Whether a key event is a press, an auto-repeat or a release.
+///
+/// Terminals only report repeats and releases with the Kitty keyboard
+/// protocol's event types enabled; otherwise every event is a press.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum KeyEventKind {
+    /// Key pressed
+    #[default]
+    Press,
+    /// Key held down and repeating
+    Repeat,
+    /// Key released
+    Release,
+}
+
 /// Keyboard event.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeyEvent {
@@ -368,20 +470,53 @@ pub struct KeyEvent {
     pub key: Key,
     /// Active modifiers
     pub modifiers: KeyModifiers,
+    /// Press, repeat or release
+    pub kind: KeyEventKind,
 }
 
 impl KeyEvent {
     /// Create a new key event.
     pub fn new(key: Key, modifiers: KeyModifiers) -> Self {
-        Self { key, modifiers }
+        Self { key, modifiers, kind: KeyEventKind::Press }
     }
 
     /// Create a simple key event with no modifiers.
     pub fn simple(key: Key) -> Self {
-        Self {
-            key,
-            modifiers: KeyModifiers::NONE,
-        }
+        Self::new(key, KeyModifiers::NONE)
+    }
+
+    /// Set the event kind.
+    pub fn with_kind(mut self, kind: KeyEventKind) -> Self {
+        self.kind = kind;
+        self
+    }
+}
+
+/// Kitty keyboard protocol enhancements to ask for.
+///
+/// Terminals implementing the protocol report keys as `CSI code ; mods u`,
+/// which tells Ctrl+I from Tab, Ctrl+M from Enter and Escape from Alt
+/// sequences, and carries Super and Hyper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyboardEnhancements {
+    /// Report ambiguous keys unambiguously (flag 1)
+    pub disambiguate: bool,
+    /// Report key repeats and releases (flag 2)
+    pub event_types: bool,
+    /// Report the shifted key along with the base key (flag 4)
+    pub alternate_keys: bool,
+}
+
+impl KeyboardEnhancements {
+    /// Progressive enhancement flags, as sent in `CSI > flags u`.
+    pub fn bits(&self) -> u8 {
+        u8::from(self.disambiguate) | u8::from(self.event_types) << 1 | u8::from(self.alternate_keys) << 2
+    }
+}
+
+impl Default for KeyboardEnhancements {
+    fn default() -> Self {
+        Self { disambiguate: true, event_types: true, alternate_keys: true }
     }
 }
 
@@ -455,14 +590,48 @@ pub enum TerminalEvent {
     FocusLost,
     /// Paste event (bracketed paste mode)
     Paste(String),
+    /// Reply to a terminal query; never a key press
+    Reply(QueryReply),
 }
 
 // =============================================================================
 // Input Parsing
 // =============================================================================
 
+/// Longest DCS/OSC reply read before giving up on its terminator.
+const MAX_STRING_REPLY: usize = 1024;
+
+const SHIFT: KeyModifiers = KeyModifiers { shift: true, ..KeyModifiers::NONE };
+const ALT: KeyModifiers = KeyModifiers { alt: true, ..KeyModifiers::NONE };
+const CTRL: KeyModifiers = KeyModifiers { ctrl: true, ..KeyModifiers::NONE };
+
+thread_local! {
+    static PENDING_EVENTS: RefCell<VecDeque<TerminalEvent>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Queue events to be returned by the next `read_event` calls.
+pub fn push_events(events: impl IntoIterator<Item = TerminalEvent>) {
+    PENDING_EVENTS.with(|pending| pending.borrow_mut().extend(events));
+}
+
+/// A resize event, if the terminal has been resized since the last one.
+fn take_resize() -> io::Result<Option<TerminalEvent>> {
+    if !RESIZED.swap(false, Ordering::SeqCst) {
+        return Ok(None);
+    }
+    let (width, height) = get_terminal_size()?;
+    Ok(Some(TerminalEvent::Resize(width, height)))
+}
+
 /// Read a terminal event with optional timeout.
 pub fn read_event(timeout: Option<Duration>) -> io::Result<Option<TerminalEvent>> {
+    if let Some(event) = PENDING_EVENTS.with(|pending| pending.borrow_mut().pop_front()) {
+        return Ok(Some(event));
+    }
+    if let Some(event) = take_resize()? {
+        return Ok(Some(event));
+    }
+
     let mut buf = [0u8; 32];
     let stdin = stdin();
     let mut handle = stdin.lock();
@@ -475,13 +644,19 @@ pub fn read_event(timeout: Option<Duration>) -> io::Result<Option<TerminalEvent>
         loop {
             match handle.read(&mut buf[..1]) {
                 Ok(0) => {
+                    if RESIZED.load(Ordering::SeqCst) {
+                        return take_resize();
+                    }
                     if start.elapsed() >= t {
                         return Ok(None);
                     }
                     std::thread::sleep(Duration::from_millis(10));
                 }
                 Ok(_) => break,
-                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::Interrupted => {
+                    if RESIZED.load(Ordering::SeqCst) {
+                        return take_resize();
+                    }
                     if start.elapsed() >= t {
                         return Ok(None);
                     }
@@ -530,13 +705,7 @@ fn parse_input<R: Read>(initial: &[u8], reader: &mut R) -> io::Result<Option<Ter
             1..=8 | 11..=12 | 14..=26 => {
                 // Ctrl+A through Ctrl+Z (excluding Tab=9, Enter=10,13, Esc=27)
                 let c = (first + b'a' - 1) as char;
-                return Ok(Some(TerminalEvent::Key(KeyEvent {
-                    key: Key::Char(c),
-                    modifiers: KeyModifiers {
-                        ctrl: true,
-                        ..Default::default()
-                    },
-                })));
+                return Ok(Some(TerminalEvent::Key(KeyEvent::new(Key::Char(c), CTRL))));
             }
             _ => Key::Null,
         };
@@ -587,6 +756,23 @@ fn parse_escape_sequence<R: Read>(reader: &mut R) -> io::Result<Option<TerminalE
     match buf[0] {
         b'[' => parse_csi_sequence(reader),
         b'O' => parse_ss3_sequence(reader),
+        // DCS and OSC replies; otherwise Alt+P / Alt+]
+        kind @ (b'P' | b']') if queries::is_awaiting_string_reply() => parse_string_reply(kind, reader),
+        // rxvt sends Alt+key as ESC before the key's own sequence
+        0x1B => {
+            let event = match reader.read(&mut buf) {
+                Ok(1) if buf[0] == b'[' => parse_csi_sequence(reader)?,
+                Ok(1) if buf[0] == b'O' => parse_ss3_sequence(reader)?,
+                _ => Some(TerminalEvent::Key(KeyEvent::simple(Key::Escape))),
+            };
+            Ok(event.map(|event| match event {
+                TerminalEvent::Key(mut key) => {
+                    key.modifiers.alt = true;
+                    TerminalEvent::Key(key)
+                }
+                other => other,
+            }))
+        }
         _ => {
             // Alt + key
             let key = if buf[0] < 32 {
@@ -594,13 +780,7 @@ fn parse_escape_sequence<R: Read>(reader: &mut R) -> io::Result<Option<TerminalE
             } else {
                 Key::Char(buf[0] as char)
             };
-            Ok(Some(TerminalEvent::Key(KeyEvent {
-                key,
-                modifiers: KeyModifiers {
-                    alt: true,
-                    ..Default::default()
-                },
-            })))
+            Ok(Some(TerminalEvent::Key(KeyEvent::new(key, ALT))))
         }
     }
 }
@@ -615,42 +795,76 @@ fn parse_csi_sequence<R: Read>(reader: &mut R) -> io::Result<Option<TerminalEven
             break;
         }
 
-        match buf[0] {
-            b'0'..=b'9' | b';' | b':' => params.push(buf[0]),
-            b'<' => params.push(buf[0]), // SGR mouse
-            b'A' => return Ok(Some(TerminalEvent::Key(KeyEvent::simple(Key::Up)))),
-            b'B' => return Ok(Some(TerminalEvent::Key(KeyEvent::simple(Key::Down)))),
-            b'C' => return Ok(Some(TerminalEvent::Key(KeyEvent::simple(Key::Right)))),
-            b'D' => return Ok(Some(TerminalEvent::Key(KeyEvent::simple(Key::Left)))),
-            b'H' => return Ok(Some(TerminalEvent::Key(KeyEvent::simple(Key::Home)))),
-            b'F' => return Ok(Some(TerminalEvent::Key(KeyEvent::simple(Key::End)))),
-            b'Z' => return Ok(Some(TerminalEvent::Key(KeyEvent::simple(Key::BackTab)))),
-            b'~' => {
-                // Parse param to determine key
-                let param: u8 = std::str::from_utf8(&params)
+        if let Some(reply) = queries::parse_csi_reply(&params, buf[0]) {
+            return Ok(Some(TerminalEvent::Reply(reply)));
+        }
+
+        let key = match buf[0] {
+            b'0'..=b'9' | b';' | b':' => {
+                params.push(buf[0]);
+                continue;
+            }
+            b'<' => {
+                params.push(buf[0]); // SGR mouse
+                continue;
+            }
+            // Query replies: DA1 (`?...c`) and DECRPM (`?...$y`); a `$`
+            // after plain numbers is rxvt's Shift suffix below
+            b'?' | b'>' if params.is_empty() => {
+                params.push(buf[0]);
+                continue;
+            }
+            b'$' if params.first() == Some(&b'?') => {
+                params.push(buf[0]);
+                continue;
+            }
+            b'A' => csi_key(Key::Up, &params),
+            b'B' => csi_key(Key::Down, &params),
+            b'C' => csi_key(Key::Right, &params),
+            b'D' => csi_key(Key::Left, &params),
+            b'H' => csi_key(Key::Home, &params),
+            b'F' => csi_key(Key::End, &params),
+            // F1-F4 as `CSI 1 ; mods P` (F3 only while no CPR is awaited)
+            b'P' => csi_key(Key::F(1), &params),
+            b'Q' => csi_key(Key::F(2), &params),
+            b'R' => csi_key(Key::F(3), &params),
+            b'S' => csi_key(Key::F(4), &params),
+            b'Z' => KeyEvent::simple(Key::BackTab),
+            // rxvt: Shift+arrows
+            b'a' if params.is_empty() => KeyEvent::new(Key::Up, SHIFT),
+            b'b' if params.is_empty() => KeyEvent::new(Key::Down, SHIFT),
+            b'c' if params.is_empty() => KeyEvent::new(Key::Right, SHIFT),
+            b'd' if params.is_empty() => KeyEvent::new(Key::Left, SHIFT),
+            // Linux console: F1-F5 as `CSI [ A` to `CSI [ E`
+            b'[' if params.is_empty() => {
+                if reader.read(&mut buf)? == 0 {
+                    break;
+                }
+                match buf[0] {
+                    b'A'..=b'E' => KeyEvent::simple(Key::F(buf[0] - b'A' + 1)),
+                    _ => KeyEvent::simple(Key::Null),
+                }
+            }
+            // Kitty keyboard protocol
+            b'u' => return Ok(Some(parse_kitty_key(&params))),
+            // VT220 keys; rxvt marks Shift, Ctrl and both with `$`, `^`, `@`
+            last @ (b'~' | b'$' | b'^' | b'@') => {
+                let number: u16 = std::str::from_utf8(&params)
                     .ok()
                     .and_then(|s| s.split(';').next())
                     .and_then(|s| s.parse().ok())
                     .unwrap_or(0);
 
-                let key = match param {
-                    1 => Key::Home,
-                    2 => Key::Insert,
-                    3 => Key::Delete,
-                    4 => Key::End,
-                    5 => Key::PageUp,
-                    6 => Key::PageDown,
-                    15 => Key::F(5),
-                    17 => Key::F(6),
-                    18 => Key::F(7),
-                    19 => Key::F(8),
-                    20 => Key::F(9),
-                    21 => Key::F(10),
-                    23 => Key::F(11),
-                    24 => Key::F(12),
-                    _ => Key::Null,
-                };
-                return Ok(Some(TerminalEvent::Key(KeyEvent::simple(key))));
+                let key = vt220_key(number);
+                if key == Key::Null {
+                    if let Some(event) = terminfo_key(b"\x1B[", &params, last) {
+                        return Ok(Some(event));
+                    }
+                }
+                let mut event = csi_key(key, &params);
+                event.modifiers.shift |= matches!(last, b'$' | b'@');
+                event.modifiers.ctrl |= matches!(last, b'^' | b'@');
+                event
             }
             b'M' | b'm' => {
                 // SGR mouse event
@@ -658,17 +872,35 @@ fn parse_csi_sequence<R: Read>(reader: &mut R) -> io::Result<Option<TerminalEven
             }
             b'I' => return Ok(Some(TerminalEvent::FocusGained)),
             b'O' => return Ok(Some(TerminalEvent::FocusLost)),
-            _ => break,
-        }
+            other => {
+                if let Some(event) = terminfo_key(b"\x1B[", &params, other) {
+                    return Ok(Some(event));
+                }
+                break;
+            }
+        };
+        return Ok(Some(TerminalEvent::Key(key)));
     }
 
     Ok(Some(TerminalEvent::Key(KeyEvent::simple(Key::Null))))
 }
 
 fn parse_ss3_sequence<R: Read>(reader: &mut R) -> io::Result<Option<TerminalEvent>> {
+    let mut params = Vec::new();
     let mut buf = [0u8; 1];
-    if reader.read(&mut buf)? == 0 {
-        return Ok(None);
+
+    // Some terminals put modifiers in SS3 keys too: `ESC O 5 A`, `ESC O 1;2 P`
+    loop {
+        if reader.read(&mut buf)? == 0 {
+            return Ok(None);
+        }
+        match buf[0] {
+            b'0'..=b'9' | b';' => params.push(buf[0]),
+            _ => break,
+        }
+    }
+    if !params.contains(&b';') {
+        params.splice(0..0, *b"1;");
     }
 
     let key = match buf[0] {
@@ -682,10 +914,142 @@ fn parse_ss3_sequence<R: Read>(reader: &mut R) -> io::Result<Option<TerminalEven
         b'Q' => Key::F(2),
         b'R' => Key::F(3),
         b'S' => Key::F(4),
+        // Keypad in application mode
+        b'M' => Key::Enter,
+        b'j'..=b'y' => Key::Char(char::from(b"*+,-./0123456789"[usize::from(buf[0] - b'j')])),
+        // rxvt: Ctrl+arrows
+        b'a'..=b'd' => {
+            let arrow = [Key::Up, Key::Down, Key::Right, Key::Left][usize::from(buf[0] - b'a')].clone();
+            return Ok(Some(TerminalEvent::Key(KeyEvent::new(arrow, CTRL))));
+        }
+        other => {
+            let event = terminfo_key(b"\x1BO", &[], other);
+            return Ok(event.or_else(|| Some(TerminalEvent::Key(KeyEvent::simple(Key::Null)))));
+        }
+    };
+
+    Ok(Some(TerminalEvent::Key(csi_key(key, &params))))
+}
+
+/// Key for a VT220-style `CSI number ~` sequence, including rxvt's
+/// Home (7), End (8) and F1-F4 (11-14).
+fn vt220_key(number: u16) -> Key {
+    match number {
+        1 | 7 => Key::Home,
+        2 => Key::Insert,
+        3 => Key::Delete,
+        4 | 8 => Key::End,
+        5 => Key::PageUp,
+        6 => Key::PageDown,
+        11..=15 => Key::F((number - 10) as u8),
+        17..=21 => Key::F((number - 11) as u8),
+        23..=26 => Key::F((number - 12) as u8),
+        28 | 29 => Key::F((number - 13) as u8),
+        31..=34 => Key::F((number - 14) as u8),
         _ => Key::Null,
+    }
+}
+
+/// Read a DCS or OSC string up to its terminator (BEL or `ESC \`).
+fn parse_string_reply<R: Read>(kind: u8, reader: &mut R) -> io::Result<Option<TerminalEvent>> {
+    let mut body = Vec::new();
+    let mut buf = [0u8; 1];
+
+    while body.len() < MAX_STRING_REPLY {
+        if reader.read(&mut buf)? == 0 {
+            break;
+        }
+        match buf[0] {
+            0x07 => break,
+            // ST is `ESC \`; the backslash may not have arrived yet
+            0x1B => {
+                let _backslash = reader.read(&mut buf)?;
+                break;
+            }
+            byte => body.push(byte),
+        }
+    }
+
+    // A reply we can't read is dropped rather than passed on as a key
+    Ok(queries::parse_string_reply(kind, &body).map(TerminalEvent::Reply))
+}
+
+/// Modifiers and event kind from a `mods[:kind]` parameter, as in
+/// `CSI 1;5A` or the Kitty protocol's `CSI 97;5:3u`.
+fn modifier_param(param: Option<&str>) -> (KeyModifiers, KeyEventKind) {
+    let mut parts = param.unwrap_or("").split(':');
+    let bits = parts.next().and_then(|m| m.parse::<u16>().ok()).map_or(0, |m| m.saturating_sub(1));
+    let kind = match parts.next() {
+        Some("2") => KeyEventKind::Repeat,
+        Some("3") => KeyEventKind::Release,
+        _ => KeyEventKind::Press,
     };
+    (KeyModifiers::from_bits(bits), kind)
+}
+
+/// Key event for a CSI sequence naming its key, with the modifiers and
+/// event kind from the second parameter.
+fn csi_key(key: Key, params: &[u8]) -> KeyEvent {
+    let params = std::str::from_utf8(params).unwrap_or("");
+    let (modifiers, kind) = modifier_param(params.split(';').nth(1));
+    KeyEvent::new(key, modifiers).with_kind(kind)
+}
+
+/// Key reported by the Kitty keyboard protocol, as
+/// `CSI code[:shifted[:base]] ; mods[:kind] ; text u`.
+///
+/// Keypad keys come back as their ordinary counterparts; lock, media and
+/// lone modifier keys are [`Key::Null`].
+fn parse_kitty_key(params: &[u8]) -> TerminalEvent {
+    let params = std::str::from_utf8(params).unwrap_or("");
+    let mut fields = params.split(';');
+    let mut codes = fields.next().unwrap_or("").split(':');
+    let code: u32 = codes.next().and_then(|c| c.parse().ok()).unwrap_or(0);
+    let shifted = codes.next().and_then(|c| c.parse().ok()).and_then(char::from_u32);
+    let (mut modifiers, kind) = modifier_param(fields.next());
+
+    let key = match code {
+        9 if modifiers.shift => {
+            modifiers.shift = false;
+            Key::BackTab
+        }
+        9 => Key::Tab,
+        13 | 57414 => Key::Enter,
+        27 => Key::Escape,
+        8 | 127 => Key::Backspace,
+        // F13-F35
+        57376..=57398 => Key::F((code - 57376 + 13) as u8),
+        57399..=57408 => char::from_digit(code - 57399, 10).map_or(Key::Null, Key::Char),
+        57409..=57416 => Key::Char(['.', '/', '*', '-', '+', '\r', '=', ','][(code - 57409) as usize]),
+        57417 => Key::Left,
+        57418 => Key::Right,
+        57419 => Key::Up,
+        57420 => Key::Down,
+        57421 => Key::PageUp,
+        57422 => Key::PageDown,
+        57423 => Key::Home,
+        57424 => Key::End,
+        57425 => Key::Insert,
+        57426 => Key::Delete,
+        // Remaining functional keys live in the private use area
+        57344..=63743 => Key::Null,
+        code => match char::from_u32(code) {
+            Some(c) if !c.is_control() => {
+                let upper = (modifiers.shift && c.is_ascii_lowercase()).then(|| c.to_ascii_uppercase());
+                Key::Char(shifted.filter(|_| modifiers.shift).or(upper).unwrap_or(c))
+            }
+            _ => Key::Null,
+        },
+    };
+    TerminalEvent::Key(KeyEvent::new(key, modifiers).with_kind(kind))
+}
 
-    Ok(Some(TerminalEvent::Key(KeyEvent::simple(key))))
+/// Look up a sequence the built-in tables don't know in the terminfo entry.
+fn terminfo_key(prefix: &[u8], params: &[u8], last: u8) -> Option<TerminalEvent> {
+    let sequence = [prefix, params, &[last]].concat();
+    crate::core::terminfo::current()?
+        .key_for(&sequence)
+        .map(TerminalEvent::Key)
 }
 
 fn parse_sgr_mouse(params: &[u8], released: bool) -> io::Result<Option<TerminalEvent>> {
@@ -762,6 +1126,145 @@ mod tests {
         assert!(mods.any());
     }
 
+    #[test]
+    fn test_query_replies_are_not_keys() {
+        use crate::core::queries::{expect_replies, reset_queries, startup_queries, Query};
+
+        let parse = |bytes: &[u8]| parse_input(&bytes[..1], &mut io::Cursor::new(bytes[1..].to_vec())).unwrap();
+        let reply = |bytes: &[u8]| match parse(bytes) {
+            Some(TerminalEvent::Reply(reply)) => reply,
+            other => panic!("expected a reply, got {other:?}"),
+        };
+
+        reset_queries();
+        assert_eq!(reply(b"\x1B[?62;4c"), QueryReply::DeviceAttributes(vec![62, 4]));
+        assert!(matches!(reply(b"\x1B[?2026;1$y"), QueryReply::Mode { mode: 2026, .. }));
+        // Without outstanding queries these are keys
+        let alt = KeyModifiers { alt: true, ..KeyModifiers::NONE };
+        assert_eq!(parse(b"\x1B]"), Some(TerminalEvent::Key(KeyEvent::new(Key::Char(']'), alt))));
+        assert!(matches!(parse(b"\x1B[12;40R"), Some(TerminalEvent::Key(_))));
+
+        expect_replies(&startup_queries(), Duration::from_secs(5));
+        assert_eq!(reply(b"\x1B[12;40R"), QueryReply::CursorPosition { row: 12, column: 40 });
+        assert_eq!(reply(b"\x1BP>|XTerm(388)\x1B\\"), QueryReply::Version("XTerm(388)".to_string()));
+        assert_eq!(reply(b"\x1B]11;rgb:0000/0000/0000\x07"), QueryReply::Background(0, 0, 0));
+        // Unreadable DCS and OSC replies are dropped
+        assert_eq!(parse(b"\x1BP0+r\x1B\\"), None);
+        assert_eq!(parse(b"\x1B]52;c;aGVsbG8=\x07"), None);
+        assert!(crate::core::queries::is_awaiting(Query::DeviceAttributes));
+
+        // Replies after the wait is over are still replies until DA1 settles them
+        reset_queries();
+        expect_replies(&startup_queries(), Duration::ZERO);
+        assert_eq!(reply(b"\x1B[12;40R"), QueryReply::CursorPosition { row: 12, column: 40 });
+        assert_eq!(reply(b"\x1BP>|XTerm(388)\x1B\\"), QueryReply::Version("XTerm(388)".to_string()));
+        assert_eq!(reply(b"\x1B]10;rgb:ffff/ffff/ffff\x07"), QueryReply::Foreground(255, 255, 255));
+        crate::core::queries::handle_reply(reply(b"\x1B[?62c"));
+        assert_eq!(parse(b"\x1B]"), Some(TerminalEvent::Key(KeyEvent::new(Key::Char(']'), alt))));
+        reset_queries();
+    }
+
+    #[test]
+    fn test_kitty_keyboard_protocol() {
+        let parse = |bytes: &[u8]| match parse_input(&bytes[..1], &mut io::Cursor::new(bytes[1..].to_vec())).unwrap() {
+            Some(TerminalEvent::Key(event)) => event,
+            other => panic!("expected a key, got {other:?}"),
+        };
+        let ctrl = KeyModifiers { ctrl: true, ..KeyModifiers::NONE };
+
+        // Ctrl+I and Ctrl+M are no longer Tab and Enter
+        assert_eq!(parse(b"\x1B[105;5u"), KeyEvent::new(Key::Char('i'), ctrl));
+        assert_eq!(parse(b"\x1B[109;5u"), KeyEvent::new(Key::Char('m'), ctrl));
+        assert_eq!(parse(b"\t"), KeyEvent::simple(Key::Tab));
+        assert_eq!(parse(b"\x1B[27u"), KeyEvent::simple(Key::Escape));
+
+        // Super, Hyper and the shifted key
+        let event = parse(b"\x1B[97:65;26u");
+        assert_eq!(event.key, Key::Char('A'));
+        assert!(event.modifiers.shift && event.modifiers.super_key && event.modifiers.hyper && !event.modifiers.ctrl);
+        assert!(!event.modifiers.meta);
+        // Meta is its own modifier
+        let event = parse(b"\x1B[97;33u");
+        assert!(event.modifiers.meta && !event.modifiers.super_key);
+
+        // Repeats and releases, also for keys in their legacy form
+        assert_eq!(parse(b"\x1B[97;1:2u").kind, KeyEventKind::Repeat);
+        assert_eq!(parse(b"\x1B[1;1:3A"), KeyEvent::simple(Key::Up).with_kind(KeyEventKind::Release));
+        assert_eq!(parse(b"\x1B[3;1:3~"), KeyEvent::simple(Key::Delete).with_kind(KeyEventKind::Release));
+
+        // Functional keys in the private use area
+        assert_eq!(parse(b"\x1B[57376u").key, Key::F(13));
+        assert_eq!(parse(b"\x1B[57414u").key, Key::Enter);
+        assert_eq!(parse(b"\x1B[57401u").key, Key::Char('2'));
+        assert_eq!(parse(b"\x1B[57441;2u").key, Key::Null);
+
+        let all = KeyboardEnhancements::default();
+        assert_eq!(all.bits(), 7);
+        assert_eq!(KeyboardEnhancements { event_types: false, ..all }.bits(), 5);
+    }
+
+    #[test]
+    fn test_modified_key_sequences() {
+        let modifiers = |names: &str| {
+            let mut modifiers = KeyModifiers::NONE;
+            for name in names.split('+').filter(|name| !name.is_empty()) {
+                match name {
+                    "shift" => modifiers.shift = true,
+                    "ctrl" => modifiers.ctrl = true,
+                    "alt" => modifiers.alt = true,
+                    "super" => modifiers.super_key = true,
+                    _ => unreachable!(),
+                }
+            }
+            modifiers
+        };
+        let cases: &[(&str, &[u8], Key, &str)] = &[
+            ("xterm", b"\x1B[1;5A", Key::Up, "ctrl"),
+            ("xterm", b"\x1B[1;2C", Key::Right, "shift"),
+            ("xterm", b"\x1B[6;3~", Key::PageDown, "alt"),
+            ("xterm", b"\x1B[1;9H", Key::Home, "super"),
+            ("xterm", b"\x1B[1;5P", Key::F(1), "ctrl"),
+            ("xterm", b"\x1B[15;2~", Key::F(5), "shift"),
+            ("xterm", b"\x1B[24;8~", Key::F(12), "shift+alt+ctrl"),
+            ("xterm", b"\x1BOP", Key::F(1), ""),
+            ("xterm", b"\x1BO5A", Key::Up, "ctrl"),
+            ("xterm", b"\x1BOj", Key::Char('*'), ""),
+            ("VTE", b"\x1BOH", Key::Home, ""),
+            ("VTE", b"\x1BOF", Key::End, ""),
+            ("VTE", b"\x1B[1;5H", Key::Home, "ctrl"),
+            ("VTE", b"\x1B[1;6D", Key::Left, "shift+ctrl"),
+            ("VTE", b"\x1B[3;5~", Key::Delete, "ctrl"),
+            ("VTE", b"\x1B[1;2Q", Key::F(2), "shift"),
+            ("VTE", b"\x1BO1;2S", Key::F(4), "shift"),
+            ("tmux", b"\x1B[1~", Key::Home, ""),
+            ("tmux", b"\x1B[4~", Key::End, ""),
+            ("tmux", b"\x1B[1;3B", Key::Down, "alt"),
+            ("tmux", b"\x1B[1;2R", Key::F(3), "shift"),
+            ("tmux", b"\x1BOS", Key::F(4), ""),
+            ("tmux", b"\x1B[Z", Key::BackTab, ""),
+            ("rxvt", b"\x1B[a", Key::Up, "shift"),
+            ("rxvt", b"\x1BOd", Key::Left, "ctrl"),
+            ("rxvt", b"\x1B[2$", Key::Insert, "shift"),
+            ("rxvt", b"\x1B[3^", Key::Delete, "ctrl"),
+            ("rxvt", b"\x1B[7@", Key::Home, "shift+ctrl"),
+            ("rxvt", b"\x1B[8~", Key::End, ""),
+            ("rxvt", b"\x1B[11~", Key::F(1), ""),
+            ("rxvt", b"\x1B[14^", Key::F(4), "ctrl"),
+            ("rxvt", b"\x1B[25~", Key::F(13), ""),
+            ("rxvt", b"\x1B\x1B[A", Key::Up, "alt"),
+            ("rxvt", b"\x1B\x1BOb", Key::Down, "alt+ctrl"),
+            ("linux", b"\x1B[[A", Key::F(1), ""),
+            ("linux", b"\x1B[[E", Key::F(5), ""),
+        ];
+
+        crate::core::queries::reset_queries();
+        for (terminal, bytes, key, names) in cases {
+            let event = parse_input(&bytes[..1], &mut io::Cursor::new(bytes[1..].to_vec())).unwrap();
+            let expected = KeyEvent::new(key.clone(), modifiers(names));
+            assert_eq!(event, Some(TerminalEvent::Key(expected)), "{terminal}: {bytes:?}");
+        }
+    }
+
     #[test]
     fn test_key_event() {
         let event = KeyEvent::simple(Key::Enter);
@@ -0,0 +1,664 @@
+//! Terminfo Database
+//!
+//! Zero-dependency reader for compiled terminfo entries, both the legacy
+//! format and the ncurses 6 format with 32-bit numbers, including the
+//! extended (user-defined) capabilities such as `Tc`, `RGB` and `BE`.
+//!
+//! ```ignore
+//! if let Some(info) = Terminfo::from_env() {
+//!     println!("{} colors", info.number("colors").unwrap_or(0));
+//!     let up = info.string("kcuu1");
+//! }
+//! ```
+
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::core::terminal::{Key, KeyEvent, KeyModifiers};
+
+// =============================================================================
+// Capability Names
+// =============================================================================
+
+/// Standard boolean capabilities, in file order.
+const BOOLEAN_NAMES: [&str; 44] = [
+    "bw", "am", "xsb", "xhp", "xenl", "eo", "gn", "hc", "km", "hs", "in", "da", "db",
+    "mir", "msgr", "os", "eslok", "xt", "hz", "ul", "xon", "nxon", "mc5i", "chts", "nrrmc",
+    "npc", "ndscr", "ccc", "bce", "hls", "xhpa", "crxm", "daisy", "xvpa", "sam", "cpix",
+    "lpix", "OTbs", "OTns", "OTnc", "OTMT", "OTNL", "OTpt", "OTxr",
+];
+
+/// Standard numeric capabilities, in file order.
+const NUMBER_NAMES: [&str; 39] = [
+    "cols", "it", "lines", "lm", "xmc", "pb", "vt", "wsl", "nlab", "lh", "lw", "ma",
+    "wnum", "colors", "pairs", "ncv", "bufsz", "spinv", "spinh", "maddr", "mjump", "mcs",
+    "mls", "npins", "orc", "orl", "orhi", "orvi", "cps", "widcs", "btns", "bitwin",
+    "bitype", "OTug", "OTdC", "OTdN", "OTdB", "OTdT", "OTkn",
+];
+
+/// Standard string capabilities, in file order.
+const STRING_NAMES: [&str; 414] = [
+    "cbt", "bel", "cr", "csr", "tbc", "clear", "el", "ed", "hpa", "cmdch", "cup", "cud1",
+    "home", "civis", "cub1", "mrcup", "cnorm", "cuf1", "ll", "cuu1", "cvvis", "dch1",
+    "dl1", "dsl", "hd", "smacs", "blink", "bold", "smcup", "smdc", "dim", "smir", "invis",
+    "prot", "rev", "smso", "smul", "ech", "rmacs", "sgr0", "rmcup", "rmdc", "rmir", "rmso",
+    "rmul", "flash", "ff", "fsl", "is1", "is2", "is3", "if", "ich1", "il1", "ip", "kbs",
+    "ktbc", "kclr", "kctab", "kdch1", "kdl1", "kcud1", "krmir", "kel", "ked", "kf0", "kf1",
+    "kf10", "kf2", "kf3", "kf4", "kf5", "kf6", "kf7", "kf8", "kf9", "khome", "kich1",
+    "kil1", "kcub1", "kll", "knp", "kpp", "kcuf1", "kind", "kri", "khts", "kcuu1", "rmkx",
+    "smkx", "lf0", "lf1", "lf10", "lf2", "lf3", "lf4", "lf5", "lf6", "lf7", "lf8", "lf9",
+    "rmm", "smm", "nel", "pad", "dch", "dl", "cud", "ich", "indn", "il", "cub", "cuf",
+    "rin", "cuu", "pfkey", "pfloc", "pfx", "mc0", "mc4", "mc5", "rep", "rs1", "rs2", "rs3",
+    "rf", "rc", "vpa", "sc", "ind", "ri", "sgr", "hts", "wind", "ht", "tsl", "uc", "hu",
+    "iprog", "ka1", "ka3", "kb2", "kc1", "kc3", "mc5p", "rmp", "acsc", "pln", "kcbt",
+    "smxon", "rmxon", "smam", "rmam", "xonc", "xoffc", "enacs", "smln", "rmln", "kbeg",
+    "kcan", "kclo", "kcmd", "kcpy", "kcrt", "kend", "kent", "kext", "kfnd", "khlp", "kmrk",
+    "kmsg", "kmov", "knxt", "kopn", "kopt", "kprv", "kprt", "krdo", "kref", "krfr", "krpl",
+    "krst", "kres", "ksav", "kspd", "kund", "kBEG", "kCAN", "kCMD", "kCPY", "kCRT", "kDC",
+    "kDL", "kslt", "kEND", "kEOL", "kEXT", "kFND", "kHLP", "kHOM", "kIC", "kLFT", "kMSG",
+    "kMOV", "kNXT", "kOPT", "kPRV", "kPRT", "kRDO", "kRPL", "kRIT", "kRES", "kSAV", "kSPD",
+    "kUND", "rfi", "kf11", "kf12", "kf13", "kf14", "kf15", "kf16", "kf17", "kf18", "kf19",
+    "kf20", "kf21", "kf22", "kf23", "kf24", "kf25", "kf26", "kf27", "kf28", "kf29", "kf30",
+    "kf31", "kf32", "kf33", "kf34", "kf35", "kf36", "kf37", "kf38", "kf39", "kf40", "kf41",
+    "kf42", "kf43", "kf44", "kf45", "kf46", "kf47", "kf48", "kf49", "kf50", "kf51", "kf52",
+    "kf53", "kf54", "kf55", "kf56", "kf57", "kf58", "kf59", "kf60", "kf61", "kf62", "kf63",
+    "el1", "mgc", "smgl", "smgr", "fln", "sclk", "dclk", "rmclk", "cwin", "wingo", "hup",
+    "dial", "qdial", "tone", "pulse", "hook", "pause", "wait", "u0", "u1", "u2", "u3",
+    "u4", "u5", "u6", "u7", "u8", "u9", "op", "oc", "initc", "initp", "scp", "setf",
+    "setb", "cpi", "lpi", "chr", "cvr", "defc", "swidm", "sdrfq", "sitm", "slm", "smicm",
+    "snlq", "snrmq", "sshm", "ssubm", "ssupm", "sum", "rwidm", "ritm", "rlm", "rmicm",
+    "rshm", "rsubm", "rsupm", "rum", "mhpa", "mcud1", "mcub1", "mcuf1", "mvpa", "mcuu1",
+    "porder", "mcud", "mcub", "mcuf", "mcuu", "scs", "smgb", "smgbp", "smglp", "smgrp",
+    "smgt", "smgtp", "sbim", "scsd", "rbim", "rcsd", "subcs", "supcs", "docr", "zerom",
+    "csnm", "kmous", "minfo", "reqmp", "getm", "setaf", "setab", "pfxl", "devt", "csin",
+    "s0ds", "s1ds", "s2ds", "s3ds", "smglr", "smgtb", "birep", "binel", "bicr", "colornm",
+    "defbi", "endbi", "setcolor", "slines", "dispc", "smpch", "rmpch", "smsc", "rmsc",
+    "pctrm", "scesc", "scesa", "ehhlm", "elhlm", "elohlm", "erhlm", "ethlm", "evhlm",
+    "sgr1", "slength", "OTi2", "OTrs", "OTnl", "OTbc", "OTko", "OTma", "OTG2", "OTG3",
+    "OTG1", "OTG4", "OTGR", "OTGL", "OTGU", "OTGD", "OTGH", "OTGV", "OTGC", "meml", "memu",
+    "box1",
+];
+
+/// Magic number of the legacy format (16-bit numbers).
+const MAGIC_LEGACY: i16 = 0o432;
+/// Magic number of the ncurses 6 format (32-bit numbers).
+const MAGIC_32BIT: i16 = 0o1036;
+
+// =============================================================================
+// Terminfo
+// =============================================================================
+
+/// A parsed terminfo entry.
+#[derive(Debug, Clone, Default)]
+pub struct Terminfo {
+    /// Terminal names, primary name first and description last
+    pub names: Vec<String>,
+    booleans: Vec<bool>,
+    numbers: Vec<Option<i32>>,
+    strings: Vec<Option<Vec<u8>>>,
+    extended_booleans: HashMap<String, bool>,
+    extended_numbers: HashMap<String, i32>,
+    extended_strings: HashMap<String, Vec<u8>>,
+    /// Keys by sequence, built on the first lookup
+    key_table: OnceCell<HashMap<Vec<u8>, KeyEvent>>,
+}
+
+impl Terminfo {
+    /// Load the entry for `$TERM`.
+    pub fn from_env() -> Option<Self> {
+        let term = env::var("TERM").ok().filter(|t| !t.is_empty())?;
+        Self::load(&term).ok()
+    }
+
+    /// Find and parse the entry for `term` in the terminfo directories.
+    pub fn load(term: &str) -> io::Result<Self> {
+        let path = search_dirs()
+            .into_iter()
+            .find_map(|dir| entry_path(&dir, term))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no terminfo entry for {term}")))?;
+        Self::parse(&fs::read(path)?)
+    }
+
+    /// Parse a compiled terminfo entry.
+    pub fn parse(data: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader { data, pos: 0 };
+
+        let magic = reader.i16()?;
+        let number_width = match magic {
+            MAGIC_LEGACY => 2,
+            MAGIC_32BIT => 4,
+            _ => return Err(invalid("not a compiled terminfo entry")),
+        };
+        let names_size = reader.count()?;
+        let boolean_count = reader.count()?;
+        let number_count = reader.count()?;
+        let string_count = reader.count()?;
+        let table_size = reader.count()?;
+
+        let names = reader.bytes(names_size)?;
+        let names = String::from_utf8_lossy(names.split(|&b| b == 0).next().unwrap_or_default())
+            .split('|')
+            .map(str::to_string)
+            .collect();
+
+        let booleans = reader.bytes(boolean_count)?.iter().map(|&b| b == 1).collect();
+        reader.align();
+        let numbers = (0..number_count)
+            .map(|_| reader.number(number_width))
+            .collect::<io::Result<_>>()?;
+        let offsets = (0..string_count).map(|_| reader.i16()).collect::<io::Result<Vec<_>>>()?;
+        let table = reader.bytes(table_size)?;
+        let strings = offsets.iter().map(|&offset| string_at(table, offset)).collect();
+
+        let mut info = Self {
+            names,
+            booleans,
+            numbers,
+            strings,
+            ..Default::default()
+        };
+
+        reader.align();
+        if reader.remaining() >= 10 {
+            info.parse_extended(&mut reader, number_width)?;
+        }
+
+        Ok(info)
+    }
+
+    /// Extended capabilities: values first, then one name per value.
+    fn parse_extended(&mut self, reader: &mut Reader<'_>, number_width: usize) -> io::Result<()> {
+        let boolean_count = reader.count()?;
+        let number_count = reader.count()?;
+        let string_count = reader.count()?;
+        let item_count = reader.count()?;
+        let table_size = reader.count()?;
+        if item_count != string_count + boolean_count + number_count + string_count {
+            return Err(invalid("inconsistent extended capability counts"));
+        }
+
+        let booleans: Vec<bool> = reader.bytes(boolean_count)?.iter().map(|&b| b == 1).collect();
+        reader.align();
+        let numbers = (0..number_count)
+            .map(|_| reader.number(number_width))
+            .collect::<io::Result<Vec<_>>>()?;
+        let offsets = (0..item_count).map(|_| reader.i16()).collect::<io::Result<Vec<_>>>()?;
+        let table = reader.bytes(table_size)?;
+
+        let (value_offsets, name_offsets) = offsets.split_at(string_count);
+        let values: Vec<Option<Vec<u8>>> = value_offsets.iter().map(|&offset| string_at(table, offset)).collect();
+
+        // Names follow the last string value
+        let names_start = value_offsets
+            .iter()
+            .filter_map(|&offset| usize::try_from(offset).ok())
+            .filter_map(|offset| Some(offset + table.get(offset..)?.iter().position(|&b| b == 0)? + 1))
+            .max()
+            .unwrap_or(0);
+        let names_table = table.get(names_start..).unwrap_or_default();
+        let mut names = name_offsets.iter().map(|&offset| {
+            string_at(names_table, offset).map(|name| String::from_utf8_lossy(&name).into_owned())
+        });
+
+        for value in booleans {
+            if let Some(name) = names.next().flatten() {
+                self.extended_booleans.insert(name, value);
+            }
+        }
+        for value in numbers {
+            if let (Some(name), Some(value)) = (names.next().flatten(), value) {
+                self.extended_numbers.insert(name, value);
+            }
+        }
+        for value in values {
+            if let (Some(name), Some(value)) = (names.next().flatten(), value) {
+                self.extended_strings.insert(name, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Primary terminal name.
+    pub fn name(&self) -> &str {
+        self.names.first().map_or("", String::as_str)
+    }
+
+    /// Boolean capability, standard or extended (`false` when absent).
+    pub fn flag(&self, name: &str) -> bool {
+        match BOOLEAN_NAMES.iter().position(|&n| n == name) {
+            Some(index) => self.booleans.get(index).copied().unwrap_or(false),
+            None => self.extended_booleans.get(name).copied().unwrap_or(false),
+        }
+    }
+
+    /// Numeric capability, standard or extended.
+    pub fn number(&self, name: &str) -> Option<i32> {
+        match NUMBER_NAMES.iter().position(|&n| n == name) {
+            Some(index) => self.numbers.get(index).copied().flatten(),
+            None => self.extended_numbers.get(name).copied(),
+        }
+    }
+
+    /// String capability, standard or extended, as raw bytes.
+    pub fn string(&self, name: &str) -> Option<&[u8]> {
+        match STRING_NAMES.iter().position(|&n| n == name) {
+            Some(index) => self.strings.get(index)?.as_deref(),
+            None => self.extended_strings.get(name).map(Vec::as_slice),
+        }
+    }
+
+    /// Whether a capability of any kind is present.
+    pub fn has(&self, name: &str) -> bool {
+        self.flag(name) || self.number(name).is_some() || self.string(name).is_some()
+    }
+
+    /// Number of colors (`colors`), 0 for monochrome terminals.
+    pub fn colors(&self) -> u32 {
+        self.number("colors").and_then(|n| u32::try_from(n).ok()).unwrap_or(0)
+    }
+
+    /// Whether the entry advertises 24-bit color, via tmux's `Tc`,
+    /// ncurses' `RGB` or a direct-color `colors` value.
+    pub fn true_color(&self) -> bool {
+        self.flag("Tc") || self.has("RGB") || self.colors() >= 1 << 24
+    }
+
+    /// Key sequences this terminal sends, with the keys they stand for.
+    ///
+    /// Includes the modified variants from extended entries such as
+    /// `kUP5` (Ctrl+Up).
+    pub fn keys(&self) -> Vec<(Vec<u8>, KeyEvent)> {
+        const KEYS: [(&str, Key); 11] = [
+            ("kcuu1", Key::Up),
+            ("kcud1", Key::Down),
+            ("kcub1", Key::Left),
+            ("kcuf1", Key::Right),
+            ("khome", Key::Home),
+            ("kend", Key::End),
+            ("kpp", Key::PageUp),
+            ("knp", Key::PageDown),
+            ("kich1", Key::Insert),
+            ("kdch1", Key::Delete),
+            ("kcbt", Key::BackTab),
+        ];
+        // Shifted keys and the prefix of their extended modified variants
+        const MODIFIED: [(&str, &str, Key); 10] = [
+            ("kri", "kUP", Key::Up),
+            ("kind", "kDN", Key::Down),
+            ("kLFT", "kLFT", Key::Left),
+            ("kRIT", "kRIT", Key::Right),
+            ("kHOM", "kHOM", Key::Home),
+            ("kEND", "kEND", Key::End),
+            ("kPRV", "kPRV", Key::PageUp),
+            ("kNXT", "kNXT", Key::PageDown),
+            ("kIC", "kIC", Key::Insert),
+            ("kDC", "kDC", Key::Delete),
+        ];
+
+        let mut keys = Vec::new();
+        let mut add = |name: &str, key: Key, modifiers: KeyModifiers| {
+            if let Some(sequence) = self.string(name) {
+                keys.push((sequence.to_vec(), KeyEvent::new(key, modifiers)));
+            }
+        };
+
+        for (name, key) in KEYS {
+            add(name, key, KeyModifiers::NONE);
+        }
+        add("kbs", Key::Backspace, KeyModifiers::NONE);
+        for n in 1..=12u8 {
+            add(&format!("kf{n}"), Key::F(n), KeyModifiers::NONE);
+        }
+        for (shifted, prefix, key) in MODIFIED {
+            let shift = KeyModifiers { shift: true, ..KeyModifiers::NONE };
+            add(shifted, key.clone(), shift);
+            // xterm modifier parameters: 2 = Shift, 3 = Alt, 5 = Ctrl, ...
+            for parameter in 3..=7 {
+                add(&format!("{prefix}{parameter}"), key.clone(), modifiers_from_parameter(parameter));
+            }
+        }
+
+        keys
+    }
+
+    /// Key for an exact input sequence, if the entry defines one.
+    pub fn key_for(&self, sequence: &[u8]) -> Option<KeyEvent> {
+        let table = self.key_table.get_or_init(|| {
+            let mut table = HashMap::new();
+            // The first key listed for a sequence wins
+            for (sequence, event) in self.keys() {
+                table.entry(sequence).or_insert(event);
+            }
+            table
+        });
+        table.get(sequence).cloned()
+    }
+}
+
+/// Modifiers for an xterm modifier parameter (`1 + bits`).
+fn modifiers_from_parameter(parameter: u8) -> KeyModifiers {
+    KeyModifiers::from_bits(u16::from(parameter.saturating_sub(1)))
+}
+
+thread_local! {
+    static CURRENT: OnceCell<Option<Rc<Terminfo>>> = const { OnceCell::new() };
+}
+
+/// Entry for `$TERM`, loaded once per thread.
+pub fn current() -> Option<Rc<Terminfo>> {
+    CURRENT.with(|current| current.get_or_init(|| Terminfo::from_env().map(Rc::new)).clone())
+}
+
+// =============================================================================
+// Database Lookup
+// =============================================================================
+
+/// Directories searched for entries, in ncurses order: `$TERMINFO`,
+/// `~/.terminfo`, `$TERMINFO_DIRS` (an empty element means the system
+/// directories) and then the system directories.
+fn search_dirs() -> Vec<PathBuf> {
+    const SYSTEM_DIRS: [&str; 5] = [
+        "/etc/terminfo",
+        "/lib/terminfo",
+        "/usr/share/terminfo",
+        "/usr/lib/terminfo",
+        "/usr/local/share/terminfo",
+    ];
+
+    let mut dirs = Vec::new();
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(Path::new(&home).join(".terminfo"));
+    }
+    if let Ok(list) = env::var("TERMINFO_DIRS") {
+        for dir in list.split(':') {
+            if dir.is_empty() {
+                dirs.extend(SYSTEM_DIRS.iter().map(PathBuf::from));
+            } else {
+                dirs.push(PathBuf::from(dir));
+            }
+        }
+    }
+    dirs.extend(SYSTEM_DIRS.iter().map(PathBuf::from));
+    dirs
+}
+
+/// Entries live under their first letter (`x/xterm`), or its hex code on
+/// case-insensitive file systems (`78/xterm`).
+fn entry_path(dir: &Path, term: &str) -> Option<PathBuf> {
+    let first = term.chars().next()?;
+    if term.contains('/') {
+        return None;
+    }
+    [first.to_string(), format!("{:02x}", u32::from(first))]
+        .into_iter()
+        .map(|sub| dir.join(sub).join(term))
+        .find(|path| path.is_file())
+}
+
+// =============================================================================
+// Binary Reader
+// =============================================================================
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| invalid("truncated terminfo entry"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn i16(&mut self) -> io::Result<i16> {
+        let bytes = self.bytes(2)?;
+        Ok(i16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn count(&mut self) -> io::Result<usize> {
+        usize::try_from(self.i16()?).map_err(|_| invalid("negative count in terminfo header"))
+    }
+
+    /// A number; negative values mean absent or cancelled.
+    fn number(&mut self, width: usize) -> io::Result<Option<i32>> {
+        let value = if width == 4 {
+            let bytes = self.bytes(4)?;
+            i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        } else {
+            i32::from(self.i16()?)
+        };
+        Ok((value >= 0).then_some(value))
+    }
+
+    /// Sections start on even offsets.
+    fn align(&mut self) {
+        self.pos += self.pos % 2;
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.pos)
+    }
+}
+
+/// NUL-terminated string at `offset`; negative offsets mean absent or cancelled.
+fn string_at(table: &[u8], offset: i16) -> Option<Vec<u8>> {
+    let start = usize::try_from(offset).ok()?;
+    let rest = table.get(start..)?;
+    let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+    Some(rest[..end].to_vec())
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Compile an entry in the 32-bit format; capabilities without a
+    /// standard name go to the extended section.
+    pub(crate) fn compile(names: &str, booleans: &[&str], numbers: &[(&str, i32)], strings: &[(&str, &[u8])]) -> Vec<u8> {
+        compile_with(MAGIC_32BIT, names, booleans, numbers, strings)
+    }
+
+    fn compile_with(magic: i16, names: &str, booleans: &[&str], numbers: &[(&str, i32)], strings: &[(&str, &[u8])]) -> Vec<u8> {
+        fn index(table: &[&str], name: &str) -> Option<usize> {
+            table.iter().position(|&n| n == name)
+        }
+        fn push_i16(out: &mut Vec<u8>, value: usize) {
+            out.extend_from_slice(&(value as i16).to_le_bytes());
+        }
+        fn push_number(out: &mut Vec<u8>, magic: i16, value: i32) {
+            if magic == MAGIC_32BIT {
+                out.extend_from_slice(&value.to_le_bytes());
+            } else {
+                out.extend_from_slice(&(value as i16).to_le_bytes());
+            }
+        }
+        fn align(out: &mut Vec<u8>) {
+            if out.len() % 2 == 1 {
+                out.push(0);
+            }
+        }
+
+        let standard = |table: &[&str], name: &str| index(table, name).is_some();
+        let bool_len = booleans.iter().filter_map(|n| index(&BOOLEAN_NAMES, n)).max().map_or(0, |i| i + 1);
+        let num_len = numbers.iter().filter_map(|(n, _)| index(&NUMBER_NAMES, n)).max().map_or(0, |i| i + 1);
+        let str_len = strings.iter().filter_map(|(n, _)| index(&STRING_NAMES, n)).max().map_or(0, |i| i + 1);
+
+        let mut bool_values = vec![0u8; bool_len];
+        for name in booleans.iter().filter(|n| standard(&BOOLEAN_NAMES, n)) {
+            bool_values[index(&BOOLEAN_NAMES, name).unwrap()] = 1;
+        }
+        let mut num_values = vec![-1; num_len];
+        for (name, value) in numbers.iter().filter(|(n, _)| standard(&NUMBER_NAMES, n)) {
+            num_values[index(&NUMBER_NAMES, name).unwrap()] = *value;
+        }
+        let mut offsets = vec![usize::from(u16::MAX); str_len];
+        let mut table = Vec::new();
+        for (name, value) in strings.iter().filter(|(n, _)| standard(&STRING_NAMES, n)) {
+            offsets[index(&STRING_NAMES, name).unwrap()] = table.len();
+            table.extend_from_slice(value);
+            table.push(0);
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&magic.to_le_bytes());
+        for count in [names.len() + 1, bool_len, num_len, str_len, table.len()] {
+            push_i16(&mut out, count);
+        }
+        out.extend_from_slice(names.as_bytes());
+        out.push(0);
+        out.extend_from_slice(&bool_values);
+        align(&mut out);
+        for value in num_values {
+            push_number(&mut out, magic, value);
+        }
+        for offset in offsets {
+            push_i16(&mut out, offset);
+        }
+        out.extend_from_slice(&table);
+
+        // Extended section
+        let ext_bools: Vec<&str> = booleans.iter().copied().filter(|n| !standard(&BOOLEAN_NAMES, n)).collect();
+        let ext_nums: Vec<_> = numbers.iter().filter(|(n, _)| !standard(&NUMBER_NAMES, n)).collect();
+        let ext_strs: Vec<_> = strings.iter().filter(|(n, _)| !standard(&STRING_NAMES, n)).collect();
+        if ext_bools.is_empty() && ext_nums.is_empty() && ext_strs.is_empty() {
+            return out;
+        }
+
+        let mut table = Vec::new();
+        let mut value_offsets = Vec::new();
+        for (_, value) in &ext_strs {
+            value_offsets.push(table.len());
+            table.extend_from_slice(value);
+            table.push(0);
+        }
+        let names_start = table.len();
+        let mut name_offsets = Vec::new();
+        let all_names = ext_bools.iter().copied().chain(ext_nums.iter().map(|(n, _)| *n)).chain(ext_strs.iter().map(|(n, _)| *n));
+        for name in all_names {
+            name_offsets.push(table.len() - names_start);
+            table.extend_from_slice(name.as_bytes());
+            table.push(0);
+        }
+
+        align(&mut out);
+        let items = value_offsets.len() + name_offsets.len();
+        for count in [ext_bools.len(), ext_nums.len(), ext_strs.len(), items, table.len()] {
+            push_i16(&mut out, count);
+        }
+        out.extend(ext_bools.iter().map(|_| 1u8));
+        align(&mut out);
+        for (_, value) in &ext_nums {
+            push_number(&mut out, magic, *value);
+        }
+        for offset in value_offsets.into_iter().chain(name_offsets) {
+            push_i16(&mut out, offset);
+        }
+        out.extend_from_slice(&table);
+        out
+    }
+
+    fn xterm_like(magic: i16) -> Terminfo {
+        let entry = compile_with(
+            magic,
+            "xterm-256color|xterm with 256 colors",
+            &["am", "bce", "XT", "Tc"],
+            &[("cols", 80), ("colors", 256), ("pairs", 0x7FFF)],
+            &[
+                ("kcuu1", b"\x1BOA"),
+                ("kf1", b"\x1BOP"),
+                ("kf12", b"\x1B[24~"),
+                ("kdch1", b"\x1B[3~"),
+                ("kLFT", b"\x1B[1;2D"),
+                ("kLFT5", b"\x1B[1;5D"),
+                ("BE", b"\x1B[?2004h"),
+            ],
+        );
+        Terminfo::parse(&entry).unwrap()
+    }
+
+    #[test]
+    fn test_parse_standard_and_extended() {
+        for magic in [MAGIC_LEGACY, MAGIC_32BIT] {
+            let info = xterm_like(magic);
+            assert_eq!(info.name(), "xterm-256color");
+            assert_eq!(info.names[1], "xterm with 256 colors");
+            assert!(info.flag("am") && info.flag("bce") && !info.flag("xenl"));
+            assert_eq!(info.number("cols"), Some(80));
+            assert_eq!(info.number("lines"), None);
+            assert_eq!(info.colors(), 256);
+            assert_eq!(info.string("kcuu1"), Some(&b"\x1BOA"[..]));
+            assert_eq!(info.string("kcud1"), None);
+
+            // Extended capabilities
+            assert!(info.flag("XT") && info.flag("Tc") && info.true_color());
+            assert_eq!(info.string("BE"), Some(&b"\x1B[?2004h"[..]));
+            assert!(!info.has("RGB"));
+        }
+    }
+
+    #[test]
+    fn test_direct_color_needs_32bit_numbers() {
+        let info = Terminfo::parse(&compile("xterm-direct", &["RGB"], &[("colors", 0x100_0000)], &[])).unwrap();
+        assert_eq!(info.colors(), 0x100_0000);
+        assert!(info.true_color());
+    }
+
+    #[test]
+    fn test_key_sequences() {
+        let info = xterm_like(MAGIC_32BIT);
+        assert_eq!(info.key_for(b"\x1BOA"), Some(KeyEvent::simple(Key::Up)));
+        assert_eq!(info.key_for(b"\x1BOP"), Some(KeyEvent::simple(Key::F(1))));
+        assert_eq!(info.key_for(b"\x1B[24~"), Some(KeyEvent::simple(Key::F(12))));
+        assert_eq!(info.key_for(b"\x1B[3~"), Some(KeyEvent::simple(Key::Delete)));
+
+        let shift = KeyModifiers { shift: true, ..KeyModifiers::NONE };
+        let ctrl = KeyModifiers { ctrl: true, ..KeyModifiers::NONE };
+        assert_eq!(info.key_for(b"\x1B[1;2D"), Some(KeyEvent::new(Key::Left, shift)));
+        assert_eq!(info.key_for(b"\x1B[1;5D"), Some(KeyEvent::new(Key::Left, ctrl)));
+        assert_eq!(info.key_for(b"\x1B[9~"), None);
+    }
+
+    #[test]
+    fn test_rejects_malformed_entries() {
+        assert!(Terminfo::parse(b"").is_err());
+        assert!(Terminfo::parse(b"\x00\x00\x00\x00").is_err());
+
+        let entry = compile("vt100", &["am"], &[("cols", 80)], &[("cup", b"\x1B[H")]);
+        for len in 0..entry.len() - 1 {
+            assert!(Terminfo::parse(&entry[..len]).is_err(), "accepted a {len}-byte prefix");
+        }
+        assert!(Terminfo::parse(&entry).is_ok());
+    }
+
+    #[test]
+    fn test_entry_path_layouts() {
+        let dir = env::temp_dir().join(format!("tuiuiu-terminfo-{}", std::process::id()));
+        fs::create_dir_all(dir.join("78")).unwrap();
+        fs::write(dir.join("78").join("xterm-test"), compile("xterm-test", &[], &[], &[])).unwrap();
+
+        let path = entry_path(&dir, "xterm-test").unwrap();
+        assert!(path.ends_with("78/xterm-test"));
+        assert_eq!(Terminfo::parse(&fs::read(path).unwrap()).unwrap().name(), "xterm-test");
+        assert!(entry_path(&dir, "../xterm-test").is_none());
+        assert!(entry_path(&dir, "missing").is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -6,6 +6,8 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use crate::core::terminal::{Key, KeyModifiers};
 use crate::core::hotkeys::{parse_hotkey, ParsedHotkey};
+use crate::core::capabilities::use_unicode;
+use crate::utils::glyphs::glyph;
 
 /// Hotkey binding.
 #[derive(Debug, Clone)]
@@ -126,13 +128,13 @@ pub fn format_hotkey(pattern: &str) -> String {
         .replace("ctrl", "Ctrl")
         .replace("alt", "Alt")
         .replace("shift", "Shift")
-        .replace("cmd", "⌘")
+        .replace("cmd", glyph("⌘", "Cmd"))
         .replace("+", " + ")
 }
 
 /// Format a hotkey for the current platform.
 pub fn format_hotkey_platform(pattern: &str) -> String {
-    if is_mac() {
+    if is_mac() && use_unicode() {
         pattern
             .replace("ctrl", "⌃")
             .replace("alt", "⌥")
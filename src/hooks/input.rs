@@ -4,7 +4,7 @@
 
 use std::cell::RefCell;
 use std::rc::Rc;
-use crate::core::terminal::{Key, KeyModifiers, KeyEvent};
+use crate::core::terminal::{Key, KeyModifiers, KeyEvent, KeyEventKind};
 
 /// Input handler function type.
 pub type InputHandler = Box<dyn Fn(&Key, &KeyModifiers)>;
@@ -53,7 +53,12 @@ where
 }
 
 /// Dispatch a key event to all handlers.
+///
+/// Handlers see presses and repeats; key releases are skipped.
 pub fn dispatch_key_event(event: &KeyEvent) {
+    if event.kind == KeyEventKind::Release {
+        return;
+    }
     INPUT_HANDLERS.with(|handlers| {
         for handler in handlers.borrow().iter() {
             handler(&event.key, &event.modifiers);
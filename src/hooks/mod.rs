@@ -8,6 +8,7 @@
 //! - `use_mouse`: Mouse event handling
 //! - `use_focus`: Focus management
 //! - `use_app`: Application context
+//! - `use_viewport`, `use_breakpoint`: Responsive layouts
 
 mod state;
 mod effects;
@@ -22,7 +23,7 @@ pub use effects::{use_effect, use_memo, use_callback, use_mount, use_cleanup};
 pub use input::{use_input, use_key, dispatch_key_event, clear_input_handlers, key_matches, InputHandler};
 pub use mouse::{use_mouse, dispatch_mouse_event, clear_mouse_handlers, use_mouse_position, MouseHandler, MousePosition};
 pub use focus::{use_focus, use_focus_manager, FocusOptions, FocusResult};
-pub use terminal::{use_terminal_size, use_fps, use_dimensions, UseFpsResult};
+pub use terminal::{use_terminal_size, use_viewport, use_breakpoint, use_fps, use_dimensions, UseFpsResult};
 pub use hotkeys::{
     use_hotkeys, HotkeyBinding, HotkeyHandler, HotkeyOptions,
     register_hotkey, trigger_hotkey, get_registered_hotkeys,
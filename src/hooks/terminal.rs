@@ -3,6 +3,7 @@
 //! Terminal-related hooks.
 
 use crate::core::terminal::get_terminal_size;
+use crate::core::responsive::{viewport_reader, Breakpoint};
 use crate::core::signals::{create_signal, create_effect, ReadSignal};
 use crate::core::tick::{get_fps, get_fps_metrics, FpsMetrics};
 
@@ -17,6 +18,18 @@ pub fn use_terminal_size() -> (ReadSignal<u16>, ReadSignal<u16>) {
     (w_signal, h_signal)
 }
 
+/// Get the viewport size as a signal that updates when the terminal is resized.
+pub fn use_viewport() -> ReadSignal<(u16, u16)> {
+    viewport_reader()
+}
+
+/// Whether the viewport currently matches a breakpoint.
+///
+/// Reading it inside an effect re-runs the effect on resize.
+pub fn use_breakpoint(breakpoint: Breakpoint) -> bool {
+    breakpoint.is_active()
+}
+
 /// FPS tracking result.
 pub struct UseFpsResult {
     /// Current FPS signal
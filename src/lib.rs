@@ -83,12 +83,17 @@ pub use core::signals::{
 
 pub use core::layout::{
     AlignContent, AlignItems, AlignSelf, FlexDirection, FlexWrap, JustifyContent, LayoutNode,
-    calculate_layout,
+    Overflow, Position, Display, GridTrack, GridPlacement, LayoutCache, calculate_layout,
+    calculate_layout_cached,
 };
 
-pub use core::renderer::{OutputBuffer, RenderContext, render_to_string};
+pub use core::renderer::{OutputBuffer, RenderContext, TextRenderOptions, render_to_string, render_to_text, render_with_context};
+pub use core::export::{ExportOptions, to_html, to_svg};
+pub use core::graphics::{GraphicsProtocol, RgbaImage};
 
-pub use core::app::{App, RenderOptions, render, render_once};
+pub use core::responsive::{Breakpoint, Responsive, set_viewport, viewport};
+
+pub use core::app::{App, RenderOptions, render, render_once, render_once_with_options};
 
 pub use core::terminal::{
     Key, KeyModifiers, MouseButton, MouseEvent, Terminal, TerminalEvent,
@@ -143,7 +148,7 @@ pub mod primitives;
 
 #[cfg(feature = "primitives")]
 pub use primitives::{
-    BoxComponent, Text, Spacer, Newline, Fragment, Divider, Canvas,
+    BoxComponent, Text, Spacer, Newline, Fragment, Divider, Canvas, Image,
     When, Each, Transform, Static, Slot,
 };
 
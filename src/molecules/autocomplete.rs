@@ -3,6 +3,7 @@
 //! Text input with suggestions.
 
 use crate::core::component::{VNode, BoxNode, BoxStyle, TextStyle, Color, NamedColor, BorderStyle};
+use crate::utils::glyphs::glyph;
 
 /// Autocomplete suggestion.
 #[derive(Debug, Clone)]
@@ -165,7 +166,7 @@ impl Autocomplete {
         };
 
         children.push(VNode::styled_text(
-            format!("🔍 {}_", display_value),
+            format!("{}{}_", glyph("🔍 ", "> "), display_value),
             TextStyle::color(input_color)
         ));
 
@@ -217,7 +218,7 @@ impl Autocomplete {
             }
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle {
                 border_style: Some(BorderStyle::Single),
@@ -226,6 +227,6 @@ impl Autocomplete {
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }))
     }
 }
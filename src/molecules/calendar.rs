@@ -195,7 +195,7 @@ impl Calendar {
             week += 1;
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle {
                 padding_left: Some(1),
@@ -203,6 +203,6 @@ impl Calendar {
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }))
     }
 }
@@ -3,6 +3,15 @@
 //! Data visualization components for terminal.
 
 use crate::core::component::{VNode, BoxNode, BoxStyle, TextStyle, Color, NamedColor};
+use crate::utils::glyphs::glyph;
+
+/// Eight levels from low to high, as block characters and in ASCII.
+const BLOCK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ASCII_LEVELS: [char; 8] = ['_', '.', ':', '-', '=', '+', '*', '#'];
+
+/// Series markers for line charts and their legends.
+const MARKERS: [char; 4] = ['●', '◆', '■', '▲'];
+const ASCII_MARKERS: [char; 4] = ['*', '+', 'x', 'o'];
 
 // =============================================================================
 // Sparkline
@@ -93,7 +102,7 @@ impl Sparkline {
         }
 
         // Sparkline characters (from low to high)
-        let chars = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let chars = glyph(BLOCK_LEVELS, ASCII_LEVELS);
 
         let min = self.data.iter().copied().fold(f64::INFINITY, f64::min);
         let max = self.data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
@@ -142,11 +151,11 @@ impl Sparkline {
             ));
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle::default(),
             ..Default::default()
-        })
+        }))
     }
 }
 
@@ -310,7 +319,7 @@ impl BarChart {
                         0
                     };
 
-                    let bar = "█".repeat(bar_len);
+                    let bar = glyph("█", "#").repeat(bar_len);
                     let color = item.color.unwrap_or(self.color);
 
                     let value_str = if self.show_values {
@@ -320,8 +329,9 @@ impl BarChart {
                     };
 
                     let line = format!(
-                        "{:>width$} │{}{}",
+                        "{:>width$} {}{}{}",
                         item.label,
+                        glyph('│', '|'),
                         bar,
                         value_str,
                         width = max_label_len
@@ -333,7 +343,7 @@ impl BarChart {
             BarOrientation::Vertical => {
                 // Vertical bars - more complex, using block characters
                 let height = 8;
-                let bar_chars = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+                let bar_chars = glyph(BLOCK_LEVELS, ASCII_LEVELS);
 
                 for row in (0..height).rev() {
                     let threshold = (row as f64 + 1.0) / height as f64;
@@ -343,7 +353,7 @@ impl BarChart {
                         let normalized = if max_value > 0.0 { item.value / max_value } else { 0.0 };
 
                         let char = if normalized >= threshold {
-                            '█'
+                            bar_chars[7]
                         } else if normalized > threshold - (1.0 / height as f64) {
                             let partial = ((normalized - (threshold - 1.0 / height as f64)) * height as f64 * 8.0) as usize;
                             bar_chars[partial.min(7)]
@@ -368,11 +378,11 @@ impl BarChart {
             }
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle::default(),
             ..Default::default()
-        })
+        }))
     }
 }
 
@@ -516,18 +526,19 @@ impl Gauge {
 
         let bar = match self.style {
             GaugeStyle::Bar => {
-                let filled_str = "█".repeat(filled);
-                let empty_str = "░".repeat(empty);
+                let filled_str = glyph("█", "#").repeat(filled);
+                let empty_str = glyph("░", "-").repeat(empty);
                 format!("{}{}", filled_str, empty_str)
             }
             GaugeStyle::Arc => {
                 // Simple arc representation
+                let (full, empty) = glyph(('◼', '◻'), ('#', 'o'));
                 let chars: Vec<char> = (0..self.width)
                     .map(|i| {
                         if (i as f64) < (self.width as f64 * percentage / 100.0) {
-                            '◼'
+                            full
                         } else {
-                            '◻'
+                            empty
                         }
                     })
                     .collect();
@@ -537,7 +548,7 @@ impl Gauge {
                 // Pie-like representation using Unicode
                 let segments = 8;
                 let filled_segments = (percentage / 100.0 * segments as f64) as usize;
-                let pie_chars = ['○', '◔', '◑', '◕', '●'];
+                let pie_chars = glyph(['○', '◔', '◑', '◕', '●'], ['.', 'o', 'O', '0', '@']);
                 let idx = (filled_segments * pie_chars.len() / segments).min(pie_chars.len() - 1);
                 pie_chars[idx].to_string()
             }
@@ -640,7 +651,7 @@ impl LineChart {
 
         // Plot each series
         for (series_idx, series) in self.data.iter().enumerate() {
-            let char = ['●', '◆', '■', '▲'][series_idx % 4];
+            let char = glyph(MARKERS, ASCII_MARKERS)[series_idx % 4];
 
             for (i, &value) in series.iter().enumerate() {
                 let x = (i * self.width as usize) / series.len().max(1);
@@ -667,7 +678,7 @@ impl LineChart {
         if self.show_legend && !self.labels.is_empty() {
             let legend_parts: Vec<String> = self.labels.iter().enumerate()
                 .map(|(i, label)| {
-                    let char = ['●', '◆', '■', '▲'][i % 4];
+                    let char = glyph(MARKERS, ASCII_MARKERS)[i % 4];
                     format!("{} {}", char, label)
                 })
                 .collect();
@@ -678,11 +689,11 @@ impl LineChart {
             ));
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle::default(),
             ..Default::default()
-        })
+        }))
     }
 }
 
@@ -755,7 +766,7 @@ impl Heatmap {
             return VNode::styled_text("No data", TextStyle::color(Color::Named(NamedColor::Gray)));
         }
 
-        let heat_chars = ['░', '▒', '▓', '█'];
+        let heat_chars = glyph(['░', '▒', '▓', '█'], ['.', ':', '*', '#']);
 
         let all_values: Vec<f64> = self.data.iter().flatten().copied().collect();
         let min = all_values.iter().copied().fold(f64::INFINITY, f64::min);
@@ -781,10 +792,10 @@ impl Heatmap {
             ));
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle::default(),
             ..Default::default()
-        })
+        }))
     }
 }
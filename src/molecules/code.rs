@@ -3,6 +3,7 @@
 //! Syntax-highlighted code display.
 
 use crate::core::component::{VNode, BoxNode, BoxStyle, TextStyle, Color, NamedColor, BorderStyle};
+use crate::utils::glyphs::glyph;
 
 /// Code block component.
 #[derive(Debug, Clone)]
@@ -152,7 +153,7 @@ impl CodeBlock {
             let (content, color) = self.highlight_syntax(line);
 
             let formatted = if self.show_line_numbers {
-                format!("{:>width$} │ {}", line_num, content, width = line_num_width)
+                format!("{:>width$} {} {}", line_num, glyph('│', '|'), content, width = line_num_width)
             } else {
                 content
             };
@@ -166,7 +167,7 @@ impl CodeBlock {
             children.push(VNode::styled_text(formatted, style));
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle {
                 border_style: Some(BorderStyle::Single),
@@ -175,7 +176,7 @@ impl CodeBlock {
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }))
     }
 }
 
@@ -237,7 +238,7 @@ impl Markdown {
             // Bullet points
             else if trimmed.starts_with("- ") || trimmed.starts_with("* ") {
                 children.push(VNode::styled_text(
-                    format!("  • {}", &trimmed[2..]),
+                    format!("  {} {}", glyph('•', '*'), &trimmed[2..]),
                     TextStyle::default()
                 ));
             }
@@ -259,14 +260,14 @@ impl Markdown {
             // Blockquotes
             else if trimmed.starts_with("> ") {
                 children.push(VNode::styled_text(
-                    format!("│ {}", &trimmed[2..]),
+                    format!("{} {}", glyph('│', '|'), &trimmed[2..]),
                     TextStyle { color: Some(Color::Named(NamedColor::Gray)), italic: true, ..Default::default() }
                 ));
             }
             // Horizontal rule
             else if trimmed == "---" || trimmed == "***" {
                 children.push(VNode::styled_text(
-                    "─".repeat(self.width.unwrap_or(40) as usize),
+                    glyph("─", "-").repeat(self.width.unwrap_or(40) as usize),
                     TextStyle::color(Color::Named(NamedColor::Gray))
                 ));
             }
@@ -285,10 +286,10 @@ impl Markdown {
             }
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle::default(),
             ..Default::default()
-        })
+        }))
     }
 }
@@ -3,7 +3,8 @@
 //! Dropdown selection component.
 
 use crate::core::component::{VNode, BoxNode, BoxStyle, TextStyle, Color, NamedColor, BorderStyle};
-use crate::core::layout::FlexDirection;
+use crate::core::layout::{FlexDirection, Position, Size};
+use crate::utils::glyphs::glyph;
 
 /// A single select option.
 #[derive(Debug, Clone)]
@@ -144,7 +145,7 @@ impl Select {
             self.placeholder.clone()
         };
 
-        let arrow = if self.open { "▲" } else { "▼" };
+        let arrow = if self.open { glyph("▲", "^") } else { glyph("▼", "v") };
         let width = self.width.unwrap_or(20);
 
         let mut children = Vec::new();
@@ -163,7 +164,7 @@ impl Select {
             Color::Named(NamedColor::Gray)
         };
 
-        children.push(VNode::styled_text(header_text, TextStyle::color(header_color)));
+        let header = VNode::styled_text(header_text, TextStyle::color(header_color));
 
         // Options list (when open)
         if self.open {
@@ -171,7 +172,7 @@ impl Select {
                 let is_selected = self.selected == Some(idx);
                 let is_focused = self.focused_index == idx;
 
-                let prefix = if is_selected { "● " } else { "  " };
+                let prefix = if is_selected { glyph("● ", "* ") } else { "  " };
                 let text = format!("{}{}", prefix, opt.label);
 
                 let style = if opt.disabled {
@@ -188,17 +189,67 @@ impl Select {
             }
         }
 
-        VNode::Box(BoxNode {
-            children,
+        with_dropdown(header, children)
+    }
+}
+
+/// Lay out a select field with its open options floating below it.
+///
+/// The options are absolutely positioned under the field and drawn as a
+/// layer, so opening a select doesn't push the content after it down. They
+/// hang from an empty anchor box after the field, so they start wherever
+/// the field ends, however tall it is.
+fn with_dropdown(header: VNode, options: Vec<VNode>) -> VNode {
+    let field = VNode::Box(Box::new(BoxNode {
+        children: vec![header],
+        style: BoxStyle {
+            border_style: Some(BorderStyle::Single),
+            padding_left: Some(1),
+            padding_right: Some(1),
+            ..Default::default()
+        },
+        ..Default::default()
+    }));
+
+    let mut children = vec![field];
+    if !options.is_empty() {
+        let list = VNode::Box(Box::new(BoxNode {
+            children: options,
             style: BoxStyle {
+                flex_direction: Some(FlexDirection::Column),
                 border_style: Some(BorderStyle::Single),
                 padding_left: Some(1),
                 padding_right: Some(1),
+                // Hide whatever the list floats over
+                background: Some(Color::Default),
+                position: Some(Position::Absolute),
+                top: Some(0),
+                left: Some(0),
+                right: Some(0),
+                z_index: Some(1),
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }));
+        children.push(VNode::Box(Box::new(BoxNode {
+            children: vec![list],
+            style: BoxStyle {
+                position: Some(Position::Relative),
+                height: Some(Size::Fixed(0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })));
     }
+
+    VNode::Box(Box::new(BoxNode {
+        children,
+        style: BoxStyle {
+            position: Some(Position::Relative),
+            ..BoxStyle::column()
+        },
+        ..Default::default()
+    }))
 }
 
 /// MultiSelect component for multiple selections.
@@ -289,14 +340,13 @@ impl MultiSelect {
             TextStyle::default()
         };
 
-        let mut children = vec![
-            VNode::styled_text(format!("{} ▼", display), header_style)
-        ];
+        let header = VNode::styled_text(format!("{} {}", display, glyph("▼", "v")), header_style);
+        let mut children = Vec::new();
 
         if self.open && !self.disabled {
             for (idx, opt) in self.options.iter().enumerate() {
                 let is_selected = self.selected.contains(&idx);
-                let checkbox = if is_selected { "☑" } else { "☐" };
+                let checkbox = if is_selected { glyph("☑", "[x]") } else { glyph("☐", "[ ]") };
                 let text = format!("{} {}", checkbox, opt.label);
 
                 let style = if is_selected {
@@ -309,16 +359,7 @@ impl MultiSelect {
             }
         }
 
-        VNode::Box(BoxNode {
-            children,
-            style: BoxStyle {
-                border_style: Some(BorderStyle::Single),
-                padding_left: Some(1),
-                padding_right: Some(1),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
+        with_dropdown(header, children)
     }
 }
 
@@ -395,7 +436,7 @@ impl RadioGroup {
 
         for (idx, opt) in self.options.iter().enumerate() {
             let is_selected = self.selected == Some(idx);
-            let radio = if is_selected { "◉" } else { "○" };
+            let radio = if is_selected { glyph("◉", "(*)") } else { glyph("○", "( )") };
             let text = format!("{} {}", radio, opt.label);
 
             let style = if opt.disabled {
@@ -409,7 +450,7 @@ impl RadioGroup {
             children.push(VNode::styled_text(text, style));
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle {
                 flex_direction: Some(if self.horizontal {
@@ -421,6 +462,6 @@ impl RadioGroup {
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }))
     }
 }
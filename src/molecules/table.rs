@@ -227,7 +227,7 @@ impl Table {
             ));
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle {
                 border_style: if self.border { Some(BorderStyle::Single) } else { None },
@@ -236,6 +236,6 @@ impl Table {
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }))
     }
 }
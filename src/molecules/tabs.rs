@@ -191,7 +191,7 @@ impl Tabs {
             ));
         }
 
-        let tab_bar = VNode::Box(BoxNode {
+        let tab_bar = VNode::Box(Box::new(BoxNode {
             children: tab_bar_children,
             style: BoxStyle {
                 flex_direction: Some(FlexDirection::Row),
@@ -199,14 +199,14 @@ impl Tabs {
                 ..Default::default()
             },
             ..Default::default()
-        });
+        }));
 
         // Content area
         let content = self.tabs.get(self.active)
             .and_then(|t| t.content.clone())
             .unwrap_or(VNode::Empty);
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children: vec![tab_bar, content],
             style: BoxStyle {
                 flex_direction: Some(FlexDirection::Column),
@@ -214,6 +214,6 @@ impl Tabs {
                 ..Default::default()
             },
             ..Default::default()
-        })
+        }))
     }
 }
@@ -3,6 +3,7 @@
 //! Hierarchical tree view.
 
 use crate::core::component::{VNode, BoxNode, BoxStyle, TextStyle, Color, NamedColor};
+use crate::utils::glyphs::{glyph, to_ascii};
 
 /// A tree node.
 #[derive(Debug, Clone)]
@@ -178,9 +179,9 @@ impl Tree {
         let connector = if depth == 0 {
             ""
         } else if is_last {
-            if self.show_lines { "└─" } else { "  " }
+            if self.show_lines { glyph("└─", "`-") } else { "  " }
         } else {
-            if self.show_lines { "├─" } else { "  " }
+            if self.show_lines { glyph("├─", "|-") } else { "  " }
         };
 
         // Expand/collapse icon
@@ -191,9 +192,11 @@ impl Tree {
         } else {
             &self.folder_icons.0
         };
+        let expand_icon = glyph(expand_icon.to_string(), to_ascii(expand_icon));
 
-        // Node icon
+        // Node icon; pictographs have no ASCII rendition and are dropped
         let icon = node.icon.as_deref().unwrap_or("");
+        let icon = if icon.is_ascii() { icon } else { glyph(icon, "") };
 
         // Build label
         let label = if icon.is_empty() {
@@ -225,7 +228,7 @@ impl Tree {
             let child_prefix = if depth == 0 {
                 String::new()
             } else {
-                let continuation = if is_last { "  " } else { if self.show_lines { "│ " } else { "  " } };
+                let continuation = if is_last { "  " } else { if self.show_lines { glyph("│ ", "| ") } else { "  " } };
                 format!("{}{}", prefix, continuation)
             };
 
@@ -249,11 +252,11 @@ impl Tree {
             children.extend(self.render_node(node, "", is_last, 0));
         }
 
-        VNode::Box(BoxNode {
+        VNode::Box(Box::new(BoxNode {
             children,
             style: BoxStyle::default(),
             ..Default::default()
-        })
+        }))
     }
 }
 
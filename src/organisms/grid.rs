@@ -0,0 +1,183 @@
+//! Grid
+//!
+//! Dashboard-style grid of cells, backed by the layout engine's grid mode.
+
+use crate::core::component::{VNode, BoxNode, BoxStyle};
+use crate::core::layout::{Display, GridPlacement, GridTrack};
+
+/// Arranges children in rows and columns.
+///
+/// Tracks can be fixed, percentages, `fr` shares or auto-sized; children are
+/// auto-placed in order unless given a position or a named area.
+///
+/// ```ignore
+/// Grid::new()
+///     .columns(GridTrack::parse_list("20 1fr"))
+///     .rows(GridTrack::parse_list("1 1fr 1"))
+///     .areas(["header header", "nav main", "footer footer"])
+///     .area("header", header)
+///     .area("nav", nav)
+///     .area("main", main)
+///     .area("footer", footer)
+///     .build()
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Grid {
+    columns: Vec<GridTrack>,
+    rows: Vec<GridTrack>,
+    areas: Vec<String>,
+    auto_rows: Option<GridTrack>,
+    row_gap: u16,
+    column_gap: u16,
+    cells: Vec<VNode>,
+}
+
+impl Grid {
+    /// Create an empty grid.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set column tracks.
+    pub fn columns(mut self, tracks: impl IntoIterator<Item = GridTrack>) -> Self {
+        self.columns = tracks.into_iter().collect();
+        self
+    }
+
+    /// Use `count` equal-width columns.
+    pub fn equal_columns(mut self, count: usize) -> Self {
+        self.columns = vec![GridTrack::Fr(1.0); count];
+        self
+    }
+
+    /// Set row tracks.
+    pub fn rows(mut self, tracks: impl IntoIterator<Item = GridTrack>) -> Self {
+        self.rows = tracks.into_iter().collect();
+        self
+    }
+
+    /// Set the size of rows added beyond the template.
+    pub fn auto_rows(mut self, track: GridTrack) -> Self {
+        self.auto_rows = Some(track);
+        self
+    }
+
+    /// Name areas of the grid, one string per row (`.` leaves a cell unnamed).
+    pub fn areas<I, S>(mut self, rows: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.areas = rows.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set gap between rows and columns.
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.row_gap = gap;
+        self.column_gap = gap;
+        self
+    }
+
+    /// Set gap between rows.
+    pub fn row_gap(mut self, gap: u16) -> Self {
+        self.row_gap = gap;
+        self
+    }
+
+    /// Set gap between columns.
+    pub fn column_gap(mut self, gap: u16) -> Self {
+        self.column_gap = gap;
+        self
+    }
+
+    /// Add a child in the next free cell.
+    pub fn child(mut self, node: impl Into<VNode>) -> Self {
+        self.cells.push(node.into());
+        self
+    }
+
+    /// Add a child at explicit column and row placements.
+    pub fn cell(mut self, node: impl Into<VNode>, column: GridPlacement, row: GridPlacement) -> Self {
+        self.cells.push(grid_item(node.into(), |style| {
+            style.grid_column = Some(column);
+            style.grid_row = Some(row);
+        }));
+        self
+    }
+
+    /// Add a child filling a named area.
+    pub fn area(mut self, name: impl Into<String>, node: impl Into<VNode>) -> Self {
+        let name = name.into();
+        self.cells.push(grid_item(node.into(), |style| style.grid_area = Some(name)));
+        self
+    }
+
+    /// Build the VNode.
+    pub fn build(self) -> VNode {
+        VNode::Box(Box::new(BoxNode {
+            children: self.cells,
+            style: BoxStyle {
+                display: Some(Display::Grid),
+                grid_template_columns: Some(self.columns),
+                grid_template_rows: Some(self.rows),
+                grid_template_areas: Some(self.areas),
+                grid_auto_rows: self.auto_rows,
+                row_gap: Some(self.row_gap),
+                column_gap: Some(self.column_gap),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+    }
+}
+
+impl From<Grid> for VNode {
+    fn from(grid: Grid) -> VNode {
+        grid.build()
+    }
+}
+
+/// Apply grid item placement to a node, wrapping it in a box if needed.
+fn grid_item(node: VNode, place: impl FnOnce(&mut BoxStyle)) -> VNode {
+    match node {
+        VNode::Box(mut box_node) => {
+            place(&mut box_node.style);
+            VNode::Box(box_node)
+        }
+        other => {
+            let mut style = BoxStyle::column();
+            place(&mut style);
+            VNode::Box(Box::new(BoxNode {
+                children: vec![other],
+                style,
+                ..Default::default()
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::renderer::render_to_string;
+    use crate::utils::ansi::strip_ansi;
+
+    #[test]
+    fn test_grid_areas_render() {
+        let grid = Grid::new()
+            .columns(GridTrack::parse_list("4 1fr"))
+            .rows(GridTrack::parse_list("1 1fr"))
+            .areas(["head head", "nav main"])
+            .area("main", VNode::text("main"))
+            .area("nav", VNode::text("nav"))
+            .area("head", VNode::text("title"))
+            .column_gap(1);
+
+        let output = strip_ansi(&render_to_string(&grid.build(), 12, 3));
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0].trim_end(), "title");
+        assert_eq!(lines[1].trim_end(), "nav  main");
+    }
+}
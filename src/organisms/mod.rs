@@ -1,13 +1,21 @@
-//! Organisms - Stub module (to be implemented)
+//! Organisms - Complex UI sections
+//!
+//! Components composed from molecules:
+//! - OverlayStack: Layered modals, toasts and tooltips
+//! - Grid: Rows and columns of cells for dashboards
 
-// Placeholder types for compilation
+mod grid;
+mod overlay_stack;
+
+pub use grid::Grid;
+pub use overlay_stack::{OverlayStack, OverlayPlacement};
+
+// Stubs for other organisms
 pub struct Modal;
 pub struct CommandPalette;
 pub struct DataTable;
 pub struct FileManager;
 pub struct SplitPanel;
 pub struct ScrollArea;
-pub struct Grid;
-pub struct OverlayStack;
 pub struct Notification;
 pub struct Toast;
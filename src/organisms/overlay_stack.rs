@@ -0,0 +1,205 @@
+//! Overlay Stack
+//!
+//! Floats modals, toasts and tooltips above a base view.
+
+use crate::core::component::{VNode, BoxNode, BoxStyle, Backdrop};
+use crate::core::layout::{AlignItems, JustifyContent, Position};
+
+/// Where an overlay is anchored within the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlayPlacement {
+    /// Centered (modals, dialogs)
+    #[default]
+    Center,
+    /// Top edge, centered horizontally
+    Top,
+    /// Bottom edge, centered horizontally
+    Bottom,
+    /// Top-left corner
+    TopLeft,
+    /// Top-right corner (toasts)
+    TopRight,
+    /// Bottom-left corner
+    BottomLeft,
+    /// Bottom-right corner
+    BottomRight,
+    /// Fixed offset from the top-left corner (tooltips)
+    At(u16, u16),
+}
+
+/// Layers overlays above base content.
+///
+/// Each overlay is absolutely positioned inside the stack and drawn with a
+/// z-index above everything added before it. Overlays are transparent
+/// outside their own boxes; give them a background to hide what's beneath,
+/// or a backdrop to dim it.
+#[derive(Debug, Clone, Default)]
+pub struct OverlayStack {
+    content: Vec<VNode>,
+    overlays: Vec<(VNode, OverlayPlacement, Option<Backdrop>)>,
+}
+
+impl OverlayStack {
+    /// Create an empty overlay stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add base content.
+    pub fn content(mut self, node: impl Into<VNode>) -> Self {
+        self.content.push(node.into());
+        self
+    }
+
+    /// Add an overlay above everything added so far.
+    pub fn overlay(mut self, node: impl Into<VNode>, placement: OverlayPlacement) -> Self {
+        self.overlays.push((node.into(), placement, None));
+        self
+    }
+
+    /// Tint everything beneath the last added overlay.
+    pub fn backdrop(mut self, backdrop: Backdrop) -> Self {
+        if let Some(overlay) = self.overlays.last_mut() {
+            overlay.2 = Some(backdrop);
+        }
+        self
+    }
+
+    /// Dim everything beneath the last added overlay, as for a modal.
+    pub fn dim_backdrop(self) -> Self {
+        self.backdrop(Backdrop::dim())
+    }
+
+    /// Build the VNode.
+    pub fn build(self) -> VNode {
+        let mut children = self.content;
+
+        for (i, (node, placement, backdrop)) in self.overlays.into_iter().enumerate() {
+            let z_index = Some(i as i32 + 1);
+
+            let style = if let OverlayPlacement::At(x, y) = placement {
+                BoxStyle {
+                    position: Some(Position::Absolute),
+                    left: Some(x),
+                    top: Some(y),
+                    z_index,
+                    backdrop,
+                    ..Default::default()
+                }
+            } else {
+                // A transparent layer covering the stack aligns the overlay
+                let (justify, align) = match placement {
+                    OverlayPlacement::Top => (JustifyContent::FlexStart, AlignItems::Center),
+                    OverlayPlacement::Bottom => (JustifyContent::FlexEnd, AlignItems::Center),
+                    OverlayPlacement::TopLeft => (JustifyContent::FlexStart, AlignItems::FlexStart),
+                    OverlayPlacement::TopRight => (JustifyContent::FlexStart, AlignItems::FlexEnd),
+                    OverlayPlacement::BottomLeft => (JustifyContent::FlexEnd, AlignItems::FlexStart),
+                    OverlayPlacement::BottomRight => (JustifyContent::FlexEnd, AlignItems::FlexEnd),
+                    _ => (JustifyContent::Center, AlignItems::Center),
+                };
+                BoxStyle {
+                    justify_content: Some(justify),
+                    align_items: Some(align),
+                    position: Some(Position::Absolute),
+                    top: Some(0),
+                    right: Some(0),
+                    bottom: Some(0),
+                    left: Some(0),
+                    z_index,
+                    backdrop,
+                    ..BoxStyle::column()
+                }
+            };
+
+            children.push(VNode::Box(Box::new(BoxNode {
+                children: vec![node],
+                style,
+                ..Default::default()
+            })));
+        }
+
+        VNode::Box(Box::new(BoxNode {
+            children,
+            style: BoxStyle {
+                position: Some(Position::Relative),
+                ..BoxStyle::column()
+            },
+            ..Default::default()
+        }))
+    }
+}
+
+impl From<OverlayStack> for VNode {
+    fn from(stack: OverlayStack) -> VNode {
+        stack.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::component::{BorderStyle, Color};
+    use crate::core::renderer::render_to_string;
+    use crate::utils::ansi::strip_ansi;
+
+    #[test]
+    fn test_overlay_floats_above_content() {
+        let modal = VNode::Box(Box::new(BoxNode {
+            children: vec![VNode::text("Hi")],
+            style: BoxStyle {
+                background: Some(Color::Default),
+                ..BoxStyle::row().with_border(BorderStyle::Single)
+            },
+            ..Default::default()
+        }));
+        let stack = OverlayStack::new()
+            .content(VNode::text("xxxxxxxx"))
+            .content(VNode::text("xxxxxxxx"))
+            .content(VNode::text("xxxxxxxx"))
+            .overlay(modal, OverlayPlacement::Center);
+
+        let output = strip_ansi(&render_to_string(&stack.build(), 8, 5));
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0], "xxxxxxxx");
+        assert_eq!(lines[1], "xx┌──┐xx");
+        assert_eq!(lines[2], "xx│Hi│xx");
+        assert_eq!(lines[3], "  └──┘  ");
+    }
+
+    #[test]
+    fn test_backdrop_dims_content_beneath() {
+        use crate::core::capabilities::{set_color_support, ColorSupport};
+        use crate::core::renderer::{render_to_buffer, RenderContext};
+
+        set_color_support(ColorSupport::TrueColor);
+
+        let modal = VNode::Box(Box::new(BoxNode {
+            children: vec![VNode::text("ok")],
+            style: BoxStyle { background: Some(Color::Rgb(0, 0, 200)), ..BoxStyle::row() },
+            ..Default::default()
+        }));
+        let content = VNode::Box(Box::new(BoxNode {
+            children: vec![VNode::text("xxxx")],
+            style: BoxStyle { background: Some(Color::Rgb(200, 100, 0)), ..BoxStyle::row() },
+            ..Default::default()
+        }));
+        let stack = OverlayStack::new()
+            .content(content)
+            .overlay(modal, OverlayPlacement::At(1, 0))
+            .dim_backdrop();
+
+        let buffer = render_to_buffer(&stack.build(), &RenderContext::new(4, 1));
+        let behind = buffer.get(0, 0).unwrap();
+        assert_eq!((behind.char, behind.bg), ('x', Color::Rgb(100, 50, 0)));
+        // The overlay itself is drawn after the backdrop, at full strength
+        let front = buffer.get(1, 0).unwrap();
+        assert_eq!((front.char, front.bg), ('o', Color::Rgb(0, 0, 200)));
+
+        // Without colors the backdrop dims with the SGR attribute
+        set_color_support(ColorSupport::None);
+        let stack = OverlayStack::new().content(VNode::text("xxxx")).overlay(VNode::text("ok"), OverlayPlacement::At(1, 0)).dim_backdrop();
+        let buffer = render_to_buffer(&stack.build(), &RenderContext::new(4, 1));
+        assert!(buffer.get(0, 0).unwrap().dim && !buffer.get(1, 0).unwrap().dim);
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! A container component with flexbox layout capabilities.
 
-use crate::core::component::{VNode, BoxNode, BoxStyle, Color, BorderStyle, Child, children_to_vnodes};
-use crate::core::layout::{FlexDirection, JustifyContent, AlignItems, FlexWrap, Size};
+use crate::core::component::{VNode, BoxNode, BoxStyle, Backdrop, BorderTitle, Color, BorderStyle, Gradient, Child, children_to_vnodes};
+use crate::core::responsive::Breakpoint;
+use crate::core::layout::{
+    FlexDirection, JustifyContent, AlignItems, AlignSelf, AlignContent, FlexWrap, Overflow, Position, Size,
+    Display, GridTrack, GridPlacement,
+};
 
 /// Box component builder.
 #[derive(Debug, Clone, Default)]
@@ -11,6 +15,8 @@ pub struct BoxComponent {
     style: BoxStyle,
     children: Vec<VNode>,
     id: Option<u64>,
+    /// Bordered children share the edges between them
+    collapse_borders: bool,
 }
 
 impl BoxComponent {
@@ -55,6 +61,18 @@ impl BoxComponent {
         self
     }
 
+    /// Set align content (distribution of wrapped lines).
+    pub fn align_content(mut self, value: AlignContent) -> Self {
+        self.style.align_content = Some(value);
+        self
+    }
+
+    /// Set align self (overrides the parent's align items).
+    pub fn align_self(mut self, value: AlignSelf) -> Self {
+        self.style.align_self = Some(value);
+        self
+    }
+
     /// Set flex wrap.
     pub fn flex_wrap(mut self, value: FlexWrap) -> Self {
         self.style.flex_wrap = Some(value);
@@ -67,6 +85,18 @@ impl BoxComponent {
         self
     }
 
+    /// Set the gap between rows (overrides `gap`).
+    pub fn row_gap(mut self, value: u16) -> Self {
+        self.style.row_gap = Some(value);
+        self
+    }
+
+    /// Set the gap between columns (overrides `gap`).
+    pub fn column_gap(mut self, value: u16) -> Self {
+        self.style.column_gap = Some(value);
+        self
+    }
+
     /// Set flex grow.
     pub fn flex_grow(mut self, value: f32) -> Self {
         self.style.flex_grow = Some(value);
@@ -79,6 +109,72 @@ impl BoxComponent {
         self
     }
 
+    /// Set flex basis.
+    pub fn flex_basis(mut self, value: u16) -> Self {
+        self.style.flex_basis = Some(Size::Fixed(value));
+        self
+    }
+
+    /// Set flex basis as percentage of the container.
+    pub fn flex_basis_percent(mut self, value: f32) -> Self {
+        self.style.flex_basis = Some(Size::Percent(value));
+        self
+    }
+
+    // === Grid ===
+
+    /// Lay out children as a grid.
+    pub fn grid(mut self) -> Self {
+        self.style.display = Some(Display::Grid);
+        self
+    }
+
+    /// Set grid column tracks.
+    pub fn grid_columns(mut self, tracks: impl IntoIterator<Item = GridTrack>) -> Self {
+        self.style.grid_template_columns = Some(tracks.into_iter().collect());
+        self
+    }
+
+    /// Set grid row tracks.
+    pub fn grid_rows(mut self, tracks: impl IntoIterator<Item = GridTrack>) -> Self {
+        self.style.grid_template_rows = Some(tracks.into_iter().collect());
+        self
+    }
+
+    /// Set grid named areas, one string per row (e.g. `"header header"`).
+    pub fn grid_areas<I, S>(mut self, rows: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.style.grid_template_areas = Some(rows.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the size of rows created beyond the template.
+    pub fn grid_auto_rows(mut self, track: GridTrack) -> Self {
+        self.style.grid_auto_rows = Some(track);
+        self
+    }
+
+    /// Place this box in grid columns.
+    pub fn grid_column(mut self, placement: GridPlacement) -> Self {
+        self.style.grid_column = Some(placement);
+        self
+    }
+
+    /// Place this box in grid rows.
+    pub fn grid_row(mut self, placement: GridPlacement) -> Self {
+        self.style.grid_row = Some(placement);
+        self
+    }
+
+    /// Place this box in a named grid area.
+    pub fn grid_area(mut self, name: impl Into<String>) -> Self {
+        self.style.grid_area = Some(name.into());
+        self
+    }
+
     // === Sizing ===
 
     /// Set width.
@@ -195,6 +291,44 @@ impl BoxComponent {
         self
     }
 
+    /// Set horizontal margin.
+    pub fn margin_x(mut self, value: u16) -> Self {
+        self.style.margin_left = Some(value);
+        self.style.margin_right = Some(value);
+        self
+    }
+
+    /// Set vertical margin.
+    pub fn margin_y(mut self, value: u16) -> Self {
+        self.style.margin_top = Some(value);
+        self.style.margin_bottom = Some(value);
+        self
+    }
+
+    /// Set top margin.
+    pub fn margin_top(mut self, value: u16) -> Self {
+        self.style.margin_top = Some(value);
+        self
+    }
+
+    /// Set right margin.
+    pub fn margin_right(mut self, value: u16) -> Self {
+        self.style.margin_right = Some(value);
+        self
+    }
+
+    /// Set bottom margin.
+    pub fn margin_bottom(mut self, value: u16) -> Self {
+        self.style.margin_bottom = Some(value);
+        self
+    }
+
+    /// Set left margin.
+    pub fn margin_left(mut self, value: u16) -> Self {
+        self.style.margin_left = Some(value);
+        self
+    }
+
     // === Border ===
 
     /// Set border style.
@@ -229,6 +363,55 @@ impl BoxComponent {
         self
     }
 
+    /// Choose which border sides to draw.
+    pub fn border_sides(mut self, top: bool, right: bool, bottom: bool, left: bool) -> Self {
+        self.style.border_top = Some(top);
+        self.style.border_right = Some(right);
+        self.style.border_bottom = Some(bottom);
+        self.style.border_left = Some(left);
+        self
+    }
+
+    /// Set top border color.
+    pub fn border_top_color(mut self, color: Color) -> Self {
+        self.style.border_top_color = Some(color);
+        self
+    }
+
+    /// Set right border color.
+    pub fn border_right_color(mut self, color: Color) -> Self {
+        self.style.border_right_color = Some(color);
+        self
+    }
+
+    /// Set bottom border color.
+    pub fn border_bottom_color(mut self, color: Color) -> Self {
+        self.style.border_bottom_color = Some(color);
+        self
+    }
+
+    /// Set left border color.
+    pub fn border_left_color(mut self, color: Color) -> Self {
+        self.style.border_left_color = Some(color);
+        self
+    }
+
+    /// Let adjacent fully bordered children share one edge, joining into
+    /// junctions (`┌──┬──┐`) instead of doubling up (`┌──┐┌──┐`).
+    ///
+    /// Each child after the first leaves out its leading side, so the
+    /// children should have no gap between them.
+    pub fn collapse_borders(mut self) -> Self {
+        self.collapse_borders = true;
+        self
+    }
+
+    /// Add a title to the border, e.g. `BorderTitle::new("Log").right().bottom()`.
+    pub fn title(mut self, title: impl Into<BorderTitle>) -> Self {
+        self.style = self.style.with_title(title);
+        self
+    }
+
     // === Colors ===
 
     /// Set background color.
@@ -249,6 +432,135 @@ impl BoxComponent {
         self
     }
 
+    /// Fill the background with a gradient.
+    pub fn background_gradient(mut self, gradient: Gradient) -> Self {
+        self.style.background_gradient = Some(gradient);
+        self
+    }
+
+    /// Set the background opacity (0.0 to 1.0).
+    pub fn opacity(mut self, value: f32) -> Self {
+        self.style.opacity = Some(value.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Tint everything drawn before this box.
+    pub fn backdrop(mut self, backdrop: Backdrop) -> Self {
+        self.style.backdrop = Some(backdrop);
+        self
+    }
+
+    /// Dim everything drawn before this box, as behind a modal.
+    pub fn dim_backdrop(self) -> Self {
+        self.backdrop(Backdrop::dim())
+    }
+
+    // === Overflow ===
+
+    /// Set overflow behavior.
+    pub fn overflow(mut self, value: Overflow) -> Self {
+        self.style.overflow = Some(value);
+        self
+    }
+
+    /// Clip children to this box.
+    pub fn overflow_hidden(self) -> Self {
+        self.overflow(Overflow::Hidden)
+    }
+
+    /// Clip children and make them scrollable.
+    pub fn overflow_scroll(self) -> Self {
+        self.overflow(Overflow::Scroll)
+    }
+
+    /// Set the scroll offset explicitly (controlled scrolling).
+    pub fn scroll_offset(mut self, x: u16, y: u16) -> Self {
+        self.style.scroll_x = Some(x);
+        self.style.scroll_y = Some(y);
+        self
+    }
+
+    /// Show a scrollbar when content overflows.
+    pub fn scrollbar(mut self, show: bool) -> Self {
+        self.style.scrollbar = Some(show);
+        self
+    }
+
+    // === Position ===
+
+    /// Set the positioning scheme.
+    pub fn position(mut self, value: Position) -> Self {
+        self.style.position = Some(value);
+        self
+    }
+
+    /// Position relative to the nearest positioned ancestor, out of flow.
+    pub fn absolute(self) -> Self {
+        self.position(Position::Absolute)
+    }
+
+    /// Stay in flow but act as the containing block for absolute children.
+    pub fn relative(self) -> Self {
+        self.position(Position::Relative)
+    }
+
+    /// Set the top offset.
+    pub fn top(mut self, value: u16) -> Self {
+        self.style.top = Some(value);
+        self
+    }
+
+    /// Set the right offset.
+    pub fn right(mut self, value: u16) -> Self {
+        self.style.right = Some(value);
+        self
+    }
+
+    /// Set the bottom offset.
+    pub fn bottom(mut self, value: u16) -> Self {
+        self.style.bottom = Some(value);
+        self
+    }
+
+    /// Set the left offset.
+    pub fn left(mut self, value: u16) -> Self {
+        self.style.left = Some(value);
+        self
+    }
+
+    /// Set the stacking order (higher draws above).
+    pub fn z_index(mut self, value: i32) -> Self {
+        self.style.z_index = Some(value);
+        self
+    }
+
+    // === Responsive ===
+
+    /// Apply `style` only while the viewport matches `breakpoint`.
+    ///
+    /// ```ignore
+    /// box_().row().responsive(Breakpoint::width_below(60), |b| b.column())
+    /// ```
+    pub fn responsive(self, breakpoint: Breakpoint, style: impl FnOnce(Self) -> Self) -> Self {
+        if breakpoint.is_active() {
+            style(self)
+        } else {
+            self
+        }
+    }
+
+    /// Hide this box (it takes no space).
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.style.hidden = Some(hidden);
+        self
+    }
+
+    /// Hide this box while the viewport matches `breakpoint`.
+    pub fn hidden_when(self, breakpoint: Breakpoint) -> Self {
+        let hidden = breakpoint.is_active();
+        self.hidden(hidden)
+    }
+
     // === Children ===
 
     /// Add children to this box.
@@ -268,13 +580,16 @@ impl BoxComponent {
     }
 
     /// Build into a VNode.
-    pub fn build(self) -> VNode {
-        VNode::Box(BoxNode {
+    pub fn build(mut self) -> VNode {
+        if self.collapse_borders {
+            collapse_borders(&mut self.children, self.style.flex_direction.unwrap_or_default());
+        }
+        VNode::Box(Box::new(BoxNode {
             id: self.id,
             style: self.style,
             children: self.children,
             handlers: Default::default(),
-        })
+        }))
     }
 }
 
@@ -284,6 +599,31 @@ impl From<BoxComponent> for VNode {
     }
 }
 
+/// Drop the side of each fully bordered child that faces a fully bordered
+/// child before it; the open ends of its other sides then join that
+/// child's border.
+fn collapse_borders(children: &mut [VNode], direction: FlexDirection) {
+    let mut follows_border = false;
+    for child in children {
+        let style = match child {
+            VNode::Box(node) if node.style.border_sides() == [true; 4] => &mut node.style,
+            _ => {
+                follows_border = false;
+                continue;
+            }
+        };
+        if std::mem::replace(&mut follows_border, true) {
+            let side = match direction {
+                FlexDirection::Row => &mut style.border_left,
+                FlexDirection::RowReverse => &mut style.border_right,
+                FlexDirection::Column => &mut style.border_top,
+                FlexDirection::ColumnReverse => &mut style.border_bottom,
+            };
+            *side = Some(false);
+        }
+    }
+}
+
 /// Create a box with default settings.
 pub fn box_() -> BoxComponent {
     BoxComponent::new()
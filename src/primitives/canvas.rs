@@ -110,7 +110,13 @@ impl Canvas {
     }
 
     /// Render to a string.
+    ///
+    /// Braille and block modes fall back to ASCII when the render mode
+    /// does not allow Unicode glyphs.
     pub fn render(&self) -> String {
+        if !crate::core::capabilities::use_unicode() {
+            return self.render_ascii();
+        }
         match self.mode {
             CanvasMode::Braille => self.render_braille(),
             CanvasMode::Block => self.render_block(),
@@ -191,6 +197,7 @@ impl Canvas {
         VNode::Text(crate::core::component::TextNode {
             content: self.render(),
             style: Default::default(),
+            ..Default::default()
         })
     }
 }
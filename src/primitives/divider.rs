@@ -106,6 +106,7 @@ impl Divider {
                 color: self.color,
                 ..Default::default()
             },
+            ..Default::default()
         })
     }
 }
@@ -0,0 +1,81 @@
+//! Image Component
+//!
+//! Inline images drawn with Kitty, iTerm2 or Sixel graphics when the
+//! terminal has them, and with block characters otherwise.
+
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::core::component::{ImageNode, VNode};
+use crate::core::graphics::{self, GraphicsProtocol, RgbaImage};
+
+/// Image component.
+#[derive(Debug, Clone)]
+pub struct Image {
+    image: Rc<RgbaImage>,
+    width: Option<u16>,
+    height: Option<u16>,
+    protocol: Option<GraphicsProtocol>,
+}
+
+impl Image {
+    /// Create an image from decoded pixels.
+    pub fn new(image: impl Into<Rc<RgbaImage>>) -> Self {
+        Self {
+            image: image.into(),
+            width: None,
+            height: None,
+            protocol: None,
+        }
+    }
+
+    /// Decode a PPM/PGM, BMP or QOI file held in memory.
+    pub fn from_bytes(data: &[u8]) -> io::Result<Self> {
+        Ok(Self::new(graphics::decode(data)?))
+    }
+
+    /// Load a PPM/PGM, BMP or QOI file.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(RgbaImage::open(path)?))
+    }
+
+    /// Set the width in cells.
+    pub fn width(mut self, cells: u16) -> Self {
+        self.width = Some(cells);
+        self
+    }
+
+    /// Set the height in cells.
+    pub fn height(mut self, cells: u16) -> Self {
+        self.height = Some(cells);
+        self
+    }
+
+    /// Force a protocol instead of detecting one.
+    pub fn protocol(mut self, protocol: GraphicsProtocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    /// Build into a VNode.
+    pub fn build(self) -> VNode {
+        VNode::Image(ImageNode {
+            image: self.image,
+            width: self.width,
+            height: self.height,
+            protocol: self.protocol,
+        })
+    }
+}
+
+impl From<Image> for VNode {
+    fn from(i: Image) -> VNode {
+        i.build()
+    }
+}
+
+/// Create an image component.
+pub fn image(image: impl Into<Rc<RgbaImage>>) -> Image {
+    Image::new(image)
+}
@@ -8,6 +8,7 @@
 //! - `Fragment`: Group without wrapper
 //! - `Divider`: Horizontal/vertical line
 //! - `Canvas`: Low-level drawing
+//! - `Image`: Inline images
 
 mod box_component;
 mod text;
@@ -15,6 +16,7 @@ mod spacer;
 mod fragment;
 mod divider;
 mod canvas;
+mod image;
 mod control_flow;
 
 pub use box_component::{BoxComponent, box_, column, row};
@@ -23,4 +25,5 @@ pub use spacer::{Spacer, Newline, spacer, newline};
 pub use fragment::{Fragment, fragment};
 pub use divider::{Divider, divider, vdivider};
 pub use canvas::{Canvas, canvas};
+pub use image::{Image, image};
 pub use control_flow::{When, Each, Transform, Static, Slot, when, each};
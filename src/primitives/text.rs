@@ -2,13 +2,15 @@
 //!
 //! Renders text content with styling.
 
-use crate::core::component::{VNode, TextNode, TextStyle, Color, NamedColor, WrapMode};
+use crate::core::component::{VNode, TextNode, TextSpan, TextStyle, Color, Gradient, NamedColor, UnderlineStyle, WrapMode};
+use crate::core::capabilities::hyperlinks_supported;
 
 /// Text component builder.
 #[derive(Debug, Clone, Default)]
 pub struct Text {
     content: String,
     style: TextStyle,
+    spans: Vec<TextSpan>,
 }
 
 impl Text {
@@ -17,9 +19,53 @@ impl Text {
         Self {
             content: content.into(),
             style: TextStyle::default(),
+            spans: Vec::new(),
         }
     }
 
+    /// Create rich text from styled spans, which wrap together as one text.
+    pub fn rich(spans: impl IntoIterator<Item = TextSpan>) -> Self {
+        let mut text = Self::empty();
+        text.spans = spans.into_iter().collect();
+        text
+    }
+
+    /// Create rich text from inline markup like `[bold red]error[/] in [cyan]main.rs[/]`.
+    ///
+    /// Use [`escape_markup`](crate::utils::markup::escape_markup) on
+    /// interpolated values that may contain `[`.
+    pub fn markup(input: &str) -> Self {
+        Self::rich(crate::utils::markup::parse_markup(input))
+    }
+
+    /// Create rich text from output with ANSI colors, like that of git or
+    /// cargo; escape sequences become styles instead of literal characters.
+    pub fn ansi(input: &str) -> Self {
+        Self::rich(crate::utils::ansi::parse_ansi(input))
+    }
+
+    /// Create text from untrusted input, such as file contents or network
+    /// data, with control characters and escape sequences shown as
+    /// placeholders.
+    pub fn untrusted(input: &str) -> Self {
+        Self::new(crate::utils::sanitize::sanitize(input))
+    }
+
+    /// Create text from untrusted input, trusting only its ANSI colors and
+    /// attributes; other escape sequences show as placeholders.
+    pub fn untrusted_ansi(input: &str) -> Self {
+        Self::rich(crate::utils::sanitize::sanitize_ansi(input))
+    }
+
+    /// Append a styled span.
+    pub fn span(mut self, text: impl Into<String>, style: TextStyle) -> Self {
+        if self.spans.is_empty() && !self.content.is_empty() {
+            self.spans.push(TextSpan::new(std::mem::take(&mut self.content)));
+        }
+        self.spans.push(TextSpan::styled(text, style));
+        self
+    }
+
     /// Create an empty text.
     pub fn empty() -> Self {
         Self::new("")
@@ -28,6 +74,7 @@ impl Text {
     /// Set the text content.
     pub fn content(mut self, content: impl Into<String>) -> Self {
         self.content = content.into();
+        self.spans.clear();
         self
     }
 
@@ -51,6 +98,12 @@ impl Text {
         self
     }
 
+    /// Color the text along a gradient.
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.style.gradient = Some(gradient);
+        self
+    }
+
     /// Set background color.
     pub fn background(mut self, color: Color) -> Self {
         self.style.background = Some(color);
@@ -130,6 +183,24 @@ impl Text {
         self
     }
 
+    /// Underline with a style, e.g. curly for spell-check squiggles.
+    pub fn underline_style(mut self, style: UnderlineStyle) -> Self {
+        self.style.underline = true;
+        self.style.underline_style = Some(style);
+        self
+    }
+
+    /// Curly underline.
+    pub fn curly_underline(self) -> Self {
+        self.underline_style(UnderlineStyle::Curly)
+    }
+
+    /// Set the underline color, independent of the text color.
+    pub fn underline_color(mut self, color: Color) -> Self {
+        self.style.underline_color = Some(color);
+        self
+    }
+
     /// Make text strikethrough.
     pub fn strikethrough(mut self) -> Self {
         self.style.strikethrough = true;
@@ -148,6 +219,16 @@ impl Text {
         self
     }
 
+    // === Hyperlinks ===
+
+    /// Link the text to a URL.
+    ///
+    /// Terminals without OSC 8 hyperlinks show the URL after the text.
+    pub fn link(mut self, url: impl Into<String>) -> Self {
+        self.style.hyperlink = Some(url.into());
+        self
+    }
+
     // === Wrap Mode ===
 
     /// Set wrap mode.
@@ -172,10 +253,28 @@ impl Text {
     }
 
     /// Build into a VNode.
-    pub fn build(self) -> VNode {
+    pub fn build(mut self) -> VNode {
+        if !hyperlinks_supported() {
+            if let Some(url) = self.style.hyperlink.take() {
+                if self.content != url {
+                    self.content = format!("{} ({})", self.content, url);
+                }
+            }
+            for span in &mut self.spans {
+                if let Some(url) = span.style.hyperlink.take() {
+                    if span.text != url {
+                        span.text = format!("{} ({})", span.text, url);
+                    }
+                }
+            }
+        }
+        if !self.spans.is_empty() {
+            self.content = self.spans.iter().map(|span| span.text.as_str()).collect();
+        }
         VNode::Text(TextNode {
             content: self.content,
             style: self.style,
+            spans: self.spans,
         })
     }
 }
@@ -220,6 +319,44 @@ mod tests {
         assert!(matches!(t.style.color, Some(Color::Named(NamedColor::Red))));
     }
 
+    #[test]
+    fn test_text_link_falls_back_to_url() {
+        use crate::core::capabilities::set_hyperlinks_supported;
+
+        let content = |node: VNode| match node {
+            VNode::Text(text) => (text.content, text.style.hyperlink),
+            _ => unreachable!(),
+        };
+
+        set_hyperlinks_supported(true);
+        let (text, url) = content(Text::new("docs").link("https://example.com").build());
+        assert_eq!(text, "docs");
+        assert_eq!(url.as_deref(), Some("https://example.com"));
+
+        set_hyperlinks_supported(false);
+        let (text, url) = content(Text::new("docs").link("https://example.com").build());
+        assert_eq!(text, "docs (https://example.com)");
+        assert_eq!(url, None);
+        let (text, _) = content(Text::new("https://example.com").link("https://example.com").build());
+        assert_eq!(text, "https://example.com");
+    }
+
+    #[test]
+    fn test_text_underline_style() {
+        let t = Text::new("teh").curly_underline().underline_color(Color::Named(NamedColor::Red));
+        assert!(t.style.underline);
+        assert_eq!(t.style.underline_style, Some(UnderlineStyle::Curly));
+        assert_eq!(t.style.underline_color, Some(Color::Named(NamedColor::Red)));
+    }
+
+    #[test]
+    fn test_text_from_ansi() {
+        let VNode::Text(node) = Text::ansi("\x1B[32m+ added\x1B[0m line").build() else { unreachable!() };
+        assert_eq!(node.content, "+ added line");
+        assert_eq!(node.spans.len(), 2);
+        assert_eq!(node.spans[0].style.color, Some(Color::Named(NamedColor::Green)));
+    }
+
     #[test]
     fn test_text_from_str() {
         let t: Text = "Hello".into();
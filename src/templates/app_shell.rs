@@ -0,0 +1,192 @@
+//! App Shell
+//!
+//! Full-screen application frame: header, sidebar, main content and footer.
+
+use crate::core::component::VNode;
+use crate::core::responsive::Breakpoint;
+use crate::primitives::{box_, BoxComponent};
+
+/// Application frame with an optional header, sidebar and footer.
+///
+/// The shell fills the screen and adapts to its size: the sidebar can be
+/// hidden on narrow terminals, and the sidebar and content can be stacked
+/// vertically when there's no room to put them side by side.
+///
+/// ```ignore
+/// AppShell::new()
+///     .header(title)
+///     .sidebar(nav)
+///     .content(body)
+///     .footer(status)
+///     .hide_sidebar_below(100)
+///     .stack_below(60)
+///     .build()
+/// ```
+#[derive(Debug, Clone)]
+pub struct AppShell {
+    header: Option<VNode>,
+    sidebar: Option<VNode>,
+    content: Vec<VNode>,
+    footer: Option<VNode>,
+    sidebar_width: u16,
+    hide_sidebar: Option<Breakpoint>,
+    stack: Option<Breakpoint>,
+}
+
+impl Default for AppShell {
+    fn default() -> Self {
+        Self {
+            header: None,
+            sidebar: None,
+            content: Vec::new(),
+            footer: None,
+            sidebar_width: 24,
+            hide_sidebar: None,
+            stack: None,
+        }
+    }
+}
+
+impl AppShell {
+    /// Create an empty shell.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the header, shown across the top.
+    pub fn header(mut self, node: impl Into<VNode>) -> Self {
+        self.header = Some(node.into());
+        self
+    }
+
+    /// Set the sidebar, shown left of the content.
+    pub fn sidebar(mut self, node: impl Into<VNode>) -> Self {
+        self.sidebar = Some(node.into());
+        self
+    }
+
+    /// Add main content.
+    pub fn content(mut self, node: impl Into<VNode>) -> Self {
+        self.content.push(node.into());
+        self
+    }
+
+    /// Set the footer, shown across the bottom.
+    pub fn footer(mut self, node: impl Into<VNode>) -> Self {
+        self.footer = Some(node.into());
+        self
+    }
+
+    /// Set the sidebar width in columns (default 24).
+    pub fn sidebar_width(mut self, width: u16) -> Self {
+        self.sidebar_width = width;
+        self
+    }
+
+    /// Hide the sidebar while the terminal is narrower than `columns`.
+    pub fn hide_sidebar_below(self, columns: u16) -> Self {
+        self.hide_sidebar_when(Breakpoint::width_below(columns))
+    }
+
+    /// Hide the sidebar while the viewport matches `breakpoint`.
+    pub fn hide_sidebar_when(mut self, breakpoint: Breakpoint) -> Self {
+        self.hide_sidebar = Some(breakpoint);
+        self
+    }
+
+    /// Stack the sidebar above the content while the terminal is narrower
+    /// than `columns`.
+    pub fn stack_below(self, columns: u16) -> Self {
+        self.stack_when(Breakpoint::width_below(columns))
+    }
+
+    /// Stack the sidebar above the content while the viewport matches `breakpoint`.
+    pub fn stack_when(mut self, breakpoint: Breakpoint) -> Self {
+        self.stack = Some(breakpoint);
+        self
+    }
+
+    /// Build the VNode.
+    pub fn build(self) -> VNode {
+        let stacked = self.stack.is_some_and(|b| b.is_active());
+
+        let mut body = box_().row().flex_grow(1.0);
+        if stacked {
+            body = body.column();
+        }
+
+        if let Some(sidebar) = self.sidebar {
+            let mut panel: BoxComponent = box_().column().flex_shrink(0.0).child(sidebar);
+            if !stacked {
+                panel = panel.width(self.sidebar_width);
+            }
+            if let Some(breakpoint) = self.hide_sidebar {
+                panel = panel.hidden_when(breakpoint);
+            }
+            body = body.child(panel.build());
+        }
+        body = body.child(box_().column().flex_grow(1.0).children(self.content).build());
+
+        let mut shell = box_().column().width_fill().height_fill();
+        if let Some(header) = self.header {
+            shell = shell.child(header);
+        }
+        shell = shell.child(body.build());
+        if let Some(footer) = self.footer {
+            shell = shell.child(footer);
+        }
+        shell.build()
+    }
+}
+
+impl From<AppShell> for VNode {
+    fn from(shell: AppShell) -> VNode {
+        shell.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::renderer::render_to_string;
+    use crate::core::responsive::set_viewport;
+    use crate::utils::ansi::strip_ansi;
+
+    fn render_shell(shell: AppShell, width: u16, height: u16) -> Vec<String> {
+        set_viewport(width, height);
+        let shell = shell
+            .header(VNode::text("HEAD"))
+            .sidebar(VNode::text("NAV"))
+            .content(VNode::text("MAIN"))
+            .footer(VNode::text("FOOT"))
+            .sidebar_width(5)
+            .build();
+        let output = strip_ansi(&render_to_string(&shell, width, height));
+        output.lines().map(|l| l.trim_end().to_string()).collect()
+    }
+
+    #[test]
+    fn test_app_shell_adapts_to_width() {
+        let responsive = || AppShell::new().hide_sidebar_below(30).stack_below(20);
+
+        let wide = render_shell(responsive(), 40, 5);
+        assert_eq!(wide[0], "HEAD");
+        assert_eq!(wide[1], "NAV  MAIN");
+        assert_eq!(wide[4], "FOOT");
+
+        // Sidebar hidden below 30 columns
+        let medium = render_shell(responsive(), 25, 5);
+        assert_eq!(medium[1], "MAIN");
+
+        // Hiding wins over stacking
+        let narrow = render_shell(responsive(), 15, 5);
+        assert_eq!(narrow[1], "MAIN");
+        assert_eq!(narrow[4], "FOOT");
+
+        let stacked = render_shell(AppShell::new().stack_below(20), 15, 5);
+        assert_eq!(stacked[1], "NAV");
+        assert_eq!(stacked[2], "MAIN");
+
+        set_viewport(80, 24);
+    }
+}
@@ -1,7 +1,13 @@
-//! Templates - Stub module (to be implemented)
+//! Templates - Page layouts
+//!
+//! Full-screen layouts composed from organisms:
+//! - AppShell: Header, sidebar, content and footer that adapt to the terminal size
+
+mod app_shell;
+
+pub use app_shell::AppShell;
 
 // Placeholder types for compilation
-pub struct AppShell;
 pub struct Page;
 pub struct Header;
 pub struct StatusBar;
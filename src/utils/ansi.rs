@@ -1,5 +1,8 @@
 //! ANSI Escape Code Utilities
 
+use crate::core::component::{NamedColor, TextSpan, TextStyle, UnderlineStyle};
+use super::sanitize::push_visible;
+
 /// Strip ANSI escape codes from a string.
 pub fn strip_ansi(s: &str) -> String {
     let mut result = String::new();
@@ -90,11 +93,12 @@ impl Style {
         if self.underline { codes.push("4".to_string()); }
         if self.inverse { codes.push("7".to_string()); }
 
-        if let Some(fg) = &self.fg {
-            codes.push(color_to_fg_code(fg));
+        let support = crate::core::capabilities::color_support();
+        if let Some(fg) = self.fg.map(|c| color_to_fg_code(&c.downsample(support))) {
+            if !fg.is_empty() { codes.push(fg); }
         }
-        if let Some(bg) = &self.bg {
-            codes.push(color_to_bg_code(bg));
+        if let Some(bg) = self.bg.map(|c| color_to_bg_code(&c.downsample(support))) {
+            if !bg.is_empty() { codes.push(bg); }
         }
 
         if codes.is_empty() {
@@ -155,6 +159,210 @@ pub fn style() -> Style {
     Style::new()
 }
 
+/// Parse text with ANSI escape codes, such as output captured from git or
+/// a compiler, into styled spans.
+///
+/// SGR colors and attributes and OSC 8 hyperlinks are kept; other escape
+/// sequences, strings (OSC, DCS, APC, ...) and control characters are
+/// dropped. Newlines stay in the text, tabs expand to the next multiple of
+/// 8 columns and a carriage return starts its line over.
+pub fn parse_ansi(input: &str) -> Vec<TextSpan> {
+    parse_escapes(input, false)
+}
+
+/// Parse escape codes into spans, either dropping the sequences other than
+/// SGR and OSC 8 or, for untrusted text, showing all but SGR as
+/// placeholders.
+pub(crate) fn parse_escapes(input: &str, show_unknown: bool) -> Vec<TextSpan> {
+    let mut spans = Vec::new();
+    let mut style = TextStyle::default();
+    let mut text = String::new();
+    let mut column = 0;
+    let mut chars = input.char_indices().peekable();
+
+    // Text so far becomes a span before the style changes
+    let flush = |text: &mut String, style: &TextStyle, spans: &mut Vec<TextSpan>| {
+        if !text.is_empty() {
+            spans.push(TextSpan::styled(std::mem::take(text), style.clone()));
+        }
+    };
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\x1B' => {
+                // Only bytes that can continue an escape sequence are consumed
+                let next = chars.next_if(|&(_, c)| (' '..='~').contains(&c)).map(|(_, c)| c);
+                let honored = match next {
+                    Some('[') => {
+                        // Parameters and intermediates, then a final byte;
+                        // anything else cuts the sequence short
+                        let mut params = String::new();
+                        while let Some((_, c)) = chars.next_if(|&(_, c)| ('\x20'..='\x3F').contains(&c)) {
+                            params.push(c);
+                        }
+                        let last = chars.next_if(|&(_, c)| ('\x40'..='\x7E').contains(&c));
+                        let sgr = last.map(|(_, c)| c) == Some('m');
+                        if sgr {
+                            flush(&mut text, &style, &mut spans);
+                            apply_sgr(&mut style, &params);
+                        }
+                        sgr
+                    }
+                    // OSC, DCS, APC, PM and SOS strings run up to ST (or
+                    // BEL for OSC) and never show as text
+                    Some(kind @ (']' | 'P' | '_' | '^' | 'X')) => {
+                        let mut body = String::new();
+                        while let Some((_, c)) = chars.next() {
+                            if c == '\x07' && kind == ']' {
+                                break;
+                            }
+                            if c == '\x1B' {
+                                chars.next_if(|&(_, c)| c == '\\');
+                                break;
+                            }
+                            body.push(c);
+                        }
+                        // OSC 8 ; params ; URL
+                        match body.strip_prefix("8;") {
+                            Some(link) if kind == ']' && !show_unknown => {
+                                let url = link.split_once(';').map_or("", |(_, url)| url);
+                                flush(&mut text, &style, &mut spans);
+                                style.hyperlink = (!url.is_empty()).then(|| url.to_string());
+                                true
+                            }
+                            _ => false,
+                        }
+                    }
+                    // nF escapes like `ESC ( B`: intermediates, then a final byte
+                    Some(' '..='/') => {
+                        while chars.next_if(|&(_, c)| (' '..='/').contains(&c)).is_some() {}
+                        chars.next_if(|&(_, c)| ('0'..='~').contains(&c));
+                        false
+                    }
+                    _ => false,
+                };
+                if show_unknown && !honored {
+                    let end = chars.peek().map_or(input.len(), |&(i, _)| i);
+                    for c in input[start..end].chars() {
+                        push_visible(&mut text, &mut column, c);
+                    }
+                }
+            }
+            '\r' if chars.peek().is_some_and(|&(_, c)| c == '\n') => {}
+            // Progress output redraws its line after a carriage return
+            '\r' if !show_unknown => {
+                discard_line(&mut text, &mut spans);
+                column = 0;
+            }
+            '\n' | '\t' => push_visible(&mut text, &mut column, c),
+            c if show_unknown || !c.is_control() => push_visible(&mut text, &mut column, c),
+            _ => {}
+        }
+    }
+    flush(&mut text, &style, &mut spans);
+    spans
+}
+
+/// Drop the text written since the last newline.
+fn discard_line(text: &mut String, spans: &mut Vec<TextSpan>) {
+    if let Some(i) = text.rfind('\n') {
+        text.truncate(i + 1);
+        return;
+    }
+    text.clear();
+    while let Some(span) = spans.last_mut() {
+        if let Some(i) = span.text.rfind('\n') {
+            span.text.truncate(i + 1);
+            return;
+        }
+        spans.pop();
+    }
+}
+
+/// Apply SGR parameters, e.g. `1;38;5;196`, to a text style.
+///
+/// Reset keeps an open hyperlink, which only OSC 8 closes.
+pub fn apply_sgr(style: &mut TextStyle, params: &str) {
+    let params: Vec<&str> = if params.is_empty() { vec!["0"] } else { params.split(';').collect() };
+    let mut iter = params.into_iter().map(str::trim);
+
+    while let Some(param) = iter.next() {
+        // Colon sub-parameters: 4:3 (curly underline), 38:2::r:g:b
+        let mut parts = param.split(':');
+        let code: u16 = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+        let subs: Vec<Option<u16>> = parts.map(|p| p.parse().ok()).collect();
+
+        match code {
+            0 => *style = TextStyle { hyperlink: style.hyperlink.take(), ..TextStyle::default() },
+            1 => style.bold = true,
+            2 => style.dim = true,
+            3 => style.italic = true,
+            4 => {
+                let kind = subs.first().copied().flatten();
+                style.underline = kind != Some(0);
+                style.underline_style = match kind {
+                    Some(2) => Some(UnderlineStyle::Double),
+                    Some(3) => Some(UnderlineStyle::Curly),
+                    Some(4) => Some(UnderlineStyle::Dotted),
+                    Some(5) => Some(UnderlineStyle::Dashed),
+                    _ => None,
+                };
+            }
+            7 => style.inverse = true,
+            9 => style.strikethrough = true,
+            21 => {
+                style.underline = true;
+                style.underline_style = Some(UnderlineStyle::Double);
+            }
+            22 => {
+                style.bold = false;
+                style.dim = false;
+            }
+            23 => style.italic = false,
+            24 => style.underline = false,
+            27 => style.inverse = false,
+            29 => style.strikethrough = false,
+            30..=37 => style.color = Some(Color::Named(NamedColor::ALL[usize::from(code - 30)])),
+            38 => style.color = extended_color(&subs, &mut iter).or(style.color),
+            39 => style.color = None,
+            40..=47 => style.background = Some(Color::Named(NamedColor::ALL[usize::from(code - 40)])),
+            48 => style.background = extended_color(&subs, &mut iter).or(style.background),
+            49 => style.background = None,
+            58 => style.underline_color = extended_color(&subs, &mut iter).or(style.underline_color),
+            59 => style.underline_color = None,
+            90..=97 => style.color = Some(Color::Named(NamedColor::ALL[usize::from(code - 90 + 8)])),
+            100..=107 => style.background = Some(Color::Named(NamedColor::ALL[usize::from(code - 100 + 8)])),
+            _ => {}
+        }
+    }
+}
+
+/// Color of a 38/48/58 parameter, either from colon sub-parameters or
+/// from the parameters that follow it.
+fn extended_color<'a>(subs: &[Option<u16>], rest: &mut impl Iterator<Item = &'a str>) -> Option<Color> {
+    let values: Vec<Option<u16>> = if subs.is_empty() {
+        let kind = rest.next().and_then(|p| p.parse().ok());
+        let count = match kind {
+            Some(5) => 1,
+            Some(2) => 3,
+            _ => 0,
+        };
+        std::iter::once(kind).chain(rest.take(count).map(|p| p.parse().ok())).collect()
+    } else if subs.first() == Some(&Some(2)) && subs.len() == 5 {
+        // `38:2::r:g:b` carries an empty color space id
+        [subs[0], subs[2], subs[3], subs[4]].to_vec()
+    } else {
+        subs.to_vec()
+    };
+
+    let byte = |i: usize| values.get(i).copied().flatten().map(|v| v.min(255) as u8);
+    match values.first().copied().flatten()? {
+        5 => Some(Color::Ansi256(byte(1)?)),
+        2 => Some(Color::Rgb(byte(1)?, byte(2)?, byte(3)?)),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,4 +378,34 @@ mod tests {
         let styled = Style::new().bold().apply("Hello");
         assert!(styled.contains("\x1B[1m"));
     }
+
+    #[test]
+    fn test_parse_ansi() {
+        let spans = parse_ansi("\x1B[1;31merror\x1B[0m: \x1B[38;5;208mwarn\x1B[39m\tok\x1B[2K\r\n");
+        let text: Vec<&str> = spans.iter().map(|span| span.text.as_str()).collect();
+        assert_eq!(text, ["error", ": ", "warn", "     ok\n"]);
+        assert!(spans[0].style.bold);
+        assert_eq!(spans[0].style.color, Some(Color::Named(NamedColor::Red)));
+        assert!(!spans[1].style.bold && spans[1].style.color.is_none());
+        assert_eq!(spans[2].style.color, Some(Color::Ansi256(208)));
+        assert_eq!(spans[3].style.color, None);
+
+        let text = |input: &str| parse_ansi(input).into_iter().map(|span| span.text).collect::<Vec<_>>();
+        // `tput sgr0` selects the ASCII character set before resetting
+        assert_eq!(text("\x1B[1mok\x1B(B\x1B[m done"), ["ok", " done"]);
+        // DCS and APC strings never show
+        assert_eq!(text("a\x1BP1$r0m\x1B\\b\x1B_Gi=1\x1B\\c"), ["abc"]);
+        assert_eq!(text("a\x1B=b\x1B\n"), ["ab\n"]);
+        // A carriage return rewrites its line, across spans too
+        assert_eq!(text("50%\rdone"), ["done"]);
+        assert_eq!(text("ok\n\x1B[1m50%\x1B[0m 1/2\rdone\r\n"), ["ok\n", "done\n"]);
+
+        let mut style = TextStyle::default();
+        apply_sgr(&mut style, "38:2::10:20:30;48;2;1;2;3;3;4:3");
+        assert_eq!((style.color, style.background), (Some(Color::Rgb(10, 20, 30)), Some(Color::Rgb(1, 2, 3))));
+        assert!(style.italic && style.underline);
+        assert_eq!(style.underline_style, Some(UnderlineStyle::Curly));
+        apply_sgr(&mut style, "");
+        assert!(style.color.is_none() && !style.italic && !style.underline);
+    }
 }
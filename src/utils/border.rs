@@ -20,3 +20,8 @@ pub const BORDER_STYLES: [BorderStyle; 9] = [
 pub fn get_border_chars(style: BorderStyle) -> BorderChars {
     crate::core::renderer::get_border_chars(style)
 }
+
+/// Get ASCII border characters for a style.
+pub fn get_ascii_border_chars(style: BorderStyle) -> BorderChars {
+    crate::core::renderer::get_ascii_border_chars(style)
+}
@@ -0,0 +1,73 @@
+//! Glyphs with ASCII Fallbacks
+//!
+//! Components draw with Unicode symbols unless the render mode is ASCII
+//! (see [`set_render_mode`](crate::core::capabilities::set_render_mode)).
+//! Anything left over is mapped by [`ascii_fallback`] when it is drawn.
+
+use crate::core::capabilities::use_unicode;
+
+/// `unicode` when drawing with Unicode, otherwise `ascii`.
+pub fn glyph<T>(unicode: T, ascii: T) -> T {
+    if use_unicode() {
+        unicode
+    } else {
+        ascii
+    }
+}
+
+/// Closest ASCII stand-in for a character.
+pub fn ascii_fallback(c: char) -> char {
+    if c.is_ascii() {
+        return c;
+    }
+
+    match c {
+        // Box drawing
+        '─' | '━' | '╌' | '╍' | '┄' | '┅' | '┈' | '┉' | '╴' | '╶' | '╸' | '╺' => '-',
+        '═' => '=',
+        '│' | '┃' | '╎' | '╏' | '┆' | '┇' | '┊' | '┋' | '║' | '╵' | '╷' | '╹' | '╻' => '|',
+        '\u{2500}'..='\u{257F}' => '+',
+        // Blocks and shades
+        '░' => '.',
+        '▒' => ':',
+        '▁' | '▂' => '_',
+        '\u{2580}'..='\u{259F}' => '#',
+        // Braille dots
+        '\u{2800}' => ' ',
+        '\u{2801}'..='\u{28FF}' => '*',
+        // Arrows and triangles
+        '←' | '◀' | '◂' | '◄' | '‹' | '«' => '<',
+        '→' | '▶' | '▸' | '►' | '›' | '»' => '>',
+        '↑' | '▲' | '▴' => '^',
+        '↓' | '▼' | '▾' => 'v',
+        // Bullets, marks and shapes
+        '•' | '●' | '◉' | '◆' | '■' | '◼' | '★' | '◐' | '◑' | '◒' | '◓' | '◕' => '*',
+        '○' | '◯' | '◻' | '□' | '◇' | '☐' | '◔' | '☆' => 'o',
+        '✓' | '✔' | '☑' | '✗' | '✘' | '☒' | '×' => 'x',
+        // Punctuation
+        '…' | '·' | '⋯' => '.',
+        '–' | '—' | '−' => '-',
+        '‘' | '’' | '′' => '\'',
+        '“' | '”' | '″' => '"',
+        '\u{A0}' => ' ',
+        _ => '?',
+    }
+}
+
+/// Replace every non-ASCII character with its [`ascii_fallback`].
+pub fn to_ascii(s: &str) -> String {
+    s.chars().map(ascii_fallback).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_fallback() {
+        assert_eq!(to_ascii("╭─┬─╮"), "+-+-+");
+        assert_eq!(to_ascii("│ ║ ═"), "| | =");
+        assert_eq!(to_ascii("██░░ ▲▼ ● ○ ✓ …"), "##.. ^v * o x .");
+        assert_eq!(to_ascii("⠋ 📁 héllo"), "* ? h?llo");
+    }
+}
@@ -0,0 +1,162 @@
+//! Inline Markup
+//!
+//! Style tags for rich text, like `[bold red]error[/] in [cyan]main.rs[/]`.
+//!
+//! - `[style]` opens a style: attributes (`bold`, `dim`, `italic`,
+//!   `underline`, `curly`, `strike`, `reverse`), a color, `on <color>` for
+//!   the background and `link=<url>`
+//! - `[/]` closes the latest open tag, `[/bold red]` the latest matching one
+//! - `\[` is a literal bracket and `\\` a literal backslash; anything
+//!   that isn't a valid tag stays text
+
+use crate::core::component::{Color, TextSpan, TextStyle, UnderlineStyle};
+
+/// Parse markup into styled spans.
+pub fn parse_markup(input: &str) -> Vec<TextSpan> {
+    let mut spans: Vec<TextSpan> = Vec::new();
+    let mut open: Vec<(String, TextStyle)> = Vec::new();
+    let mut text = String::new();
+    let mut rest = input;
+
+    // Text so far becomes a span in the style of the open tags
+    let flush = |text: &mut String, open: &[(String, TextStyle)], spans: &mut Vec<TextSpan>| {
+        if !text.is_empty() {
+            let style = open.iter().fold(TextStyle::default(), |style, (_, tag)| style.merge(tag));
+            spans.push(TextSpan::styled(std::mem::take(text), style));
+        }
+    };
+
+    while let Some(i) = rest.find(['[', '\\']) {
+        text.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        if let Some(after) = rest.strip_prefix("\\[").or_else(|| rest.strip_prefix("\\\\")) {
+            text.push_str(&rest[1..2]);
+            rest = after;
+            continue;
+        }
+        if rest.starts_with('\\') {
+            text.push('\\');
+            rest = &rest[1..];
+            continue;
+        }
+
+        let Some(end) = rest.find(']') else { break };
+        let tag = &rest[1..end];
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = normalize(name);
+            let position = if name.is_empty() {
+                open.len().checked_sub(1)
+            } else {
+                open.iter().rposition(|(open_name, _)| *open_name == name)
+            };
+            if let Some(position) = position {
+                flush(&mut text, &open, &mut spans);
+                open.remove(position);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        } else if let Some(style) = parse_style(tag) {
+            flush(&mut text, &open, &mut spans);
+            open.push((normalize(tag), style));
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        // Not a tag: keep the bracket as text
+        text.push('[');
+        rest = &rest[1..];
+    }
+    text.push_str(rest);
+    flush(&mut text, &open, &mut spans);
+    spans
+}
+
+/// Parse a style tag's contents, like `bold red on white`.
+pub fn parse_style(tag: &str) -> Option<TextStyle> {
+    let mut style = TextStyle::default();
+    let mut words = tag.split_whitespace().peekable();
+    words.peek()?;
+
+    while let Some(word) = words.next() {
+        match word.to_ascii_lowercase().as_str() {
+            "bold" | "b" => style.bold = true,
+            "dim" | "d" => style.dim = true,
+            "italic" | "i" => style.italic = true,
+            "underline" | "u" => style.underline = true,
+            "curly" | "undercurl" => {
+                style.underline = true;
+                style.underline_style = Some(UnderlineStyle::Curly);
+            }
+            "strike" | "strikethrough" | "s" => style.strikethrough = true,
+            "reverse" | "inverse" => style.inverse = true,
+            "on" => style.background = Some(Color::parse(words.next()?)?),
+            _ => {
+                if let Some(url) = word.strip_prefix("link=") {
+                    style.hyperlink = Some(url.to_string());
+                } else {
+                    style.color = Some(Color::parse(word)?);
+                }
+            }
+        }
+    }
+    Some(style)
+}
+
+/// Escape text so markup shows it literally, e.g. before interpolating a
+/// file name.
+pub fn escape_markup(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('[', "\\[")
+}
+
+/// Tag text compared when closing, ignoring case and spacing.
+fn normalize(tag: &str) -> String {
+    tag.split_whitespace().collect::<Vec<_>>().join(" ").to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::component::NamedColor;
+
+    fn plain(spans: &[TextSpan]) -> Vec<&str> {
+        spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_markup() {
+        let file = "main.rs";
+        let spans = parse_markup(&format!("[bold red]error[/] in [cyan]{file}[/]"));
+        assert_eq!(plain(&spans), ["error", " in ", "main.rs"]);
+        assert!(spans[0].style.bold);
+        assert_eq!(spans[0].style.color, Some(Color::Named(NamedColor::Red)));
+        assert!(!spans[1].style.bold && spans[1].style.color.is_none());
+        assert_eq!(spans[2].style.color, Some(Color::Named(NamedColor::Cyan)));
+
+        // Nesting layers styles; named closes reach past inner tags
+        let spans = parse_markup("[bold]a[#ff8800 on blue]b[/bold]c[/]");
+        assert_eq!(plain(&spans), ["a", "b", "c"]);
+        assert!(spans[1].style.bold && !spans[2].style.bold);
+        assert_eq!(spans[2].style.color, Some(Color::Rgb(255, 136, 0)));
+        assert_eq!(spans[2].style.background, Some(Color::Named(NamedColor::Blue)));
+
+        // Escapes, non-tags and stray closes stay as text
+        let spans = parse_markup("\\[x] [1] [not a style] [/] a[b");
+        assert_eq!(plain(&spans), ["[x] [1] [not a style] [/] a[b"]);
+        assert_eq!(plain(&parse_markup(&escape_markup("[red]"))), ["[red]"]);
+
+        // Backslashes round-trip, even right before a tag
+        for value in [r"C:\build\", r"a\[b]\\c", "\\"] {
+            let spans = parse_markup(&format!("[cyan]{}[/] done", escape_markup(value)));
+            assert_eq!(plain(&spans), [value, " done"]);
+            assert!(spans[1].style.color.is_none());
+        }
+        // Lone backslashes that escape nothing stay as they are
+        assert_eq!(plain(&parse_markup(r"a\b")), [r"a\b"]);
+
+        let link = parse_style("u link=https://example.com").unwrap();
+        assert!(link.underline);
+        assert_eq!(link.hyperlink.as_deref(), Some("https://example.com"));
+        assert!(parse_style("").is_none() && parse_style("on").is_none());
+    }
+}
@@ -6,8 +6,14 @@ pub mod ansi;
 pub mod text;
 pub mod cursor;
 pub mod border;
+pub mod glyphs;
+pub mod markup;
+pub mod sanitize;
 
-pub use ansi::{strip_ansi, colorize, style, Color, Style};
-pub use text::{measure_text, visible_width, wrap_text, truncate_text, slice_ansi};
+pub use ansi::{strip_ansi, parse_ansi, colorize, style, Color, Style};
+pub use text::{measure_text, visible_width, char_width, wrap_text, wrap_chars, truncate_text, slice_ansi};
 pub use cursor::{show_cursor, hide_cursor, move_cursor, save_cursor, restore_cursor};
 pub use border::{BorderStyle, BorderChars, BORDER_STYLES};
+pub use glyphs::{glyph, ascii_fallback, to_ascii};
+pub use markup::{parse_markup, parse_style, escape_markup};
+pub use sanitize::{sanitize, sanitize_ansi, control_picture};
@@ -0,0 +1,84 @@
+//! Untrusted Text
+//!
+//! Text from files, the network or subprocesses can carry control
+//! characters and escape sequences that would move the cursor, retitle or
+//! reconfigure the terminal if written verbatim. Sanitising shows them as
+//! visible placeholders instead: C0 controls and DEL as Unicode control
+//! pictures (`␛`, `␇`, ...) and C1 controls as `�`.
+
+use crate::core::component::TextSpan;
+
+/// Visible placeholder for a control character, or `None` for printable ones.
+pub fn control_picture(c: char) -> Option<char> {
+    match c {
+        '\x00'..='\x1F' => char::from_u32(0x2400 + c as u32),
+        '\x7F' => Some('\u{2421}'),
+        '\u{80}'..='\u{9F}' => Some('\u{FFFD}'),
+        _ => None,
+    }
+}
+
+/// Make untrusted text safe to render: newlines are kept, tabs expand to
+/// spaces and every other control character and escape sequence shows as
+/// placeholders.
+pub fn sanitize(input: &str) -> String {
+    let mut text = String::with_capacity(input.len());
+    let mut column = 0;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\r' && chars.peek() == Some(&'\n') {
+            continue;
+        }
+        push_visible(&mut text, &mut column, c);
+    }
+    text
+}
+
+/// Sanitise untrusted text, trusting only its ANSI styling: SGR colors and
+/// attributes become span styles, while any other escape sequence (cursor
+/// movement, titles, hyperlinks, ...) shows as placeholders.
+pub fn sanitize_ansi(input: &str) -> Vec<TextSpan> {
+    super::ansi::parse_escapes(input, true)
+}
+
+/// Append a character as it should show: a newline, a tab as spaces up to
+/// the next multiple of 8 columns, or a placeholder for other controls.
+pub(crate) fn push_visible(text: &mut String, column: &mut usize, c: char) {
+    match c {
+        '\n' => {
+            text.push('\n');
+            *column = 0;
+        }
+        '\t' => {
+            let spaces = 8 - *column % 8;
+            text.extend(std::iter::repeat(' ').take(spaces));
+            *column += spaces;
+        }
+        c => {
+            text.push(control_picture(c).unwrap_or(c));
+            *column += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_hostile_input() {
+        // Retitling, clearing the screen and moving the cursor
+        assert_eq!(sanitize("\x1B]0;pwned\x07\x1B[2J\x1B[H"), "␛]0;pwned␇␛[2J␛[H");
+        // 8-bit CSI, DEL, backspaces hiding text and carriage returns
+        assert_eq!(sanitize("a\u{9B}31mb\x7Fc\x08\x08d\re"), "a�31mb␡c␈␈d␍e");
+        assert_eq!(sanitize("line\r\n\tx"), "line\n        x");
+        assert!(sanitize("\x1BP+q544e\x1B\\").chars().all(|c| control_picture(c).is_none()));
+
+        // Trusting ANSI styling honors SGR but still shows everything else
+        let spans = sanitize_ansi("\x1B[1;31mred\x1B[0m\x1B]8;;http://evil\x07x\x1B[3A\x1Bc");
+        let text: String = spans.iter().map(|span| span.text.as_str()).collect();
+        assert_eq!(text, "red␛]8;;http://evil␇x␛[3A␛c");
+        assert!(spans[0].style.bold && spans.iter().all(|span| span.style.hyperlink.is_none()));
+        assert_eq!(sanitize_ansi("\x1B[31").iter().map(|span| span.text.as_str()).collect::<String>(), "␛[31");
+    }
+}
@@ -7,11 +7,34 @@ pub fn measure_text(s: &str) -> usize {
     visible_width(s)
 }
 
-/// Get the visible width of a string.
+/// Get the visible width of a string, in terminal columns.
 pub fn visible_width(s: &str) -> usize {
-    let stripped = strip_ansi(s);
-    // Simple implementation - doesn't handle Unicode width properly
-    stripped.chars().count()
+    strip_ansi(s).chars().map(char_width).sum()
+}
+
+/// Columns a character takes up in a terminal: 2 for East Asian wide
+/// characters and most emoji, 0 for combining marks and other zero-width
+/// characters, 1 otherwise.
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
 }
 
 /// Wrap text to a maximum width.
@@ -54,6 +77,31 @@ pub fn wrap_text(s: &str, max_width: usize) -> Vec<String> {
     lines
 }
 
+/// Wrap text at character boundaries to a maximum width.
+///
+/// Wide characters count two columns and move to the next line whole; one
+/// wider than `max_width` gets a line to itself.
+pub fn wrap_chars(s: &str, max_width: usize) -> Vec<String> {
+    if max_width == 0 {
+        return vec![s.to_string()];
+    }
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut width = 0;
+    for c in strip_ansi(s).chars() {
+        let w = char_width(c);
+        if width + w > max_width && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            width = 0;
+        }
+        line.push(c);
+        width += w;
+    }
+    lines.push(line);
+    lines
+}
+
 /// Truncate text to a maximum width.
 pub fn truncate_text(s: &str, max_width: usize, ellipsis: &str) -> String {
     let width = visible_width(s);
@@ -63,25 +111,25 @@ pub fn truncate_text(s: &str, max_width: usize, ellipsis: &str) -> String {
 
     let ellipsis_width = visible_width(ellipsis);
     if max_width <= ellipsis_width {
-        return ellipsis.chars().take(max_width).collect();
-    }
-
-    let target_width = max_width - ellipsis_width;
-    let mut result = String::new();
-    let mut current_width = 0;
-
-    for c in strip_ansi(s).chars() {
-        if current_width >= target_width {
-            break;
-        }
-        result.push(c);
-        current_width += 1;
+        return take_width(ellipsis, max_width);
     }
 
+    let mut result = take_width(&strip_ansi(s), max_width - ellipsis_width);
     result.push_str(ellipsis);
     result
 }
 
+/// Longest prefix of `s` that fits in `max_width` columns.
+fn take_width(s: &str, max_width: usize) -> String {
+    let mut width = 0;
+    s.chars()
+        .take_while(|&c| {
+            width += char_width(c);
+            width <= max_width
+        })
+        .collect()
+}
+
 /// Slice a string with ANSI codes preserved.
 pub fn slice_ansi(s: &str, start: usize, end: usize) -> String {
     // Simple implementation - strips ANSI and slices
@@ -97,6 +145,15 @@ mod tests {
     fn test_visible_width() {
         assert_eq!(visible_width("Hello"), 5);
         assert_eq!(visible_width("\x1B[31mHello\x1B[0m"), 5);
+        assert_eq!(visible_width("漢字a"), 5);
+    }
+
+    #[test]
+    fn test_char_width() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('漢'), 2);
+        assert_eq!(char_width('🚀'), 2);
+        assert_eq!(char_width('\u{301}'), 0);
     }
 
     #[test]
@@ -105,9 +162,19 @@ mod tests {
         assert_eq!(wrapped, vec!["Hello", "World"]);
     }
 
+    #[test]
+    fn test_wrap_chars() {
+        assert_eq!(wrap_chars("abcdefg", 3), vec!["abc", "def", "g"]);
+        assert_eq!(wrap_chars("", 3), vec![""]);
+        assert_eq!(wrap_chars("漢字ab漢", 3), vec!["漢", "字a", "b漢"]);
+        assert_eq!(wrap_chars("漢a", 1), vec!["漢", "a"]);
+    }
+
     #[test]
     fn test_truncate() {
         let truncated = truncate_text("Hello World", 8, "...");
         assert_eq!(truncated, "Hello...");
+        assert_eq!(truncate_text("漢字漢字", 6, "…"), "漢字…");
+        assert_eq!(truncate_text("漢字漢字", 4, "…"), "漢…");
     }
 }